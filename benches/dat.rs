@@ -0,0 +1,43 @@
+//! Loading of large ASCII files, like `tests/heat.dat` with more points.
+//!
+//! Run with `cargo bench --features parallel` to compare against the serial parser.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use tecio::DatFormat;
+
+const VARIABLES: [&str; 6] = ["X(M)", "Y(M)", "U(M/S)", "V(M/S)", "P(N)", "T(K)"];
+
+/// An ordered zone of `i * j` points, values are written five to a line.
+fn heat(i: usize, j: usize, packing: &str) -> String {
+    let mut text = String::from("TITLE     = \"Pin Geometry\"\nVARIABLES = ");
+    for name in &VARIABLES {
+        text.push_str(&format!("\"{}\"\n", name));
+    }
+    text.push_str("ZONE T=\"K=    1\"\n STRANDID=0, SOLUTIONTIME=0\n");
+    text.push_str(&format!(" I={}, J={}, K=1, ZONETYPE=Ordered\n", i, j));
+    text.push_str(&format!(" DATAPACKING={}\n", packing));
+    text.push_str(" DT=(SINGLE SINGLE SINGLE SINGLE SINGLE SINGLE )\n");
+    let values = i * j * VARIABLES.len();
+    for n in 0..values {
+        let value = 1.0 + (n as f64 * 0.618_033_988_7).fract() * 8.0;
+        text.push_str(&format!(" {:.9}E+000", value));
+        if n % 5 == 4 || n + 1 == values {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+fn read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dat");
+    group.sample_size(10);
+    for &packing in &["BLOCK", "POINT"] {
+        let text = heat(210, 500, packing);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_function(packing, |b| b.iter(|| DatFormat::read(&text).unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, read);
+criterion_main!(benches);
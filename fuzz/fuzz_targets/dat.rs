@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tecio::DatFormat;
+
+fuzz_target!(|text: &str| {
+    let _ = DatFormat::read(text);
+});
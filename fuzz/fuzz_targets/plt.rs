@@ -0,0 +1,15 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tecio::PltFormat;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(plt) = PltFormat::read(data) {
+        for zone in 1..=plt.zones.len() {
+            for var in 1..=plt.dataset.var_names.len() {
+                let _ = plt.get_data(zone, var);
+            }
+            let _ = plt.get_connectivity(zone);
+            let _ = plt.get_poly_faces(zone);
+        }
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tecio::SzlFormat;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(szl) = SzlFormat::read(data) {
+        for zone in 1..=szl.zones.len() {
+            for var in 1..=szl.dataset.var_names.len() {
+                let _ = szl.get_data(zone, var);
+            }
+            let _ = szl.get_connectivity(zone);
+        }
+    }
+});
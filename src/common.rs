@@ -2,15 +2,33 @@ use crate::{ TecReader};
 use libc::c_char;
 use std::{
     borrow::Cow,
-    convert::From,
+    collections::BTreeMap,
+    convert::{From, TryFrom},
     ffi::{c_void, CString, OsStr},
     marker::PhantomData,
+    fmt,
     ptr::null_mut,
 };
 use thiserror::{Error};
 
 pub type Result<T> = std::result::Result<T, TecioError>;
 
+/// Implements a fallible conversion from the integer values stored in files.
+macro_rules! try_from_i32 {
+    ($name: ident { $($variant: ident),+ $(,)? }) => {
+        impl std::convert::TryFrom<i32> for $name {
+            type Error = ParseError;
+
+            fn try_from(value: i32) -> std::result::Result<Self, Self::Error> {
+                $(if value == $name::$variant as i32 {
+                    return Ok($name::$variant);
+                })+
+                Err(ParseError::UnexpectedValue(value))
+            }
+        }
+    };
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 #[repr(i32)]
 pub enum ZoneType {
@@ -44,15 +62,9 @@ impl ZoneType {
     }
 }
 
-impl From<i32> for ZoneType {
-    fn from(value: i32) -> Self {
-        if value <= 7 && value >= 0 {
-            unsafe { std::mem::transmute(value) }
-        } else {
-            panic!("Wrong integer for zone type!")
-        }
-    }
-}
+try_from_i32!(ZoneType {
+    Ordered, FELine, FETriangle, FEQuad, FETetra, FEBrick, FEPolygon, FEPolyhedron
+});
 
 #[derive(Debug, Copy, Clone)]
 #[repr(i32)]
@@ -65,19 +77,7 @@ pub enum TecDataType {
     I1 = 6,
 }
 
-impl From<i32> for TecDataType {
-    fn from(i: i32) -> Self {
-        match i {
-            1 => Self::F32,
-            2 => Self::F64,
-            3 => Self::I32,
-            4 => Self::I16,
-            5 => Self::I8,
-            6 => Self::I1,
-            _ => panic!("Wrong data type!"),
-        }
-    }
-}
+try_from_i32!(TecDataType { F32, F64, I32, I16, I8, I1 });
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TecData<'a> {
@@ -91,30 +91,45 @@ pub enum TecData<'a> {
     U32(Cow<'a, [u32]>),
 }
 
+macro_rules! convert {
+    ($self: expr, $ty: ty, $name: expr, [$($var: ident),*]) => {
+        match $self {
+            $(TecData::$var(ref cow) => cow.iter().map(|&v| v as $ty).collect(),)*
+            #[allow(unreachable_patterns)]
+            d => panic!("{} values can't be converted to {}", d.data_type_name(), $name),
+        }
+    };
+}
+
 impl<'a> TecData<'a> {
-    pub(crate) fn get(&self) -> TecData<'a> {
+    /// Borrows the values for as long as `self` lives.
+    pub fn as_view(&self) -> TecDataRef<'_> {
         match self {
-            TecData::F64(ref cow) => match cow {
-                Cow::Owned(ref owned) => TecData::F64(Cow::Borrowed(unsafe {
-                    std::mem::transmute(owned.as_slice())
-                })),
-                Cow::Borrowed(bor) => TecData::F64(Cow::Borrowed(*bor)),
-            },
-            TecData::F32(ref cow) => match cow {
-                Cow::Owned(ref owned) => TecData::F32(Cow::Borrowed(unsafe {
-                    std::mem::transmute(owned.as_slice())
-                })),
-                Cow::Borrowed(bor) => TecData::F32(Cow::Borrowed(*bor)),
-            },
-            TecData::I32(ref cow) => match cow {
-                Cow::Owned(ref owned) => TecData::I32(Cow::Borrowed(unsafe {
-                    std::mem::transmute(owned.as_slice())
-                })),
-                Cow::Borrowed(bor) => TecData::I32(Cow::Borrowed(*bor)),
-            },
-            _ => unimplemented!(),
+            TecData::F64(v) => TecDataRef::F64(v),
+            TecData::F32(v) => TecDataRef::F32(v),
+            TecData::I64(v) => TecDataRef::I64(v),
+            TecData::I32(v) => TecDataRef::I32(v),
+            TecData::I16(v) => TecDataRef::I16(v),
+            TecData::I8(v) => TecDataRef::I8(v),
+            TecData::U64(v) => TecDataRef::U64(v),
+            TecData::U32(v) => TecDataRef::U32(v),
         }
     }
+
+    /// Copies borrowed values, so that the data no longer depends on the file it was read from.
+    pub fn into_owned(self) -> TecData<'static> {
+        match self {
+            TecData::F64(v) => TecData::F64(Cow::Owned(v.into_owned())),
+            TecData::F32(v) => TecData::F32(Cow::Owned(v.into_owned())),
+            TecData::I64(v) => TecData::I64(Cow::Owned(v.into_owned())),
+            TecData::I32(v) => TecData::I32(Cow::Owned(v.into_owned())),
+            TecData::I16(v) => TecData::I16(Cow::Owned(v.into_owned())),
+            TecData::I8(v) => TecData::I8(Cow::Owned(v.into_owned())),
+            TecData::U64(v) => TecData::U64(Cow::Owned(v.into_owned())),
+            TecData::U32(v) => TecData::U32(Cow::Owned(v.into_owned())),
+        }
+    }
+
     pub fn len(&self) -> usize {
         use TecData::*;
         match self {
@@ -128,36 +143,230 @@ impl<'a> TecData<'a> {
             U32(c) => c.len(),
         }
     }
-    pub fn as_f32(&self) -> Vec<f32>{
-        match self{
-            TecData::F32(ref cow) => {
-                cow.clone().into_owned()
-            },
-            TecData::F64(ref cow) => {
-                cow.iter().map(|v| *v as f32).collect()
-            }
-            _ => unimplemented!(),
+
+    /// Name of the stored type, as used in messages.
+    pub(crate) fn data_type_name(&self) -> &'static str {
+        use TecData::*;
+        match self {
+            F64(_) => "f64",
+            F32(_) => "f32",
+            I64(_) => "i64",
+            I32(_) => "i32",
+            I16(_) => "i16",
+            I8(_) => "i8",
+            U64(_) => "u64",
+            U32(_) => "u32",
         }
     }
 
-    pub fn as_f64(&self) -> Vec<f64>{
-        match self{
-            TecData::F32(ref cow) => {
-                cow.iter().map(|v| *v as f64).collect()
-            },
-            TecData::F64(ref cow) => {
-                cow.clone().into_owned()
+    /// Type the values are stored as in Tecplot files, `None` for types only used for node maps.
+    pub fn data_type(&self) -> Option<TecDataType> {
+        match self {
+            TecData::F64(_) => Some(TecDataType::F64),
+            TecData::F32(_) => Some(TecDataType::F32),
+            TecData::I32(_) => Some(TecDataType::I32),
+            TecData::I16(_) => Some(TecDataType::I16),
+            TecData::I8(_) => Some(TecDataType::I8),
+            _ => None,
+        }
+    }
+
+    /// Values as `f32`. `f64` values outside its range become infinite and 64 bit integers lose
+    /// precision, use [`TecData::to_vec`] to catch values that don't fit.
+    pub fn as_f32(&self) -> Vec<f32> {
+        match self {
+            // Bytes are unsigned.
+            TecData::I8(v) => v.iter().map(|&v| v as u8 as f32).collect(),
+            _ => convert!(self, f32, "f32", [F64, F32, I64, I32, I16, U64, U32]),
+        }
+    }
+
+    /// Values as `f64`, 64 bit integers beyond 2^53 are rounded.
+    pub fn as_f64(&self) -> Vec<f64> {
+        match self {
+            TecData::I8(v) => v.iter().map(|&v| v as u8 as f64).collect(),
+            _ => convert!(self, f64, "f64", [F64, F32, I64, I32, I16, U64, U32]),
+        }
+    }
+
+    /// Values as `i64`, see [`TecData::to_vec`]. Panics if a value doesn't fit.
+    #[deprecated(note = "use `to_vec::<i64>`, which returns an error for values that don't fit")]
+    pub fn as_i64(&self) -> Vec<i64> {
+        self.to_vec().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Values as `i32`, see [`TecData::to_vec`]. Panics if a value doesn't fit.
+    #[deprecated(note = "use `to_vec::<i32>`, which returns an error for values that don't fit")]
+    pub fn as_i32(&self) -> Vec<i32> {
+        self.to_vec().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Values as `i16`, see [`TecData::to_vec`]. Panics if a value doesn't fit.
+    #[deprecated(note = "use `to_vec::<i16>`, which returns an error for values that don't fit")]
+    pub fn as_i16(&self) -> Vec<i16> {
+        self.to_vec().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Values as `i8`, see [`TecData::to_vec`]. Panics if a value doesn't fit.
+    #[deprecated(note = "use `to_vec::<i8>`, which returns an error for values that don't fit")]
+    pub fn as_i8(&self) -> Vec<i8> {
+        self.to_vec().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Byte and bit values as stored, 0 to 255. Panics for any other data type.
+    #[deprecated(note = "use `to_vec::<u8>`, which returns an error for values that don't fit")]
+    pub fn as_u8(&self) -> Vec<u8> {
+        convert!(self, u8, "u8", [I8])
+    }
+
+    /// Converts every value to `T`. Widening is lossless, integers become floats with rounding,
+    /// while floats only become integers when they are whole and in range and `f64` only becomes
+    /// `f32` within its range. Bytes and bits are unsigned. Fails at the first value that doesn't
+    /// fit.
+    pub fn to_vec<T: TecValue>(&self) -> Result<Vec<T>> {
+        fn convert<S: Copy, T: TecValue>(
+            values: &[S],
+            from: &'static str,
+            f: impl Fn(S) -> Option<T>,
+        ) -> Result<Vec<T>> {
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, &v)| f(v).ok_or(TecioError::Conversion { index, from, to: T::NAME }))
+                .collect()
+        }
+        let from = self.data_type_name();
+        match self {
+            TecData::F64(v) => convert(v, from, T::from_f64),
+            TecData::F32(v) => convert(v, from, |v| T::from_f64(v as f64)),
+            TecData::I64(v) => convert(v, from, T::from_i64),
+            TecData::I32(v) => convert(v, from, |v| T::from_i64(v as i64)),
+            TecData::I16(v) => convert(v, from, |v| T::from_i64(v as i64)),
+            TecData::I8(v) => convert(v, from, |v| T::from_i64(v as u8 as i64)),
+            TecData::U64(v) => convert(v, from, T::from_u64),
+            TecData::U32(v) => convert(v, from, |v| T::from_u64(v as u64)),
+        }
+    }
+
+    /// Borrows the values if they are stored as `T`.
+    pub fn try_as_slice<T: TecValue>(&self) -> Result<&[T]> {
+        T::slice(self).ok_or(TecioError::WrongDataType {
+            expected: T::NAME,
+            found: self.data_type_name(),
+        })
+    }
+}
+
+/// Types of the values [`TecData`] holds.
+pub trait TecValue: Copy + 'static {
+    const NAME: &'static str;
+    /// The values of `data`, if stored as `Self`.
+    fn slice<'s>(data: &'s TecData<'_>) -> Option<&'s [Self]>;
+    /// Conversions for [`TecData::to_vec`], `None` if the value doesn't fit.
+    fn from_f64(v: f64) -> Option<Self>;
+    fn from_i64(v: i64) -> Option<Self>;
+    fn from_u64(v: u64) -> Option<Self>;
+}
+
+macro_rules! float_value {
+    ($ty: ident, $var: ident, $in_range: expr) => {
+        impl TecValue for $ty {
+            const NAME: &'static str = stringify!($ty);
+            fn slice<'s>(data: &'s TecData<'_>) -> Option<&'s [Self]> {
+                match data {
+                    TecData::$var(v) => Some(v),
+                    _ => None,
+                }
+            }
+            fn from_f64(v: f64) -> Option<Self> {
+                Some(v as $ty).filter(|_| $in_range(v))
+            }
+            fn from_i64(v: i64) -> Option<Self> {
+                Some(v as $ty)
+            }
+            fn from_u64(v: u64) -> Option<Self> {
+                Some(v as $ty)
+            }
+        }
+    };
+}
+
+/// Types without a variant of their own, like `u8`, can only be converted to.
+macro_rules! integer_value {
+    ($ty: ident $(, $var: ident)?) => {
+        impl TecValue for $ty {
+            const NAME: &'static str = stringify!($ty);
+            fn slice<'s>(data: &'s TecData<'_>) -> Option<&'s [Self]> {
+                match data {
+                    $(TecData::$var(v) => Some(v),)?
+                    _ => None,
+                }
+            }
+            fn from_f64(v: f64) -> Option<Self> {
+                // Casts saturate, so whole values in range are the ones that survive the trip.
+                let whole = v as i128;
+                if whole as f64 == v {
+                    $ty::try_from(whole).ok()
+                } else {
+                    None
+                }
+            }
+            fn from_i64(v: i64) -> Option<Self> {
+                $ty::try_from(v).ok()
+            }
+            fn from_u64(v: u64) -> Option<Self> {
+                $ty::try_from(v).ok()
             }
-            _ => unimplemented!(),
         }
+    };
+}
+
+float_value!(f64, F64, |_| true);
+// Infinities and NaN are kept, finite values have to be within range.
+float_value!(f32, F32, |v: f64| !v.is_finite() || v.abs() <= f32::MAX as f64);
+integer_value!(i64, I64);
+integer_value!(i32, I32);
+integer_value!(i16, I16);
+integer_value!(i8, I8);
+integer_value!(u64, U64);
+integer_value!(u32, U32);
+// Byte and bit values, stored as `i8`.
+integer_value!(u8);
+
+/// Values borrowed from a [`TecData`], see [`TecData::as_view`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TecDataRef<'a> {
+    F64(&'a [f64]),
+    F32(&'a [f32]),
+    I64(&'a [i64]),
+    I32(&'a [i32]),
+    I16(&'a [i16]),
+    I8(&'a [i8]),
+    U64(&'a [u64]),
+    U32(&'a [u32]),
+}
+
+impl<'a> TecDataRef<'a> {
+    pub fn len(&self) -> usize {
+        TecData::from(*self).len()
     }
 
-    pub fn as_i32(&self) -> Vec<i32>{
-        match self{
-            TecData::I32(ref cow) => {
-                cow.clone().into_owned()
-            },
-            _ => unimplemented!(),
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> From<TecDataRef<'a>> for TecData<'a> {
+    fn from(view: TecDataRef<'a>) -> Self {
+        match view {
+            TecDataRef::F64(v) => TecData::F64(Cow::Borrowed(v)),
+            TecDataRef::F32(v) => TecData::F32(Cow::Borrowed(v)),
+            TecDataRef::I64(v) => TecData::I64(Cow::Borrowed(v)),
+            TecDataRef::I32(v) => TecData::I32(Cow::Borrowed(v)),
+            TecDataRef::I16(v) => TecData::I16(Cow::Borrowed(v)),
+            TecDataRef::I8(v) => TecData::I8(Cow::Borrowed(v)),
+            TecDataRef::U64(v) => TecData::U64(Cow::Borrowed(v)),
+            TecDataRef::U32(v) => TecData::U32(Cow::Borrowed(v)),
         }
     }
 }
@@ -217,13 +426,15 @@ pub enum FileType {
     SolutionOnly(*mut c_void),
 }
 
-impl From<i32> for FileType {
-    fn from(i: i32) -> Self {
+impl TryFrom<i32> for FileType {
+    type Error = ParseError;
+
+    fn try_from(i: i32) -> std::result::Result<Self, Self::Error> {
         match i {
-            0 => Self::Full,
-            1 => Self::GridOnly,
-            2 => Self::SolutionOnly(null_mut()),
-            _ => panic!("Wrong file type"),
+            0 => Ok(Self::Full),
+            1 => Ok(Self::GridOnly),
+            2 => Ok(Self::SolutionOnly(null_mut())),
+            _ => Err(ParseError::UnexpectedValue(i)),
         }
     }
 }
@@ -238,22 +449,14 @@ impl FileType {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, PartialEq, Copy, Clone)]
 #[repr(i32)]
 pub enum ValueLocation {
     CellCentered = 0,
     Nodal = 1,
 }
 
-impl From<i32> for ValueLocation{
-    fn from(i: i32) -> Self {
-        match i {
-            0 => Self::CellCentered,
-            1 => Self::Nodal,
-            _ => unreachable!()
-        }
-    }
-}
+try_from_i32!(ValueLocation { CellCentered, Nodal });
 
 #[derive(Debug, Copy, Clone)]
 #[repr(i32)]
@@ -264,14 +467,14 @@ pub enum FaceNeighborMode {
     GlobalOneToMany,
 }
 
-impl From<i32> for FaceNeighborMode {
-    fn from(value: i32) -> Self {
-        if value <= 3 && value >= 0 {
-            unsafe { std::mem::transmute(value) }
-        } else {
-            panic!("Wrong integer for FaceNeighborMode!")
-        }
-    }
+try_from_i32!(FaceNeighborMode {
+    LocalOneToOne, LocalOneToMany, GlobalOneToOne, GlobalOneToMany
+});
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DataPacking {
+    Point,
+    Block,
 }
 /*pub struct TecZone{
     pub name: String,
@@ -283,6 +486,28 @@ impl From<i32> for FaceNeighborMode {
     pub k_max: i64,
 }*/
 
+/// nom's `count`, failing instead of allocating when `input` is too short to hold `n` items of at
+/// least `size` bytes each.
+pub(crate) fn sized_count<I, O, F>(
+    parser: F,
+    n: usize,
+    size: usize,
+) -> impl Fn(I) -> nom::IResult<I, Vec<O>, ParseError>
+where
+    I: Clone + PartialEq + nom::InputLength,
+    F: Fn(I) -> nom::IResult<I, O, ParseError>,
+    ParseError: nom::error::ParseError<I>,
+{
+    let items = nom::multi::count(parser, n);
+    move |input: I| {
+        let available = input.input_len();
+        if n.saturating_mul(size) > available {
+            return Err(nom::Err::Error(ParseError::TooLarge { count: n, available }));
+        }
+        items(input)
+    }
+}
+
 pub fn try_err<S: ToString>(er: i32, message: S) -> Result<()> {
     if er != 0 {
         Err(TecioError::Other {
@@ -294,6 +519,9 @@ pub fn try_err<S: ToString>(er: i32, message: S) -> Result<()> {
     }
 }
 
+/// Auxiliary name/value pairs, e.g. solver settings or reference values.
+pub type AuxData = BTreeMap<String, String>;
+
 #[derive(Debug, Clone)]
 pub struct Dataset {
     pub num_variables: i32,
@@ -303,6 +531,15 @@ pub struct Dataset {
 
     pub var_names: Vec<String>,
     //pub zone_names: Vec<String>,
+    pub aux_data: AuxData,
+    /// Auxiliary data of every variable, empty if no variable has any.
+    pub var_aux_data: Vec<AuxData>,
+    pub geometries: Vec<Geometry>,
+    pub texts: Vec<TextAnnotation>,
+    /// Label sets used instead of numbers, e.g. on axes or contour legends.
+    pub custom_labels: Vec<Vec<String>>,
+    /// Arbitrary strings stored with the dataset.
+    pub user_records: Vec<String>,
 }
 
 impl Dataset {
@@ -313,8 +550,19 @@ impl Dataset {
             title: "".to_string(),
             var_names: vec![],
             //zone_names: vec![]
+            aux_data: AuxData::new(),
+            var_aux_data: vec![],
+            geometries: vec![],
+            texts: vec![],
+            custom_labels: vec![],
+            user_records: vec![],
         }
     }
+
+    /// Auxiliary data of the one-based variable `var_id`.
+    pub fn var_aux_data(&self, var_id: usize) -> Option<&AuxData> {
+        self.var_aux_data.get(var_id - 1)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -329,7 +577,7 @@ impl TecZone {
         match self {
             TecZone::Ordered(oz) => &oz.name,
             TecZone::ClassicFE(fe) => &fe.name,
-            _ => unimplemented!(),
+            TecZone::PolyFE(fe) => &fe.name,
         }
     }
 
@@ -337,13 +585,13 @@ impl TecZone {
         match self {
             TecZone::Ordered(_) => ZoneType::Ordered,
             TecZone::ClassicFE(fe) => fe.zone_type,
-            _ => unimplemented!(),
+            TecZone::PolyFE(fe) => fe.zone_type,
         }
     }
 
     pub fn is_fe(&self) -> bool {
         match self {
-            TecZone::ClassicFE(_) => true,
+            TecZone::ClassicFE(_) | TecZone::PolyFE(_) => true,
             _ => false,
         }
     }
@@ -351,42 +599,91 @@ impl TecZone {
         match self {
             TecZone::Ordered(z) => &z.var_location,
             TecZone::ClassicFE(z) => &z.var_location,
-            _ => unimplemented!(),
+            TecZone::PolyFE(z) => &z.var_location,
         }
     }
     pub fn solution_time(&self) -> f64{
         match self{
             TecZone::Ordered(z) => z.solution_time,
             TecZone::ClassicFE(z) => z.solution_time,
-            _ => unimplemented!()
+            TecZone::PolyFE(z) => z.solution_time,
         }
     }
     pub fn node_count(&self) -> usize {
         match self {
-            TecZone::Ordered(z) => (z.i_max * z.j_max * z.k_max) as _,
+            TecZone::Ordered(z) => z.i_max.saturating_mul(z.j_max).saturating_mul(z.k_max) as _,
             TecZone::ClassicFE(z) => z.nodes as _,
-            _ => unimplemented!(),
+            TecZone::PolyFE(z) => z.nodes as _,
         }
     }
     pub fn cell_count(&self) -> usize {
         match self {
             TecZone::Ordered(z) => z.cell_count(),
             TecZone::ClassicFE(z) => z.cells as _,
-            _ => unimplemented!(),
+            TecZone::PolyFE(z) => z.cells as _,
         }
     }
     pub fn data_types(&self) -> Option<&[TecDataType]> {
         match self {
             TecZone::Ordered(z) => z.var_types.as_ref().map(|v| v.as_slice()),
             TecZone::ClassicFE(z) => z.var_types.as_ref().map(|v| v.as_slice()),
-            _ => unimplemented!(),
+            TecZone::PolyFE(z) => z.var_types.as_ref().map(|v| v.as_slice()),
+        }
+    }
+    /// One-based id of the zone the variable `var_id` is shared from, if it is shared.
+    pub fn shared_from(&self, var_id: usize) -> Option<usize> {
+        let list = match self {
+            TecZone::Ordered(z) => &z.var_share_list,
+            TecZone::ClassicFE(z) => &z.var_share_list,
+            TecZone::PolyFE(z) => &z.var_share_list,
+        };
+        list.get(var_id - 1).filter(|&&z| z > 0).map(|&z| z as usize)
+    }
+    /// Passive variables are flagged with 1, an empty list means there are none.
+    pub fn passive_var_list(&self) -> &[i32] {
+        match self {
+            TecZone::Ordered(z) => &z.passive_var_list,
+            TecZone::ClassicFE(z) => &z.passive_var_list,
+            TecZone::PolyFE(z) => &z.passive_var_list,
+        }
+    }
+    /// Whether the one-based variable `var_id` is passive, i.e. has no values in this zone.
+    pub fn is_passive(&self, var_id: usize) -> bool {
+        self.passive_var_list().get(var_id - 1) == Some(&1)
+    }
+    /// One-based id of the zone's parent zone, if it has one.
+    pub fn parent_zone(&self) -> Option<usize> {
+        let parent = match self {
+            TecZone::Ordered(z) => z.parent_zone,
+            TecZone::ClassicFE(z) => z.parent_zone,
+            TecZone::PolyFE(z) => z.parent_zone,
+        };
+        Some(parent).filter(|&z| z > 0).map(|z| z as usize)
+    }
+    /// One-based id of the zone the connectivity is shared from, if it is shared.
+    pub fn connectivity_shared_from(&self) -> Option<usize> {
+        match self {
+            TecZone::ClassicFE(z) if z.connectivity_share_zone > 0 => {
+                Some(z.connectivity_share_zone as usize)
+            }
+            TecZone::PolyFE(z) if z.connectivity_share_zone > 0 => {
+                Some(z.connectivity_share_zone as usize)
+            }
+            _ => None,
+        }
+    }
+    pub fn aux_data(&self) -> &AuxData {
+        match self {
+            TecZone::Ordered(z) => &z.aux_data,
+            TecZone::ClassicFE(z) => &z.aux_data,
+            TecZone::PolyFE(z) => &z.aux_data,
         }
     }
     pub fn data_types_mut(&mut self) -> &mut Option<Vec<TecDataType>> {
         match self {
             TecZone::Ordered(z) => &mut z.var_types,
             TecZone::ClassicFE(z) => &mut z.var_types,
-            _ => unimplemented!(),
+            TecZone::PolyFE(z) => &mut z.var_types,
         }
     }
 }
@@ -396,7 +693,7 @@ impl Zone for TecZone {
         match self{
             TecZone::Ordered(z) => z.id(),
             TecZone::ClassicFE(z) => z.id(),
-            _ => unimplemented!()
+            TecZone::PolyFE(z) => z.id(),
         }
     }
     fn time(&self) -> f64 {
@@ -414,19 +711,22 @@ pub struct OrderedZone {
 
     pub solution_time: f64,
     pub strand: i32,
+    /// One-based id of the parent zone, 0 if the zone has none.
+    pub parent_zone: i32,
     pub i_max: i64,
     pub j_max: i64,
     pub k_max: i64,
     pub var_location: Vec<ValueLocation>,
     pub var_types: Option<Vec<TecDataType>>,
+    pub var_share_list: Vec<i32>,
     pub passive_var_list: Vec<i32>,
+    pub aux_data: AuxData,
 }
 
 impl OrderedZone {
     fn cell_count(&self) -> usize {
-        (if self.i_max != 1 { self.i_max - 1 } else { 1 }
-            * if self.j_max != 1 { self.j_max - 1 } else { 1 }
-            * if self.k_max != 1 { self.k_max - 1 } else { 1 }) as _
+        let cells = |max: i64| if max != 1 { max - 1 } else { 1 };
+        cells(self.i_max).saturating_mul(cells(self.j_max)).saturating_mul(cells(self.k_max)) as _
     }
 }
 impl Zone for OrderedZone {
@@ -448,17 +748,24 @@ pub struct ClassicFEZone {
     pub id: i32,
     pub solution_time: f64,
     pub strand: i32,
+    /// One-based id of the parent zone, 0 if the zone has none.
+    pub parent_zone: i32,
 
     pub nodes: i64,
     pub cells: i64,
 
     pub var_location: Vec<ValueLocation>,
     pub var_types: Option<Vec<TecDataType>>,
+    pub var_share_list: Vec<i32>,
+    pub passive_var_list: Vec<i32>,
+    /// One-based id of the zone the connectivity is shared from, 0 if the zone has its own.
+    pub connectivity_share_zone: i32,
+    pub aux_data: AuxData,
 }
 
 impl ClassicFEZone {
     pub fn num_connections(&self) -> usize {
-        self.cells as usize * self.zone_type.num_nodes()
+        (self.cells as usize).saturating_mul(self.zone_type.num_nodes())
     }
 }
 
@@ -474,8 +781,377 @@ impl Zone for ClassicFEZone {
     }
 }
 
+/// Polygonal or polyhedral zone, cells are described by their faces.
 #[derive(Debug, Clone)]
-pub struct PolyFE {}
+pub struct PolyFE {
+    pub name: String,
+    pub zone_type: ZoneType,
+    pub id: i32,
+    pub solution_time: f64,
+    pub strand: i32,
+    /// One-based id of the parent zone, 0 if the zone has none.
+    pub parent_zone: i32,
+
+    pub nodes: i64,
+    pub cells: i64,
+    pub faces: i64,
+    /// Sum of node counts of all faces, `2 * faces` for polygons.
+    pub total_num_face_nodes: i64,
+    pub num_connected_boundary_faces: i64,
+    pub total_num_boundary_connections: i64,
+
+    pub var_location: Vec<ValueLocation>,
+    pub var_types: Option<Vec<TecDataType>>,
+    pub var_share_list: Vec<i32>,
+    pub passive_var_list: Vec<i32>,
+    /// One-based id of the zone the face data is shared from, 0 if the zone has its own.
+    pub connectivity_share_zone: i32,
+    pub aux_data: AuxData,
+}
+
+impl Zone for PolyFE {
+    fn id(&self) -> i32 {
+        self.id
+    }
+    fn time(&self) -> f64 {
+        self.solution_time
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Face connectivity of a [`PolyFE`] zone, all indices are one-based.
+///
+/// A right element of 0 marks a face without a neighbor, a negative one `-n` refers to the
+/// `n`-th connected boundary face, whose connections are described by the `boundary_*` arrays.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolyFaces {
+    /// Nodes of every face, always 2 for polygons.
+    pub face_node_counts: Vec<i32>,
+    pub face_nodes: Vec<i64>,
+    pub face_left_elements: Vec<i64>,
+    pub face_right_elements: Vec<i64>,
+    /// Number of connections of every connected boundary face.
+    pub boundary_connection_counts: Vec<i32>,
+    pub boundary_connection_elements: Vec<i64>,
+    pub boundary_connection_zones: Vec<i32>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum CoordSys {
+    Grid = 0,
+    Frame = 1,
+    Grid3D = 4,
+}
+try_from_i32!(CoordSys { Grid, Frame, Grid3D });
+
+/// Whether an annotation is shown in every frame using the dataset or only in its own.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum Scope {
+    Global = 0,
+    Local = 1,
+}
+try_from_i32!(Scope { Global, Local });
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum DrawOrder {
+    AfterData = 0,
+    BeforeData = 1,
+}
+try_from_i32!(DrawOrder { AfterData, BeforeData });
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum Clipping {
+    ClipToAxes = 0,
+    ClipToViewport = 1,
+    ClipToFrame = 2,
+}
+try_from_i32!(Clipping { ClipToAxes, ClipToViewport, ClipToFrame });
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum LinePattern {
+    Solid = 0,
+    Dashed = 1,
+    DashDot = 2,
+    DashDotDot = 3,
+    Dotted = 4,
+    LongDash = 5,
+}
+try_from_i32!(LinePattern { Solid, Dashed, DashDot, DashDotDot, Dotted, LongDash });
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum ArrowheadStyle {
+    Plain = 0,
+    Filled = 1,
+    Hollow = 2,
+}
+try_from_i32!(ArrowheadStyle { Plain, Filled, Hollow });
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum ArrowheadAttachment {
+    None = 0,
+    Beginning = 1,
+    End = 2,
+    Both = 3,
+}
+try_from_i32!(ArrowheadAttachment { None, Beginning, End, Both });
+
+/// Points of a polyline relative to the anchor of its geometry, `z` is empty for 2D lines.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Polyline {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub z: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeometryShape {
+    /// One or more polylines, 3D lines use [`CoordSys::Grid3D`].
+    Lines(Vec<Polyline>),
+    /// Anchored at the lower left corner.
+    Rectangle { width: f64, height: f64 },
+    /// Anchored at the lower left corner.
+    Square { size: f64 },
+    /// Anchored at the center.
+    Circle { radius: f64 },
+    /// Anchored at the center.
+    Ellipse { horizontal_axis: f64, vertical_axis: f64 },
+}
+
+impl GeometryShape {
+    pub(crate) fn type_id(&self) -> i32 {
+        match self {
+            GeometryShape::Lines(_) => 0,
+            GeometryShape::Rectangle { .. } => 1,
+            GeometryShape::Square { .. } => 2,
+            GeometryShape::Circle { .. } => 3,
+            GeometryShape::Ellipse { .. } => 4,
+        }
+    }
+}
+
+/// Geometry annotation stored alongside the data, e.g. a probe location or a domain outline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Geometry {
+    pub shape: GeometryShape,
+    pub anchor: [f64; 3],
+    pub coord_sys: CoordSys,
+    pub scope: Scope,
+    pub draw_order: DrawOrder,
+    /// One-based id of the zone the geometry is attached to.
+    pub zone: Option<i32>,
+    pub color: i32,
+    /// Fill color of closed shapes, `None` if they aren't filled.
+    pub fill_color: Option<i32>,
+    pub line_pattern: LinePattern,
+    pub pattern_length: f64,
+    pub line_thickness: f64,
+    pub num_ellipse_points: i32,
+    pub arrowhead_style: ArrowheadStyle,
+    pub arrowhead_attachment: ArrowheadAttachment,
+    pub arrowhead_size: f64,
+    pub arrowhead_angle: f64,
+    pub macro_function: String,
+    pub clipping: Clipping,
+}
+
+impl Geometry {
+    /// Black, unfilled geometry in grid coordinates with Tecplot's default line settings.
+    pub fn new(shape: GeometryShape, anchor: [f64; 3]) -> Self {
+        let coord_sys = match &shape {
+            GeometryShape::Lines(lines) if lines.iter().any(|l| !l.z.is_empty()) => {
+                CoordSys::Grid3D
+            }
+            _ => CoordSys::Grid,
+        };
+        Self {
+            shape,
+            anchor,
+            coord_sys,
+            scope: Scope::Global,
+            draw_order: DrawOrder::AfterData,
+            zone: None,
+            color: 0,
+            fill_color: None,
+            line_pattern: LinePattern::Solid,
+            pattern_length: 2.0,
+            line_thickness: 0.1,
+            num_ellipse_points: 72,
+            arrowhead_style: ArrowheadStyle::Plain,
+            arrowhead_attachment: ArrowheadAttachment::None,
+            arrowhead_size: 5.0,
+            arrowhead_angle: 12.0,
+            macro_function: String::new(),
+            clipping: Clipping::ClipToViewport,
+        }
+    }
+}
+
+/// Units of the character height of a text.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum SizeUnits {
+    Grid = 0,
+    Frame = 1,
+    Point = 2,
+}
+try_from_i32!(SizeUnits { Grid, Frame, Point });
+
+/// Which point of a text is placed at its anchor position.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum TextAnchor {
+    Left = 0,
+    Center = 1,
+    Right = 2,
+    MidLeft = 3,
+    MidCenter = 4,
+    MidRight = 5,
+    HeadLeft = 6,
+    HeadCenter = 7,
+    HeadRight = 8,
+}
+try_from_i32!(TextAnchor {
+    Left, Center, Right, MidLeft, MidCenter, MidRight, HeadLeft, HeadCenter, HeadRight
+});
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum TextBox {
+    None = 0,
+    Filled = 1,
+    Hollow = 2,
+}
+try_from_i32!(TextBox { None, Filled, Hollow });
+
+/// Typeface families with their regular, bold, italic and bold italic font ids.
+const FONTS: [(&str, [i32; 4]); 7] = [
+    ("Helvetica", [0, 1, 12, 13]),
+    ("Times", [5, 7, 6, 8]),
+    ("Courier", [9, 10, 14, 15]),
+    ("Greek", [2; 4]),
+    ("Math", [3; 4]),
+    ("User Defined", [4; 4]),
+    ("Extended", [11; 4]),
+];
+
+/// Text annotation stored alongside the data, e.g. a label or a plot caption.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextAnnotation {
+    pub text: String,
+    pub anchor: [f64; 3],
+    pub coord_sys: CoordSys,
+    pub scope: Scope,
+    pub typeface: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub size_units: SizeUnits,
+    pub height: f64,
+    /// Counterclockwise rotation in degrees.
+    pub angle: f64,
+    pub line_spacing: f64,
+    pub alignment: TextAnchor,
+    pub box_type: TextBox,
+    pub box_margin: f64,
+    pub box_line_thickness: f64,
+    pub box_color: i32,
+    pub box_fill_color: i32,
+    pub color: i32,
+    /// One-based id of the zone the text is attached to.
+    pub zone: Option<i32>,
+    pub macro_function: String,
+    pub clipping: Clipping,
+}
+
+impl TextAnnotation {
+    /// Black 14 point Helvetica text in grid coordinates without a box.
+    pub fn new<S: Into<String>>(text: S, anchor: [f64; 3]) -> Self {
+        Self {
+            text: text.into(),
+            anchor,
+            coord_sys: CoordSys::Grid,
+            scope: Scope::Global,
+            typeface: "Helvetica".to_owned(),
+            bold: false,
+            italic: false,
+            size_units: SizeUnits::Point,
+            height: 14.0,
+            angle: 0.0,
+            line_spacing: 1.0,
+            alignment: TextAnchor::Left,
+            box_type: TextBox::None,
+            box_margin: 20.0,
+            box_line_thickness: 0.1,
+            box_color: 0,
+            box_fill_color: 7,
+            color: 0,
+            zone: None,
+            macro_function: String::new(),
+            clipping: Clipping::ClipToViewport,
+        }
+    }
+
+    /// Font id of the binary format, unknown typefaces fall back to Helvetica.
+    pub(crate) fn font_id(&self) -> i32 {
+        let fonts = FONTS
+            .iter()
+            .find(|(family, _)| family.eq_ignore_ascii_case(&self.typeface))
+            .map_or(FONTS[0].1, |(_, fonts)| *fonts);
+        fonts[self.bold as usize + 2 * self.italic as usize]
+    }
+
+    pub(crate) fn set_font_id(&mut self, id: i32) -> std::result::Result<(), ParseError> {
+        let (family, style) = FONTS
+            .iter()
+            .find_map(|(family, fonts)| {
+                fonts.iter().position(|&f| f == id).map(|style| (family, style))
+            })
+            .ok_or(ParseError::UnexpectedValue(id))?;
+        self.typeface = family.to_string();
+        self.bold = style % 2 == 1;
+        self.italic = style >= 2;
+        Ok(())
+    }
+}
+
+/// Joins labels into the `"a" "b"` list tecio uses for custom label sets.
+pub(crate) fn quote_labels<S: AsRef<str>>(labels: &[S]) -> String {
+    labels
+        .iter()
+        .map(|label| label.as_ref().replace('\\', "\\\\").replace('"', "\\\""))
+        .map(|label| format!("\"{}\"", label))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Splits a `"a" "b"` label list, quotes inside of labels are escaped with a backslash.
+pub(crate) fn unquote_labels(labels: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut chars = labels.chars();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+        let mut label = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => label.extend(chars.next()),
+                c => label.push(c),
+            }
+        }
+        result.push(label);
+    }
+    result
+}
 
 pub trait Zone {
     fn id(&self) -> i32;
@@ -484,7 +1160,7 @@ pub trait Zone {
 }
 
 
-#[derive(Debug, Copy, Clone, Error)]
+#[derive(Debug, Copy, Clone, PartialEq, Error)]
 pub enum ParseError {
     #[error("Header Version Missing")]
     HeaderVersionMissing,
@@ -500,18 +1176,27 @@ pub enum ParseError {
     WrongDataTag,
     #[error("Unexpected end of header")]
     EndOfHeader,
+    #[error("Unexpected value {0}")]
+    UnexpectedValue(i32),
+    #[error("Unknown record marker {0}")]
+    UnknownMarker(f32),
+    #[error("Invalid {0}")]
+    InvalidValue(&'static str),
+    #[error("Index {index} outside of 1..={max}")]
+    IndexOutOfRange { index: i64, max: i64 },
+    #[error("{count} values don't fit into the remaining {available} bytes")]
+    TooLarge { count: usize, available: usize },
     #[error("Nom Error of kind: {}", .0.description())]
     NomError(nom::error::ErrorKind),
 }
 
 impl nom::error::ParseError<&[u8]> for ParseError {
-    fn from_error_kind(input: &[u8], kind: nom::error::ErrorKind) -> Self {
-        println!("{:?} {:?}", input, kind);
-        unimplemented!()
+    fn from_error_kind(_input: &[u8], kind: nom::error::ErrorKind) -> Self {
+        ParseError::NomError(kind)
     }
 
-    fn append(input: &[u8], kind: nom::error::ErrorKind, other: Self) -> Self {
-        unimplemented!()
+    fn append(_input: &[u8], _kind: nom::error::ErrorKind, other: Self) -> Self {
+        other
     }
 }
 
@@ -543,8 +1228,168 @@ pub enum TecioError {
     IOError(#[from] std::io::Error),
     #[error("Error during parsing: {0}")]
     ParseError(#[from] ParseError),
+    /// Malformed file contents, `zone` and `var` are one-based.
+    #[error("{error} at {location}{}", context(*.zone, *.var))]
+    Malformed {
+        error: ParseError,
+        location: Location,
+        zone: Option<usize>,
+        var: Option<usize>,
+    },
     #[error("Nom Error: {0}")]
     NomErr(#[from] nom::Err<ParseError>),
     #[error("VMAP Error: {0}")]
     VmapError(#[from] vmap::Error),
+    /// A value of [`TecData`] doesn't fit into the requested type.
+    #[error("{from} value at index {index} doesn't fit into {to}")]
+    Conversion { index: usize, from: &'static str, to: &'static str },
+    #[error("Expected {expected} values, found {found}")]
+    WrongDataType { expected: &'static str, found: &'static str },
+}
+
+impl TecioError {
+    /// Records where in the file a parse error occurred. Errors that already know their location
+    /// only take missing zone and var ids, other errors are returned unchanged.
+    pub(crate) fn at(self, location: Location, zone: Option<usize>, var: Option<usize>) -> Self {
+        let error = match self {
+            TecioError::Malformed {
+                error,
+                location,
+                zone: z,
+                var: v,
+            } => {
+                return TecioError::Malformed {
+                    error,
+                    location,
+                    zone: z.or(zone),
+                    var: v.or(var),
+                }
+            }
+            TecioError::ParseError(error)
+            | TecioError::NomErr(nom::Err::Error(error))
+            | TecioError::NomErr(nom::Err::Failure(error)) => error,
+            TecioError::NomErr(nom::Err::Incomplete(_)) => {
+                ParseError::NomError(nom::error::ErrorKind::Eof)
+            }
+            error => return error,
+        };
+        TecioError::Malformed {
+            error,
+            location,
+            zone,
+            var,
+        }
+    }
+
+    /// The parse error behind `self`, with or without its location.
+    pub fn parse_error(&self) -> Option<ParseError> {
+        match self {
+            TecioError::ParseError(error)
+            | TecioError::NomErr(nom::Err::Error(error))
+            | TecioError::NomErr(nom::Err::Failure(error))
+            | TecioError::Malformed { error, .. } => Some(*error),
+            _ => None,
+        }
+    }
+}
+
+/// Position of malformed input.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Location {
+    /// Byte offset into a binary file.
+    Offset(usize),
+    /// One-based line and column of a text file.
+    LineColumn { line: usize, column: usize },
+}
+
+impl Location {
+    /// Location of `at`, which has to be a slice of `text`.
+    pub(crate) fn in_text(text: &str, at: &str) -> Self {
+        let parsed = &text[..at.as_ptr() as usize - text.as_ptr() as usize];
+        let line_start = parsed.rfind('\n').map_or(0, |i| i + 1);
+        Location::LineColumn {
+            line: parsed.matches('\n').count() + 1,
+            column: parsed[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Offset(offset) => write!(f, "byte {}", offset),
+            Location::LineColumn { line, column } => write!(f, "line {}, column {}", line, column),
+        }
+    }
+}
+
+fn context(zone: Option<usize>, var: Option<usize>) -> String {
+    match (zone, var) {
+        (Some(zone), Some(var)) => format!(" (zone {}, var {})", zone, var),
+        (Some(zone), None) => format!(" (zone {})", zone),
+        (None, Some(var)) => format!(" (var {})", var),
+        (None, None) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_conversions() {
+        let shorts = TecData::from(vec![-300i16, 0, 300]);
+        assert_eq!(shorts.to_vec::<i64>().unwrap(), vec![-300, 0, 300]);
+        assert_eq!(shorts.to_vec::<f32>().unwrap(), vec![-300.0, 0.0, 300.0]);
+        assert_eq!(shorts.to_vec::<i32>().unwrap(), vec![-300, 0, 300]);
+        match shorts.to_vec::<i8>() {
+            Err(TecioError::Conversion { index: 0, from: "i16", to: "i8" }) => {}
+            r => panic!("Expected -300 not to fit into i8, got {:?}", r),
+        }
+        assert!(shorts.to_vec::<u32>().is_err());
+
+        let big = TecData::from(vec![1u64, u64::MAX]);
+        assert_eq!(big.to_vec::<u64>().unwrap(), vec![1, u64::MAX]);
+        assert!(matches!(big.to_vec::<i64>(), Err(TecioError::Conversion { index: 1, .. })));
+        assert_eq!(big.as_f64(), vec![1.0, u64::MAX as f64]);
+
+        let floats = TecData::from(vec![2.0f64, -1e10]);
+        assert_eq!(floats.to_vec::<i64>().unwrap(), vec![2, -10_000_000_000]);
+        assert!(floats.to_vec::<i32>().is_err());
+        assert!(TecData::from(vec![0.5f64]).to_vec::<i64>().is_err());
+        assert!(TecData::from(vec![f64::NAN]).to_vec::<i32>().is_err());
+        assert!(TecData::from(vec![1e300f64]).to_vec::<f32>().is_err());
+        let special = TecData::from(vec![f64::INFINITY, f64::NAN]).to_vec::<f32>().unwrap();
+        assert!(special[0].is_infinite() && special[1].is_nan());
+
+        let bytes = TecData::from(vec![0i8, 127, -56]);
+        assert_eq!(bytes.to_vec::<i32>().unwrap(), vec![0, 127, 200]);
+        assert_eq!(bytes.as_f64(), vec![0.0, 127.0, 200.0]);
+        assert_eq!(bytes.as_f32(), vec![0.0, 127.0, 200.0]);
+        assert!(matches!(bytes.to_vec::<i8>(), Err(TecioError::Conversion { index: 2, .. })));
+        assert_eq!(bytes.to_vec::<u8>().unwrap(), vec![0, 127, 200]);
+        assert!(shorts.to_vec::<u8>().is_err());
+        assert!(bytes.try_as_slice::<u8>().is_err());
+
+        assert_eq!(shorts.try_as_slice::<i16>().unwrap(), &[-300, 0, 300]);
+        match shorts.try_as_slice::<f64>() {
+            Err(TecioError::WrongDataType { expected: "f64", found: "i16" }) => {}
+            r => panic!("Expected shorts not to borrow as f64, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn data_views() {
+        let owned = TecData::from(vec![1.0f32, 2.0]);
+        let view = owned.as_view();
+        assert_eq!(view, TecDataRef::F32(&[1.0, 2.0]));
+        assert_eq!(view.len(), 2);
+        assert_eq!(TecData::from(view), owned);
+
+        let dat = crate::DatFormat::read("VARIABLES = \"X\"\nZONE I=3\n1 2 3\n").unwrap();
+        let data = dat.get_data(1, 1).into_owned();
+        drop(dat);
+        let sum = std::thread::spawn(move || data.as_f64().iter().sum::<f64>()).join().unwrap();
+        assert_eq!(sum, 6.0);
+    }
 }
@@ -1,6 +1,8 @@
 mod dat;
-mod plt;
+pub(crate) mod plt;
+mod szl;
 mod szplt;
-pub use dat::{DatFormat};
+pub use dat::{DatFormat, DatZone, DatZones};
 pub use plt::{PltFormat};
+pub use szl::SzlFormat;
 pub use szplt::SzpltFormat;
@@ -1,9 +1,11 @@
 use std::{
     borrow::Cow,
     convert::From,
-    fs::{read_to_string, File},
-    io::{BufRead, Cursor, Read},
+    fs::File,
+    io::{BufRead, BufReader, Cursor, Read},
     path::Path,
+    convert::TryFrom,
+    str::FromStr,
 };
 
 use nom::{
@@ -13,24 +15,30 @@ use nom::{
                     multispace0, alphanumeric1, newline, multispace1,
                 },
     },
+    branch::alt,
     combinator::{cond, map_res, not, opt},
-    multi::{count, fold_many0, many0, many1, many_till},
+    multi::{fold_many0, many0, many1, many_till},
     number::complete::{be_u8, le_f32, le_f64, le_i32, le_u32, double, float, recognize_float},
-    sequence::{tuple, separated_pair, delimited},
+    sequence::{tuple, pair, separated_pair, delimited},
     IResult,
 };
 
 use crate::{
-    common::{try_err, Dataset, OrderedZone, Result, TecDataType, TecZone, TecioError, ZoneType, ParseError},
-    ClassicFEZone, FaceNeighborMode, FileType, TecData, ValueLocation,
+    common::{try_err, AuxData, DataPacking, Dataset, Location, OrderedZone, Result, TecDataType, TecZone, TecioError, ZoneType, ParseError},
+    ArrowheadAttachment, ArrowheadStyle, Clipping, CoordSys, DrawOrder, Geometry, GeometryShape,
+    LinePattern, Polyline, Scope, SizeUnits, TextAnchor, TextAnnotation, TextBox,
+    ClassicFEZone, FaceNeighborMode, FileType, PolyFE, PolyFaces, TecData, ValueLocation,
 };
-use nom::bytes::complete::{is_not, take_till};
-use nom::character::complete::{anychar, line_ending, space1, space0};
-use nom::sequence::terminated;
+use nom::bytes::complete::{is_not, take_till, take_while1};
+use nom::character::complete::{anychar, char, digit1, line_ending, space1, space0};
+use nom::sequence::{preceded, terminated};
 use nom::multi::separated_list;
 use std::ptr::null_mut;
 use crate::formats::plt::HeaderBlock;
 
+#[cfg(feature = "parallel")]
+mod parallel;
+
 
 #[derive(Clone, Debug)]
 pub struct DatFormat {
@@ -43,12 +51,13 @@ pub struct DatFormat {
 pub(crate) struct DataBlock {
     pub(crate) data: Vec<(usize, TecData<'static>)>,
     pub(crate) connectivity: Option<TecData<'static>>,
+    pub(crate) poly_faces: Option<PolyFaces>,
     pub(crate) min_max: Vec<(f64, f64)>,
 }
 
 impl DataBlock {
-    pub fn get_data(&self, var_id: usize) -> TecData {
-        self.data[var_id].1.get()
+    pub fn get_data(&self, var_id: usize) -> TecData<'_> {
+        TecData::from(self.data[var_id].1.as_view())
     }
 }
 
@@ -80,111 +89,89 @@ enum KeyWord {
     ParentZone,
     PassiveVarList,
     AuxData,
+    DatasetAuxData,
 }
 
 #[derive(Debug, Clone)]
 enum Values<'a> {
     String(&'a str),
     StringList(Vec<&'a str>),
-    Location(Vec<(Vec<&'a str>, &'a str)>),
+    /// Variable sets with an optional value, like `[1-3]=CELLCENTERED`.
+    VarSets(Vec<(Vec<&'a str>, Option<&'a str>)>),
+    Vars(Vec<&'a str>),
     Number(f64),
+    Aux(&'a str, &'a str),
 }
 
+/// Keywords are case insensitive like the rest of the format.
 fn keyword(input: &str) -> IResult<&str, KeyWord, ParseError> {
     let (rest, word) = alphanumeric1(input)?;
-    match word {
-        "ZONE" => {
-            Err(nom::Err::Error(ParseError::EndOfHeader))
-        }
-        "TITLE" => {
-            Ok((rest, KeyWord::Title))
-        }
-        "VARIABLES" => {
-            Ok((rest, KeyWord::Variables))
-        }
-        "FILETYPE" => {
-            Ok((rest, KeyWord::FileType))
-        }
-        "T" => {
-            Ok((rest, KeyWord::T))
-        }
-        "ZONETYPE" => {
-            Ok((rest, KeyWord::ZoneType))
-        }
-        "I" => {
-            Ok((rest, KeyWord::I))
-        }
-        "J" => {
-            Ok((rest, KeyWord::J))
-        }
-        "K" => {
-            Ok((rest, KeyWord::K))
-        }
-        "Nodes" | "N" | "NODES" => {
-            Ok((rest, KeyWord::Nodes))
-        }
-        "Elements" | "ELEMENTS" | "E" => {
-            Ok((rest, KeyWord::Elements))
-        }
-        "FACES" => {
-            Ok((rest, KeyWord::Faces))
-        }
-        "TOTALNUMFACENODES" => {
-            Ok((rest, KeyWord::TotalNumFaceNodes))
-        }
-        "NUMCONNECTEDBOUNDARYFACES" => {
-            Ok((rest, KeyWord::NumConnectedBoundaryFaces))
-        }
-        "TOTALNUMBOUNDARYCONNECTIONS" => {
-            Ok((rest, KeyWord::TotalNumBoundaryConnections))
-        }
-        "FACENEIGHBORCONNECTIONS" => {
-            Ok((rest, KeyWord::FaceNeighborConnections))
-        }
-        "DT" => {
-            Ok((rest, KeyWord::DT))
-        }
-        "DATAPACKING" => {
-            Ok((rest, KeyWord::DataPacking))
-        }
-        "VARLOCATION" => {
-            Ok((rest, KeyWord::VarLocation))
-        }
-        "VARSHARELIST" => {
-            Ok((rest, KeyWord::VarShareList))
-        }
-        "NV" => {
-            Ok((rest, KeyWord::NV))
-        }
-        "CONNECTIVITYSHAREZONE" => {
-            Ok((rest, KeyWord::ConnectivityShareZone))
-        }
-        "STRANDID" => {
-            Ok((rest, KeyWord::StrandId))
-        }
-        "SOLUTIONTIME" => {
-            Ok((rest, KeyWord::SolutionTime))
-        }
-        "PARENTZONE" => {
-            Ok((rest, KeyWord::ParentZone))
-        }
-        "PASSIVEVARLIST" => {
-            Ok((rest, KeyWord::PassiveVarList))
-        }
-        "AUXDATA" => {
-            Ok((rest, KeyWord::AuxData))
-        }
+    let key = match word.to_ascii_uppercase().as_str() {
+        "ZONE" => return Err(nom::Err::Error(ParseError::EndOfHeader)),
+        "TITLE" => KeyWord::Title,
+        "VARIABLES" => KeyWord::Variables,
+        "FILETYPE" => KeyWord::FileType,
+        "T" => KeyWord::T,
+        "ZONETYPE" => KeyWord::ZoneType,
+        "I" => KeyWord::I,
+        "J" => KeyWord::J,
+        "K" => KeyWord::K,
+        "NODES" | "N" => KeyWord::Nodes,
+        "ELEMENTS" | "E" => KeyWord::Elements,
+        "FACES" => KeyWord::Faces,
+        "TOTALNUMFACENODES" => KeyWord::TotalNumFaceNodes,
+        "NUMCONNECTEDBOUNDARYFACES" => KeyWord::NumConnectedBoundaryFaces,
+        "TOTALNUMBOUNDARYCONNECTIONS" => KeyWord::TotalNumBoundaryConnections,
+        "FACENEIGHBORCONNECTIONS" => KeyWord::FaceNeighborConnections,
+        "DT" => KeyWord::DT,
+        "DATAPACKING" => KeyWord::DataPacking,
+        "VARLOCATION" => KeyWord::VarLocation,
+        "VARSHARELIST" => KeyWord::VarShareList,
+        "NV" => KeyWord::NV,
+        "CONNECTIVITYSHAREZONE" => KeyWord::ConnectivityShareZone,
+        "STRANDID" => KeyWord::StrandId,
+        "SOLUTIONTIME" => KeyWord::SolutionTime,
+        "PARENTZONE" => KeyWord::ParentZone,
+        "PASSIVEVARLIST" => KeyWord::PassiveVarList,
+        "AUXDATA" => KeyWord::AuxData,
+        "DATASETAUXDATA" => KeyWord::DatasetAuxData,
+        _ => return Err(nom::Err::Error(ParseError::WrongHeaderTag)),
+    };
+    Ok((rest, key))
+}
 
-        _ => {
-            Err(nom::Err::Error(ParseError::WrongHeaderTag))
+
+/// Quoted string, in which `\"` and `\\` stand for a quote and a backslash. The text between the
+/// quotes is returned as is, see [`unescape`].
+fn word(input: &str) -> IResult<&str, &str, ParseError> {
+    let (rest, _) = tag("\"")(input)?;
+    let mut chars = rest.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' => return Ok((&rest[i + 1..], &rest[..i])),
+            _ => {}
         }
     }
+    Err(nom::Err::Error(ParseError::NomError(nom::error::ErrorKind::Tag)))
 }
 
-
-fn word(input: &str) -> IResult<&str, &str, ParseError> {
-    let (r, v) = delimited(tag("\""), take_while(|c| c != '"'), tag("\""))(input)?;
-    Ok((r, v))
+/// Resolves the escaped quotes and backslashes of a string parsed by [`word`].
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&next)) if next == '"' || next == '\\' => {
+                out.push(next);
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
 }
 
 fn number(input: &str) -> IResult<&str, f64, ParseError> {
@@ -221,27 +208,27 @@ fn separ(input: &str) -> IResult<&str, (), ParseError> {
 }
 
 
-fn var_location(input: &str) -> IResult<&str, Values, ParseError> {
-    fn sp1(input: &str) -> IResult<&str, (Vec<&str>, &str), ParseError> {
-        fn pattern(input: &str) -> IResult<&str, Vec<&str>, ParseError> {
-            fn var_specifier(input: &str) -> IResult<&str, &str, ParseError> {
-                take_while::<_, &str, ParseError>(|c: char| c.is_numeric() || c == '-')(input)
-            }
+/// Variable set like `[1-3, 5]`.
+fn var_set(input: &str) -> IResult<&str, Vec<&str>, ParseError> {
+    fn var_specifier(input: &str) -> IResult<&str, &str, ParseError> {
+        take_while::<_, &str, ParseError>(|c: char| c.is_numeric() || c == '-')(input)
+    }
 
-            let (r, v) = delimited(
-                tag("["),
-                separated_list(separ_comma, var_specifier),
-                tag("]"),
-            )(input).unwrap();
+    let (r, v) = delimited(
+        tag("["),
+        separated_list(separ_comma, var_specifier),
+        tag("]"),
+    )(input)?;
 
-            Ok((r, v))
-        }
+    Ok((r, v))
+}
+
+fn var_sets(input: &str) -> IResult<&str, Values, ParseError> {
+    fn sp1(input: &str) -> IResult<&str, (Vec<&str>, Option<&str>), ParseError> {
         let (r, v) = do_parse!(input,
-             pat: pattern
-              >>      multispace0
-              >>      char!('=')
+             pat: var_set
               >>      multispace0
-             >> val: value
+             >> val: opt!(preceded!(char!('='), preceded!(multispace0, value)))
              >>      multispace0 >>
             ( (pat, val) )
       )?;
@@ -256,9 +243,9 @@ fn var_location(input: &str) -> IResult<&str, Values, ParseError> {
             sp1,
         ),
         tag(")"),
-    )(input).unwrap();
+    )(input)?;
 
-    Ok((r, Values::Location(v)))
+    Ok((r, Values::VarSets(v)))
 }
 
 
@@ -282,7 +269,9 @@ fn keyword_parser(keyword: KeyWord, input: &str) -> IResult<&str, Values, ParseE
             let (r, s) = value(input)?;
             Ok((r, Values::String(s)))
         }
-        StrandId | I | J | K | Nodes | Elements | SolutionTime => {
+        StrandId | I | J | K | Nodes | Elements | SolutionTime | Faces | TotalNumFaceNodes
+        | NumConnectedBoundaryFaces | TotalNumBoundaryConnections | FaceNeighborConnections
+        | ConnectivityShareZone | ParentZone | NV => {
             let (r, s) = number(input)?;
             Ok((r, Values::Number(s)))
         }
@@ -298,24 +287,54 @@ fn keyword_parser(keyword: KeyWord, input: &str) -> IResult<&str, Values, ParseE
 
             Ok((r, Values::StringList(v)))
         }
-        VarLocation => {
-            let (r, v) = var_location(input)?;
-
-            Ok((r, v))
+        VarLocation | VarShareList => var_sets(input),
+        PassiveVarList => {
+            let (r, v) = var_set(input)?;
+            Ok((r, Values::Vars(v)))
         }
-        _ => unimplemented!()
+        _ => Err(nom::Err::Error(ParseError::NotSupportedFeature)),
     }
 }
 
 
+fn aux_name(input: &str) -> IResult<&str, &str, ParseError> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '.')(input)
+}
+
+/// `name = "value"` pair following AUXDATA and DATASETAUXDATA.
+fn aux_data(input: &str) -> IResult<&str, Values, ParseError> {
+    let (rest, (name, value)) = aux_pair(input)?;
+    Ok((rest, Values::Aux(name, value)))
+}
+
+/// ` name="value"` of an aux data entry.
+fn aux_pair(input: &str) -> IResult<&str, (&str, &str), ParseError> {
+    do_parse!(input,
+                  space1
+        >> name:  aux_name
+        >>        space0
+        >>        char!('=')
+        >>        space0
+        >> value: word
+        >> ( (name, value) )
+    )
+}
+
 fn key_value(input: &str) -> IResult<&str, (KeyWord, Values), ParseError> {
     let (rest, key) = do_parse!(input,
              multispace0
-              >> key: keyword
-              >>      multispace0
+              >> key: keyword >>
+            ( key )
+      )?;
+    if let KeyWord::AuxData | KeyWord::DatasetAuxData = key {
+        let (rest, value) = aux_data(rest)?;
+        return Ok((rest, (key, value)));
+    }
+    let (rest, _) = do_parse!(rest,
+                  multispace0
               >>      char!('=')
               >>      multispace0 >>
-            ( key )
+            ( () )
       )?;
 
     let (rest, value) = keyword_parser(key, rest)?;
@@ -340,20 +359,20 @@ fn parse_header(input: &str) -> IResult<&str, DatHeader, ParseError> {
         match key {
             KeyWord::Title => {
                 match value {
-                    Values::String(s) => Some(s.to_owned()),
+                    Values::String(s) => Some(unescape(s)),
                     _ => None,
                 }
             }
             _ => None,
         }
-    }).unwrap_or("Dataset").to_owned();
+    }).unwrap_or_else(|| "Dataset".to_owned());
 
     let filetype = values.iter().find_map(|(key, value)| {
         match key {
             KeyWord::FileType => {
                 match value {
                     Values::String(s) => {
-                        match *s {
+                        match s.to_ascii_uppercase().as_str() {
                             "FULL" => Some(FileType::Full),
                             "GRID" => Some(FileType::GridOnly),
                             "SOLUTION" => Some(FileType::SolutionOnly(null_mut())),
@@ -371,18 +390,26 @@ fn parse_header(input: &str) -> IResult<&str, DatHeader, ParseError> {
         match key {
             KeyWord::Variables => {
                 match value {
-                    Values::StringList(s) => Some(s.iter().map(|&s| format!("\"{}\"", s)).collect()),
+                    Values::StringList(s) => Some(s.iter().map(|&s| format!("\"{}\"", unescape(s))).collect()),
                     _ => None,
                 }
             }
             _ => None,
         }
-    }).unwrap();
+    }).ok_or(nom::Err::Error(ParseError::InvalidValue("VARIABLES")))?;
+
+    let aux_data = values.iter().filter_map(|(key, value)| {
+        match (key, value) {
+            (KeyWord::DatasetAuxData, Values::Aux(name, value)) => Some((name.to_string(), unescape(value))),
+            _ => None,
+        }
+    }).collect();
 
     let header = DatHeader {
         title,
         filetype,
         var_list,
+        aux_data,
     };
 
 
@@ -392,147 +419,242 @@ fn parse_header(input: &str) -> IResult<&str, DatHeader, ParseError> {
 use std::collections::HashMap;
 
 
-fn resolve_var_location(var_loc: &[(Vec<&str>, &str)], var_num: usize) -> Vec<ValueLocation> {
-    let mut locations = vec![ValueLocation::Nodal; var_num];
-
-    var_loc.iter().for_each(|(patterns, location)| {
-        let location = match *location {
-            "CELLCENTERED" => ValueLocation::CellCentered,
-            "NODAL" => ValueLocation::Nodal,
-            x => panic!("Unknown value location: {:?}", x)
+/// Resolves one-based variable ranges like `[1-3, 5]` to zero-based indices, `what` names the
+/// keyword in errors.
+fn var_indices(
+    patterns: &[&str],
+    var_num: usize,
+    what: &'static str,
+) -> std::result::Result<Vec<usize>, ParseError> {
+    let mut indices = vec![];
+    for pattern in patterns {
+        let (first, last) = match pattern.find('-') {
+            Some(i) => (&pattern[..i], &pattern[i + 1..]),
+            None => (*pattern, *pattern),
         };
-
-        patterns.iter().for_each(|&pattern| {
-            if pattern.contains("-") {
-                let mut sp = pattern.split("-");
-                let first: usize = sp.next().unwrap().parse().expect("Bad number!");
-                let last: usize = sp.next().unwrap().parse().expect("Bad number!");
-                if last > first && first > 0 && last <= var_num {
-                    for i in first..=last {
-                        locations[i] = location;
-                    }
-                } else {
-                    panic!("Var location num outside of var num count!")
-                }
+        let index = |var: &str| {
+            let var = var.parse::<usize>().map_err(|_| ParseError::InvalidValue(what))?;
+            if (1..=var_num).contains(&var) {
+                Ok(var)
             } else {
-                //2
-                let num = pattern.parse::<usize>().expect("Bad number for zone location");
-                if num <= var_num {
-                    locations[num - 1] = location;
-                } else {
-                    panic!("Var location num outside of var num count!")
-                }
+                Err(ParseError::IndexOutOfRange {
+                    index: var as i64,
+                    max: var_num as i64,
+                })
             }
-        })
-    });
-    locations
+        };
+        let (first, last) = (index(first)?, index(last)?);
+        if first > last {
+            return Err(ParseError::InvalidValue(what));
+        }
+        indices.extend(first - 1..last);
+    }
+    Ok(indices)
 }
 
-fn float_sep(input: &str) -> IResult<&str, (), ParseError> {
-    do_parse!(input,
-                  space0
-          >>      opt!(line_ending)
-          >> space0
-          >>
-        ( () )
-     )
-}
-fn weird_float(input: &str) -> IResult<&str, &str, ParseError>{
-    take_while::<_, &str, ParseError>(|c: char|
-        c == 'E' ||
-        c == '-' ||
-        c == '+' ||
-        c == '.' ||
-        c.is_digit(10)
-    )(input)
+/// Resolves `[1-3, 5]=CELLCENTERED` sets.
+fn resolve_var_location(
+    var_loc: &[(Vec<&str>, Option<&str>)],
+    var_num: usize,
+) -> std::result::Result<Vec<ValueLocation>, ParseError> {
+    let mut locations = vec![ValueLocation::Nodal; var_num];
+
+    for (patterns, location) in var_loc {
+        let location = match *location {
+            Some(l) if l.eq_ignore_ascii_case("CELLCENTERED") => ValueLocation::CellCentered,
+            Some(l) if l.eq_ignore_ascii_case("NODAL") => ValueLocation::Nodal,
+            _ => return Err(ParseError::InvalidValue("VARLOCATION")),
+        };
+        for var in var_indices(patterns, var_num, "VARLOCATION")? {
+            locations[var] = location;
+        }
+    }
+    Ok(locations)
 }
 
-fn float_with_separ(input: &str) -> IResult<&str, &str, ParseError> {
-    do_parse!(input,
-            opt!(float_sep) >>
-        f: weird_float >>
-        float_sep >>
-        ( f )
-    )
+/// Resolves `[1-3]=2` sets to the one-based zones variables are shared from, 0 for variables of
+/// the zone itself. Sets without a zone are shared from the previous zone `zone - 1`.
+fn resolve_var_share_list(
+    var_share: &[(Vec<&str>, Option<&str>)],
+    var_num: usize,
+    zone: usize,
+) -> std::result::Result<Vec<i32>, ParseError> {
+    let mut share_list = vec![0; var_num];
+
+    for (patterns, source) in var_share {
+        let source = match source {
+            Some(source) => source
+                .parse::<usize>()
+                .map_err(|_| ParseError::InvalidValue("VARSHARELIST"))?,
+            None => zone - 1,
+        };
+        if !(1..zone).contains(&source) {
+            return Err(ParseError::IndexOutOfRange {
+                index: source as i64,
+                max: zone as i64 - 1,
+            });
+        }
+        for var in var_indices(patterns, var_num, "VARSHARELIST")? {
+            share_list[var] = source as i32;
+        }
+    }
+    Ok(share_list)
 }
 
-fn parse_zone(input: &str, var_num: usize) -> IResult<&str, (TecZone, DataBlock), ParseError> {
-    let (rest, tag) = tag("ZONE")(input)?;
-    let (rest, values) = many0(terminated(key_value, separ))(rest)?;
+/// Parses the header of a zone record of `text` starting at `input`, following the `previous`
+/// zones. Errors carry their line and column and the variable they occurred in.
+fn parse_zone_header<'a>(
+    text: &str,
+    input: &'a str,
+    var_num: usize,
+    previous: &[TecZone],
+) -> Result<(&'a str, TecZone, DataPacking)> {
+    let located = |input: &'a str, var: Option<usize>| {
+        move |e: nom::Err<ParseError>| TecioError::from(e).at(Location::in_text(text, input), None, var)
+    };
+    let invalid = |what| located(input, None)(nom::Err::Error(ParseError::InvalidValue(what)));
+
+    let (rest, _) = tag_no_case("ZONE")(input).map_err(located(input, None))?;
+    let (rest, values) = many0(terminated(key_value, separ))(rest).map_err(located(rest, None))?;
+    let (aux_data, values): (Vec<_>, Vec<_>) = values.into_iter().partition(|(key, _)| *key == KeyWord::AuxData);
+    let aux_data: AuxData = aux_data.into_iter().filter_map(|(_, value)| match value {
+        Values::Aux(name, value) => Some((name.to_owned(), unescape(value))),
+        _ => None,
+    }).collect();
     let values: HashMap<KeyWord, Values> = values.into_iter().collect();
 
-    let zonetype = values.get(&KeyWord::ZoneType).map(|t| {
-        match t {
-            Values::String(t) => {
-                match t.to_lowercase().as_str() {
-                    "ordered" => ZoneType::Ordered,
-                    "felineseg" => ZoneType::FELine,
-                    "fetriangle" => ZoneType::FETriangle,
-                    "fequadrilateral" => ZoneType::FEQuad,
-                    "fetetrahedron" => ZoneType::FETetra,
-                    "febrick" => ZoneType::FEBrick,
-                    "fepolygon" => ZoneType::FEPolygon,
-                    "fepolyhedral" => ZoneType::FEPolyhedron,
-                    x => panic!("Unknown zone type: {:?}!", x)
-                }
-            }
-            x => panic!("Unknown zone type: {:?}!", x)
-        }
-    }).unwrap_or(ZoneType::Ordered);
-
-    let get_number = |key| values.get(&key).map(|v| {
-        match v {
-            Values::Number(n) => *n,
-            x => panic!("Expected number, got {:?}", x)
-        }
-    }).unwrap_or(1.0);
+    let zonetype = match values.get(&KeyWord::ZoneType) {
+        Some(Values::String(t)) => match t.to_lowercase().as_str() {
+            "ordered" => ZoneType::Ordered,
+            "felineseg" => ZoneType::FELine,
+            "fetriangle" => ZoneType::FETriangle,
+            "fequadrilateral" => ZoneType::FEQuad,
+            "fetetrahedron" => ZoneType::FETetra,
+            "febrick" => ZoneType::FEBrick,
+            "fepolygon" => ZoneType::FEPolygon,
+            "fepolyhedron" | "fepolyhedral" => ZoneType::FEPolyhedron,
+            _ => return Err(invalid("ZONETYPE")),
+        },
+        Some(_) => return Err(invalid("ZONETYPE")),
+        None => ZoneType::Ordered,
+    };
 
-    let zone_title = values.get(&KeyWord::T).map(|x| match x {
-        Values::String(name) => (*name).to_owned(),
-        _ => unimplemented!()
-    }).unwrap_or_else(|| format!("Unnamed zone"));
-    let solution_time = get_number(KeyWord::SolutionTime);
-    let strand_id = get_number(KeyWord::StrandId) as _;
-    let var_location = values.get(&KeyWord::VarLocation).map(|v| {
-        match v {
-            Values::Location(l) => resolve_var_location(l.as_slice(), var_num),
-            x => panic!("Expected list of var locations, got: {:?}!", x)
-        }
-    }).unwrap_or_else(|| vec![ValueLocation::Nodal; var_num]);
+    let get_number = |key| match values.get(&key) {
+        Some(Values::Number(n)) => Ok(*n),
+        Some(_) => Err(invalid("number")),
+        None => Ok(1.0),
+    };
+    // Sizes have to be whole numbers, negative ones would wrap around when used as lengths.
+    let get_size = |key, default: i64, min: i64| match values.get(&key) {
+        Some(Values::Number(n)) if *n >= min as f64 && n.fract() == 0.0 => Ok(*n as i64),
+        Some(_) => Err(invalid("zone size")),
+        None => Ok(default),
+    };
 
-    let var_types = values.get(&KeyWord::DT).map(|v| {
-        match v {
-            Values::StringList(list) => {
-                list.iter().map(|n| match *n {
-                    "SINGLE" => TecDataType::F32,
-                    "DOUBLE" => TecDataType::F64,
-                    x => panic!("Expected var type, got: {:?}!", x)
-                }).collect()
-            }
-            x => panic!("Expected list of var types, got: {:?}!", x)
-        }
-    }).unwrap_or_else(|| vec![TecDataType::F64; var_num]);
+    let zone_title = match values.get(&KeyWord::T) {
+        Some(Values::String(name)) => unescape(name),
+        Some(_) => return Err(invalid("zone title")),
+        None => "Unnamed zone".to_owned(),
+    };
+    let solution_time = get_number(KeyWord::SolutionTime)?;
+    let strand_id = get_number(KeyWord::StrandId)? as _;
+    let var_location = match values.get(&KeyWord::VarLocation) {
+        Some(Values::VarSets(l)) => resolve_var_location(l.as_slice(), var_num)
+            .map_err(|e| located(input, None)(nom::Err::Error(e)))?,
+        Some(_) => return Err(invalid("VARLOCATION")),
+        None => vec![ValueLocation::Nodal; var_num],
+    };
 
-    let data_pack = values.get(&KeyWord::DataPacking).map(|t| {
-        match t {
-            Values::String(t) => {
-                match t.to_lowercase().as_str() {
-                    "point" => DataPacking::Point,
-                    "block" => DataPacking::Block,
+    let var_types = match values.get(&KeyWord::DT) {
+        Some(Values::StringList(list)) => list
+            .iter()
+            .map(|n| match n.to_ascii_uppercase().as_str() {
+                "SINGLE" => Ok(TecDataType::F32),
+                "DOUBLE" => Ok(TecDataType::F64),
+                "LONGINT" => Ok(TecDataType::I32),
+                "SHORTINT" => Ok(TecDataType::I16),
+                "BYTE" => Ok(TecDataType::I8),
+                "BIT" => Ok(TecDataType::I1),
+                _ => Err(invalid("DT")),
+            })
+            .collect::<Result<Vec<_>>>()?,
+        Some(_) => return Err(invalid("DT")),
+        None => vec![TecDataType::F64; var_num],
+    };
+    if var_types.len() != var_num || var_location.len() != var_num {
+        return Err(invalid("DT"));
+    }
 
-                    x => panic!("Unknown zone type: {:?}!", x)
-                }
+    let zone_num = previous.len() + 1;
+    let var_share_list = match values.get(&KeyWord::VarShareList) {
+        Some(Values::VarSets(l)) => resolve_var_share_list(l.as_slice(), var_num, zone_num)
+            .map_err(|e| located(input, None)(nom::Err::Error(e)))?,
+        Some(_) => return Err(invalid("VARSHARELIST")),
+        None => vec![0; var_num],
+    };
+    let mut passive_var_list = vec![0; var_num];
+    match values.get(&KeyWord::PassiveVarList) {
+        Some(Values::Vars(vars)) => {
+            for var in var_indices(vars, var_num, "PASSIVEVARLIST")
+                .map_err(|e| located(input, None)(nom::Err::Error(e)))?
+            {
+                passive_var_list[var] = 1;
             }
-            x => panic!("Unknown zone type: {:?}!", x)
         }
-    }).unwrap_or(DataPacking::Block);
+        Some(_) => return Err(invalid("PASSIVEVARLIST")),
+        None => {}
+    }
+    // One-based id of an earlier zone, 0 if the keyword is missing.
+    let earlier_zone = |key, what| match values.get(&key) {
+        Some(Values::Number(n)) if n.fract() == 0.0 && *n >= 1.0 && *n < zone_num as f64 => {
+            Ok(*n as i32)
+        }
+        Some(Values::Number(n)) => {
+            let error = ParseError::IndexOutOfRange { index: *n as i64, max: zone_num as i64 - 1 };
+            Err(located(input, None)(nom::Err::Error(error)))
+        }
+        Some(_) => Err(invalid(what)),
+        None => Ok(0),
+    };
+    let parent_zone = earlier_zone(KeyWord::ParentZone, "PARENTZONE")?;
+    let connectivity_share_zone =
+        earlier_zone(KeyWord::ConnectivityShareZone, "CONNECTIVITYSHAREZONE")?;
+    if connectivity_share_zone > 0 && zonetype == ZoneType::Ordered {
+        return Err(invalid("CONNECTIVITYSHAREZONE"));
+    }
+    // The variable holding node numbers in old FE files, read like any other.
+    match values.get(&KeyWord::NV) {
+        Some(Values::Number(n)) if n.fract() == 0.0 && *n >= 1.0 && *n <= var_num as f64 => {}
+        Some(_) => return Err(invalid("NV")),
+        None => {}
+    }
+    match values.get(&KeyWord::FaceNeighborConnections) {
+        Some(Values::Number(n)) if *n == 0.0 => {}
+        Some(Values::Number(_)) => {
+            // Face neighbor connections aren't part of the zone model yet.
+            let error = ParseError::NotSupportedFeature;
+            return Err(located(input, None)(nom::Err::Error(error)));
+        }
+        Some(_) => return Err(invalid("FACENEIGHBORCONNECTIONS")),
+        None => {}
+    }
+
+    let data_pack = match values.get(&KeyWord::DataPacking) {
+        Some(Values::String(t)) => match t.to_lowercase().as_str() {
+            "point" => DataPacking::Point,
+            "block" => DataPacking::Block,
+            _ => return Err(invalid("DATAPACKING")),
+        },
+        Some(_) => return Err(invalid("DATAPACKING")),
+        None => DataPacking::Block,
+    };
 
 
     let zone = match zonetype {
         ZoneType::Ordered => {
-            let i_max = get_number(KeyWord::I) as i64;
-            let j_max = get_number(KeyWord::J) as i64;
-            let k_max = get_number(KeyWord::K) as i64;
+            let i_max = get_size(KeyWord::I, 1, 1)?;
+            let j_max = get_size(KeyWord::J, 1, 1)?;
+            let k_max = get_size(KeyWord::K, 1, 1)?;
 
 
             let zone = TecZone::Ordered(OrderedZone {
@@ -540,12 +662,15 @@ fn parse_zone(input: &str, var_num: usize) -> IResult<&str, (TecZone, DataBlock)
                 id: 0,
                 solution_time,
                 strand: strand_id,
+                parent_zone,
                 i_max,
                 j_max,
                 k_max,
                 var_location,
                 var_types: Some(var_types),
-                passive_var_list: vec![0; var_num]
+                var_share_list,
+                passive_var_list,
+                aux_data,
             });
 
 
@@ -556,8 +681,8 @@ fn parse_zone(input: &str, var_num: usize) -> IResult<&str, (TecZone, DataBlock)
         | ZoneType::FEQuad
         | ZoneType::FETriangle
         | ZoneType::FELine => {
-            let cells = get_number(KeyWord::Elements) as i64;
-            let nodes = get_number(KeyWord::Nodes) as i64;
+            let cells = get_size(KeyWord::Elements, 1, 0)?;
+            let nodes = get_size(KeyWord::Nodes, 1, 0)?;
 
             TecZone::ClassicFE(ClassicFEZone {
                 name: zone_title,
@@ -565,182 +690,1133 @@ fn parse_zone(input: &str, var_num: usize) -> IResult<&str, (TecZone, DataBlock)
                 id: 0,
                 solution_time,
                 strand: strand_id,
+                parent_zone,
+                nodes,
+                cells,
+                var_location,
+                var_types: Some(var_types),
+                var_share_list,
+                passive_var_list,
+                connectivity_share_zone,
+                aux_data,
+            })
+        }
+        ZoneType::FEPolygon | ZoneType::FEPolyhedron => {
+            let cells = get_size(KeyWord::Elements, 1, 0)?;
+            let nodes = get_size(KeyWord::Nodes, 1, 0)?;
+            let faces = get_size(KeyWord::Faces, 1, 0)?;
+            let get_count = |key| get_size(key, 0, 0);
+            let total_num_face_nodes = match zonetype {
+                ZoneType::FEPolygon => faces.saturating_mul(2),
+                _ => get_count(KeyWord::TotalNumFaceNodes)?,
+            };
+
+            TecZone::PolyFE(PolyFE {
+                name: zone_title,
+                zone_type: zonetype,
+                id: 0,
+                solution_time,
+                strand: strand_id,
+                parent_zone,
                 nodes,
                 cells,
+                faces,
+                total_num_face_nodes,
+                num_connected_boundary_faces: get_count(KeyWord::NumConnectedBoundaryFaces)?,
+                total_num_boundary_connections: get_count(KeyWord::TotalNumBoundaryConnections)?,
                 var_location,
                 var_types: Some(var_types),
+                var_share_list,
+                passive_var_list,
+                connectivity_share_zone,
+                aux_data,
             })
         }
-        _ => unimplemented!()
     };
-    let mut rest = rest;
-    let mut data = Vec::with_capacity(var_num);
-    let min_max = vec![(0.0, 0.0); var_num];
 
+    // Shared data has to fit the zone it is shared into.
+    let len = |zone: &TecZone, loc| match loc {
+        ValueLocation::Nodal => zone.node_count(),
+        ValueLocation::CellCentered => zone.cell_count(),
+    };
+    for (var, &loc) in zone.var_locs().iter().enumerate() {
+        if let Some(source) = zone.shared_from(var + 1).map(|z| &previous[z - 1]) {
+            if source.var_locs()[var] != loc || len(source, loc) != len(&zone, loc) {
+                let error = ParseError::InvalidValue("VARSHARELIST");
+                return Err(located(input, Some(var + 1))(nom::Err::Error(error)));
+            }
+        }
+    }
+    let faces = |zone: &TecZone| match zone {
+        TecZone::PolyFE(z) => (z.faces, z.total_num_face_nodes, z.num_connected_boundary_faces),
+        _ => (0, 0, 0),
+    };
+    if let Some(source) = zone.connectivity_shared_from().map(|z| &previous[z - 1]) {
+        if source.zone_type() != zone.zone_type()
+            || source.node_count() != zone.node_count()
+            || source.cell_count() != zone.cell_count()
+            || faces(source) != faces(&zone)
+        {
+            return Err(invalid("CONNECTIVITYSHAREZONE"));
+        }
+    }
 
-    match data_pack {
-        DataPacking::Block => {
-            for (num, (loc, ty)) in zone.var_locs().iter().zip(zone.data_types().unwrap().iter()).enumerate() {
-                let c = match loc {
-                    ValueLocation::Nodal => {
-                        zone.node_count()
-                    }
-                    ValueLocation::CellCentered => {
-                        zone.cell_count()
-                    }
-                };
+    // Every line holds one value of each variable, so all of them have to be nodal.
+    let cell_centered = (1..=var_num).find(|&var| {
+        zone.var_locs()[var - 1] == ValueLocation::CellCentered
+            && !zone.is_passive(var)
+            && zone.shared_from(var).is_none()
+    });
+    if let (DataPacking::Point, Some(var)) = (data_pack, cell_centered) {
+        let error = ParseError::InvalidValue("cell centered variable in POINT packing");
+        return Err(located(input, Some(var))(nom::Err::Error(error)));
+    }
 
-                let (r, _) = float_sep(rest)?; // ??????????
-                rest = r;
+    Ok((rest, zone, data_pack))
+}
 
-                let (r, x) = count(float_with_separ, c)(rest)?;
+/// Values are read into buffers growing with the input, not with the counts a file claims.
+const PREALLOCATED: usize = 1 << 16;
 
-                rest = r;
-                let d = match ty {
-                    TecDataType::F32 => {
-                        TecData::from(
-                            x.into_iter().map(|p| p.parse::<f32>().unwrap()).collect::<Vec<_>>()
-                        )
-                    }
-                    TecDataType::F64 => {
-                        TecData::from(
-                            x.into_iter().map(|p| p.parse::<f64>().unwrap()).collect::<Vec<_>>()
-                        )
-                    }
-                    TecDataType::I32 => {
-                        TecData::from(
-                            x.into_iter().map(|p| p.parse::<i32>().unwrap()).collect::<Vec<_>>()
-                        )
-                    }
-                    TecDataType::I16 => {
-                        TecData::from(
-                            x.into_iter().map(|p| p.parse::<i16>().unwrap()).collect::<Vec<_>>()
-                        )
-                    }
-                    _ => unimplemented!()
-                };
-                data.push((num + 1, d));
+/// Keywords starting a record, everything else up to the values belongs to the current one.
+const RECORDS: [&str; 6] =
+    ["ZONE", "TEXT", "GEOMETRY", "CUSTOMLABELS", "DATASETAUXDATA", "VARAUXDATA"];
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || c == ','
+}
+
+fn starts_record(line: &str) -> bool {
+    let end = line.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(line.len());
+    RECORDS.iter().any(|record| record.eq_ignore_ascii_case(&line[..end]))
+}
+
+fn starts_values(line: &str) -> bool {
+    line.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
+}
+
+/// Parses a value token, Fortran writes exponents like `1.0D+00`.
+fn parse_number<T: FromStr>(token: &str, what: &'static str) -> std::result::Result<T, ParseError> {
+    token
+        .parse()
+        .or_else(|_| token.replace(['D', 'd'], "E").parse())
+        .map_err(|_| ParseError::InvalidValue(what))
+}
+
+/// Text of a record, which starts on line `line` of the file.
+struct Chunk {
+    text: String,
+    line: usize,
+}
+
+impl Chunk {
+    /// Parses the chunk with `f`, moving the locations of its errors from the chunk to the file.
+    fn parse<'a, T>(&'a self, f: impl FnOnce(&'a str) -> Result<T>) -> Result<T> {
+        f(&self.text).map_err(|e| match e {
+            TecioError::Malformed {
+                error,
+                location: Location::LineColumn { line, column },
+                zone,
+                var,
+            } => TecioError::Malformed {
+                error,
+                location: Location::LineColumn { line: line + self.line - 1, column },
+                zone,
+                var,
+            },
+            e => e,
+        })
+    }
+}
+
+/// Reads a DAT file line by line, keeping track of where values come from for errors.
+struct TokenReader<R> {
+    input: R,
+    line: String,
+    /// Byte offset of the unread rest of `line`.
+    pos: usize,
+    /// One-based number of `line`.
+    line_no: usize,
+    /// Bytes read before `line`.
+    offset: usize,
+    /// Copies of a `k*value` token left to hand out.
+    repeat: Option<(usize, String, Location)>,
+    /// Location of the last value.
+    at: Location,
+}
+
+impl<R: BufRead> TokenReader<R> {
+    fn new(input: R) -> Self {
+        Self {
+            input,
+            line: String::new(),
+            pos: 0,
+            line_no: 0,
+            offset: 0,
+            repeat: None,
+            at: Location::LineColumn { line: 1, column: 1 },
+        }
+    }
+
+    /// Moves on to the next line, `#` comment lines are skipped. False at the end of input.
+    fn next_line(&mut self) -> Result<bool> {
+        self.offset += self.line.len();
+        self.line.clear();
+        self.pos = 0;
+        if self.input.read_line(&mut self.line)? == 0 {
+            return Ok(false);
+        }
+        self.line_no += 1;
+        if self.line.trim_start().starts_with('#') {
+            self.pos = self.line.len();
+        }
+        Ok(true)
+    }
+
+    /// Location of the byte `at` of the current line.
+    fn location(&self, at: usize) -> Location {
+        Location::LineColumn {
+            line: self.line_no,
+            column: self.line[..at].chars().count() + 1,
+        }
+    }
+
+    /// Skips to the start of the next token, false at the end of input.
+    fn skip_separators(&mut self) -> Result<bool> {
+        loop {
+            match self.line[self.pos..].find(|c| !is_separator(c)) {
+                Some(i) => {
+                    self.pos += i;
+                    return Ok(true);
+                }
+                None if !self.next_line()? => return Ok(false),
+                None => {}
             }
         }
-        DataPacking::Point => {
-            let nodes = zone.node_count();
+    }
 
-            for (num, ty) in zone.data_types().unwrap().iter().enumerate() {
-                match ty {
-                    TecDataType::F32 => {
-                        data.push((num +1, TecData::F32(Cow::Owned(Vec::with_capacity(nodes)))))
-                    }
-                    TecDataType::F64 => {
-                        data.push((num +1, TecData::F64(Cow::Owned(Vec::with_capacity(nodes)))))
-                    }
-                    _ => unimplemented!()
+    /// Reads the text of the next record up to the line its values start on, `None` at the end
+    /// of input. Lines belong to the record until one starts with a value or another record.
+    fn record(&mut self) -> Result<Option<Chunk>> {
+        if !self.skip_separators()? {
+            return Ok(None);
+        }
+        // Padded, so that columns in the chunk match the file.
+        let mut text = " ".repeat(self.line[..self.pos].chars().count());
+        let line = self.line_no;
+        loop {
+            text.push_str(self.line[self.pos..].trim_end_matches(['\r', '\n']));
+            text.push('\n');
+            if !self.next_line()? {
+                break;
+            }
+            let rest = self.line[self.pos..].trim_start();
+            if starts_values(rest) || starts_record(rest) {
+                break;
+            }
+        }
+        Ok(Some(Chunk { text, line }))
+    }
+
+    /// Next value, a `k*value` token stands for `k` copies of it. `None` at the end of input.
+    fn value<T: FromStr>(&mut self, what: &'static str, var: Option<usize>) -> Result<Option<T>> {
+        if let Some((count, token, location)) = &mut self.repeat {
+            *count -= 1;
+            self.at = *location;
+            let value = parse_number(token, what);
+            if *count == 0 {
+                self.repeat = None;
+            }
+            return value.map(Some).map_err(|e| TecioError::from(e).at(self.at, None, var));
+        }
+        if !self.skip_separators()? {
+            return Ok(None);
+        }
+        let start = self.pos;
+        let end = self.line[start..].find(is_separator).map_or(self.line.len(), |i| start + i);
+        self.pos = end;
+        self.at = self.location(start);
+        let token = &self.line[start..end];
+        let (token, at) = match token.find('*') {
+            Some(i) => {
+                let repeat = ParseError::InvalidValue("repeat count");
+                let count = token[..i]
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&k| k >= 1)
+                    .ok_or_else(|| TecioError::from(repeat).at(self.at, None, var))?;
+                let value = &token[i + 1..];
+                if count > 1 {
+                    self.repeat = Some((count - 1, value.to_owned(), self.at));
                 }
+                (value, start + i + 1)
             }
+            None => (token, start),
+        };
+        parse_number(token, what)
+            .map(Some)
+            .map_err(|e| TecioError::from(e).at(self.location(at), None, var))
+    }
 
-            for point in 0..nodes {
-                let (r, x) = count(float_with_separ, var_num)(rest)?;
-                rest = r;
-                for (num, (d, ty)) in x.into_iter().zip(zone.data_types().unwrap().iter()).enumerate() {
-                    match ty {
-                        TecDataType::F32 => {
-                           match &mut data[num].1 {
-                               TecData::F32(Cow::Owned(v)) => {
-                                   if d.starts_with("-."){
-                                       let (_, digits) = d.split_at(2);
-                                       v.push(format!("-0.{}", digits).parse().unwrap());
-                                   }else{
-                                       v.push(d.parse().unwrap());
-                                   }
-                               }
-                               _ => unreachable!()
-                           }
-                        }
-                        TecDataType::F64 => {
-                            match &mut data[num].1 {
-                                TecData::F64(Cow::Owned(v)) => {
-                                    if d.starts_with("-."){
-                                        let (_, digits) = d.split_at(2);
-                                        let s = format!("-0.{}", digits);
-                                        match s.parse(){
-                                            Ok(s) => v.push(s),
-                                            _ => v.push(0.0),
-                                        }
-                                    }else{
-                                        match d.parse(){
-                                            Ok(x) => v.push(x),
-                                            Err(_) => v.push(0.0),
-                                        }
-                                    }
-
-                                },
-                                _ => unreachable!()
-                            }
-                        },
-                        _ => unimplemented!()
-                    }
+    /// Offset and location values start at, for [`TokenReader::truncated`].
+    fn mark(&self) -> (usize, Location) {
+        (self.offset + self.pos, self.location(self.pos))
+    }
+
+    /// Error for `count` values from `start` on, which run past the end of input.
+    fn truncated(
+        &self,
+        (start, location): (usize, Location),
+        count: usize,
+        var: Option<usize>,
+    ) -> TecioError {
+        let available = self.offset + self.line.len() - start;
+        TecioError::from(ParseError::TooLarge { count, available }).at(location, None, var)
+    }
+
+    /// Fails if a `k*value` token reaches past the end of a block of values.
+    fn end_block(&mut self, var: Option<usize>) -> Result<()> {
+        match self.repeat.take() {
+            Some((_, _, location)) => {
+                let error = ParseError::InvalidValue("repeat count");
+                Err(TecioError::from(error).at(location, None, var))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Reads a block of `n` values.
+    fn values<T: FromStr>(&mut self, n: usize, var: Option<usize>) -> Result<Vec<T>> {
+        let start = self.mark();
+        let mut values = Vec::with_capacity(n.min(PREALLOCATED));
+        while values.len() < n {
+            match self.value("number", var)? {
+                Some(value) => values.push(value),
+                None => return Err(self.truncated(start, n, var)),
+            }
+        }
+        self.end_block(var)?;
+        Ok(values)
+    }
+
+    /// Reads `n` integers. Node indices, for which `nodes` is given, have to be within
+    /// `1..=nodes`.
+    fn integers(&mut self, n: usize, nodes: Option<i64>) -> Result<Vec<i64>> {
+        let start = self.mark();
+        let mut values = Vec::with_capacity(n.min(PREALLOCATED));
+        while values.len() < n {
+            let value = match self.value::<i64>("integer", None)? {
+                Some(value) => value,
+                None => return Err(self.truncated(start, n, None)),
+            };
+            match nodes {
+                Some(max) if value < 1 || value > max => {
+                    let error = ParseError::IndexOutOfRange { index: value, max };
+                    return Err(TecioError::from(error).at(self.at, None, None));
                 }
+                _ => values.push(value),
+            }
+        }
+        self.end_block(None)?;
+        Ok(values)
+    }
+
+    /// Reads `n` integers, the first one `valid` rejects is reported as invalid `what`.
+    fn checked_integers<T: FromStr + Copy>(
+        &mut self,
+        n: usize,
+        what: &'static str,
+        valid: impl Fn(T) -> bool,
+    ) -> Result<Vec<T>> {
+        let start = self.mark();
+        let mut values = Vec::with_capacity(n.min(PREALLOCATED));
+        while values.len() < n {
+            match self.value::<T>(what, None)? {
+                Some(value) if valid(value) => values.push(value),
+                Some(_) => {
+                    let error = ParseError::InvalidValue(what);
+                    return Err(TecioError::from(error).at(self.at, None, None));
+                }
+                None => return Err(self.truncated(start, n, None)),
+            }
+        }
+        self.end_block(None)?;
+        Ok(values)
+    }
+
+    /// Reads a count, like the number of polylines of a geometry.
+    fn count(&mut self) -> Result<usize> {
+        let n = self.integers(1, None)?[0];
+        let error = ParseError::InvalidValue("count");
+        usize::try_from(n).map_err(|_| TecioError::from(error).at(self.at, None, None))
+    }
+}
+
+/// Reads `n` values of a variable stored as `ty`, bits are kept as bytes.
+fn read_values<R: BufRead>(
+    reader: &mut TokenReader<R>,
+    n: usize,
+    ty: TecDataType,
+    var: Option<usize>,
+) -> Result<TecData<'static>> {
+    #[cfg(feature = "parallel")]
+    {
+        if parallel::enabled(n) {
+            return parallel::read_values(reader, n, ty, var);
+        }
+    }
+    Ok(match ty {
+        TecDataType::F32 => TecData::from(reader.values::<f32>(n, var)?),
+        TecDataType::F64 => TecData::from(reader.values::<f64>(n, var)?),
+        TecDataType::I32 => TecData::from(reader.values::<i32>(n, var)?),
+        TecDataType::I16 => TecData::from(reader.values::<i16>(n, var)?),
+        TecDataType::I8 | TecDataType::I1 => TecData::from(bytes(reader.values::<u8>(n, var)?)),
+    })
+}
+
+/// Bytes are written unsigned and kept with their bits as `i8`.
+fn bytes(values: Vec<u8>) -> Vec<i8> {
+    values.into_iter().map(|b| b as i8).collect()
+}
+
+/// Appends the next value to `values`, false at the end of input.
+fn push_value<R: BufRead>(
+    reader: &mut TokenReader<R>,
+    values: &mut TecData<'static>,
+    var: Option<usize>,
+) -> Result<bool> {
+    fn push<T: FromStr + Clone, R: BufRead>(
+        reader: &mut TokenReader<R>,
+        values: &mut Cow<'static, [T]>,
+        var: Option<usize>,
+    ) -> Result<bool> {
+        match reader.value("number", var)? {
+            Some(value) => {
+                values.to_mut().push(value);
+                Ok(true)
             }
+            None => Ok(false),
         }
     }
+    match values {
+        TecData::F32(v) => push(reader, v, var),
+        TecData::F64(v) => push(reader, v, var),
+        TecData::I32(v) => push(reader, v, var),
+        TecData::I16(v) => push(reader, v, var),
+        TecData::I8(v) => match reader.value::<u8>("number", var)? {
+            Some(value) => {
+                v.to_mut().push(value as i8);
+                Ok(true)
+            }
+            None => Ok(false),
+        },
+        TecData::I64(v) => push(reader, v, var),
+        TecData::U64(v) => push(reader, v, var),
+        TecData::U32(v) => push(reader, v, var),
+    }
+}
 
+/// Reads `nodes` points of the `stored` variables into the columns of `data`.
+fn read_points<R: BufRead>(
+    reader: &mut TokenReader<R>,
+    data: &mut [(usize, TecData<'static>)],
+    stored: &[usize],
+    nodes: usize,
+) -> Result<()> {
+    #[cfg(feature = "parallel")]
+    {
+        if parallel::enabled(nodes.saturating_mul(stored.len())) {
+            return parallel::read_points(reader, data, stored, nodes);
+        }
+    }
+    let start = reader.mark();
+    for _ in 0..nodes {
+        for &var in stored {
+            if !push_value(reader, &mut data[var].1, Some(var + 1))? {
+                let count = nodes.saturating_mul(stored.len());
+                return Err(reader.truncated(start, count, None));
+            }
+        }
+    }
+    reader.end_block(None)
+}
 
-    let connectivity = match &zone {
-        TecZone::ClassicFE(fe) => {
-            let (r, v) = count(float_with_separ, fe.num_connections())(rest)?;
-            rest = r;
+/// Reads the values, node map and faces following the header of `zone`.
+fn read_zone_data<R: BufRead>(
+    reader: &mut TokenReader<R>,
+    zone: &TecZone,
+    data_pack: DataPacking,
+) -> Result<DataBlock> {
+    let var_num = zone.var_locs().len();
+    let min_max = vec![(0.0, 0.0); var_num];
+    let data_types = zone.data_types().unwrap_or(&[]);
+    // Passive and shared variables have no values in the zone.
+    let stored = (0..var_num)
+        .filter(|&var| !zone.is_passive(var + 1) && zone.shared_from(var + 1).is_none())
+        .collect::<Vec<_>>();
 
-            Some(TecData::from(
-                v.into_iter().map(|p| p.parse::<i32>().unwrap()).collect::<Vec<_>>()
-            ))
+    let data = match data_pack {
+        DataPacking::Block => {
+            let mut data = Vec::with_capacity(var_num);
+            for (num, (loc, ty)) in zone.var_locs().iter().zip(data_types.iter()).enumerate() {
+                let n = match loc {
+                    _ if !stored.contains(&num) => 0,
+                    ValueLocation::Nodal => zone.node_count(),
+                    ValueLocation::CellCentered => zone.cell_count(),
+                };
+                data.push((num + 1, read_values(reader, n, *ty, Some(num + 1))?));
+            }
+            data
+        }
+        DataPacking::Point => {
+            let mut data = data_types
+                .iter()
+                .enumerate()
+                .map(|(num, &ty)| Ok((num + 1, read_values(reader, 0, ty, None)?)))
+                .collect::<Result<Vec<_>>>()?;
+            let nodes = if stored.is_empty() { 0 } else { zone.node_count() };
+            read_points(reader, &mut data, &stored, nodes)?;
+            data
         }
-        _ => None
     };
 
+    let connectivity = match zone {
+        TecZone::ClassicFE(fe) if fe.connectivity_share_zone == 0 => {
+            let v = reader.integers(fe.num_connections(), Some(fe.nodes))?;
+            Some(TecData::from(v.into_iter().map(|n| n as i32).collect::<Vec<_>>()))
+        }
+        _ => None,
+    };
+    let poly_faces = match zone {
+        TecZone::PolyFE(fe) if fe.connectivity_share_zone == 0 => {
+            Some(read_poly_faces(reader, fe)?)
+        }
+        _ => None,
+    };
 
-    let block = DataBlock {
+    Ok(DataBlock {
         data,
         connectivity,
+        poly_faces,
         min_max,
+    })
+}
+
+/// Face data follows the variables: node counts of every face (polyhedra only), face nodes, left
+/// and right elements, then counts, elements and zones of boundary connections.
+fn read_poly_faces<R: BufRead>(reader: &mut TokenReader<R>, zone: &PolyFE) -> Result<PolyFaces> {
+    let faces = zone.faces as usize;
+    let positive = |n: i32| n > 0;
+    let face_node_counts = match zone.zone_type {
+        ZoneType::FEPolyhedron => {
+            let counts = reader.checked_integers(faces, "face node count", positive)?;
+            check_total(&counts, zone.total_num_face_nodes, reader.at, "TOTALNUMFACENODES")?;
+            Some(counts)
+        }
+        _ => None,
     };
-    Ok((rest, (zone, block)))
+    let face_nodes = reader.integers(zone.total_num_face_nodes as usize, Some(zone.nodes))?;
+    // Negative elements refer to the connected boundary faces, zero to no element.
+    let elements = -zone.num_connected_boundary_faces..=zone.cells;
+    let element = |e: i64| elements.contains(&e);
+    let face_left_elements = reader.checked_integers(faces, "face element", element)?;
+    let face_right_elements = reader.checked_integers(faces, "face element", element)?;
+    let boundary_connection_counts = reader.checked_integers(
+        zone.num_connected_boundary_faces as usize,
+        "boundary connection count",
+        positive,
+    )?;
+    let connections = zone.total_num_boundary_connections;
+    let counts = &boundary_connection_counts;
+    check_total(counts, connections, reader.at, "TOTALNUMBOUNDARYCONNECTIONS")?;
+    let boundary_connection_elements = reader.integers(connections as usize, None)?;
+    let boundary_connection_zones =
+        reader.checked_integers(connections as usize, "boundary connection zone", |_: i32| true)?;
+
+    Ok(PolyFaces {
+        // Polygon faces are edges, known to fit once the left elements have been read.
+        face_node_counts: face_node_counts.unwrap_or_else(|| vec![2; faces]),
+        face_nodes,
+        face_left_elements,
+        face_right_elements,
+        boundary_connection_counts,
+        boundary_connection_elements,
+        boundary_connection_zones,
+    })
 }
 
+/// Fails unless `counts` add up to `total`, given by the zone header entry `what`.
+fn check_total(counts: &[i32], total: i64, at: Location, what: &'static str) -> Result<()> {
+    if counts.iter().fold(0i64, |sum, &n| sum.saturating_add(n as i64)) != total {
+        Err(TecioError::from(ParseError::InvalidValue(what)).at(at, None, None))?
+    }
+    Ok(())
+}
 
-impl DatFormat {
-    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let file = read_to_string(path)?;
-        let rest = file.as_str();
+const COORD_SYS: &[(&str, CoordSys)] =
+    &[("GRID", CoordSys::Grid), ("FRAME", CoordSys::Frame), ("GRID3D", CoordSys::Grid3D)];
+const SCOPES: &[(&str, Scope)] = &[("GLOBAL", Scope::Global), ("LOCAL", Scope::Local)];
+const CLIPPINGS: &[(&str, Clipping)] = &[
+    ("CLIPTOAXES", Clipping::ClipToAxes),
+    ("CLIPTOVIEWPORT", Clipping::ClipToViewport),
+    ("CLIPTOFRAME", Clipping::ClipToFrame),
+];
+
+/// Error at `at`, which has to be a slice of `text`.
+fn error_at(text: &str, at: &str, error: ParseError) -> TecioError {
+    TecioError::from(error).at(Location::in_text(text, at), None, None)
+}
+
+/// `NAME=value` options of TEXT and GEOMETRY records, values are quoted strings or single tokens.
+fn record_options(input: &str) -> IResult<&str, Vec<(&str, &str)>, ParseError> {
+    many0(preceded(
+        tuple((multispace0, opt(char(',')), multispace0)),
+        separated_pair(
+            take_while1(|c: char| c.is_ascii_alphabetic()),
+            tuple((space0, char('='), space0)),
+            alt((word, take_while1(|c: char| !c.is_whitespace() && c != ','))),
+        ),
+    ))(input)
+}
+
+/// Looks up the option `value` among `names`, `what` names the option in errors.
+fn named<T: Copy>(text: &str, value: &str, names: &[(&str, T)], what: &'static str) -> Result<T> {
+    names
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        .map(|&(_, v)| v)
+        .ok_or_else(|| error_at(text, value, ParseError::InvalidValue(what)))
+}
+
+fn option_number<T: FromStr>(text: &str, value: &str, what: &'static str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| error_at(text, value, ParseError::InvalidValue(what)))
+}
+
+/// One-based zone a record is attached to.
+fn option_zone(text: &str, value: &str) -> Result<i32> {
+    match option_number(text, value, "ZN")? {
+        zone if zone >= 1 => Ok(zone),
+        _ => Err(error_at(text, value, ParseError::InvalidValue("ZN"))),
+    }
+}
+
+/// Color index of `BLACK` to `WHITE` and `CUST1` to `CUST56`.
+fn option_color(text: &str, value: &str) -> Result<i32> {
+    const COLORS: [&str; 8] =
+        ["BLACK", "RED", "GREEN", "BLUE", "CYAN", "YELLOW", "PURPLE", "WHITE"];
+    let upper = value.to_ascii_uppercase();
+    let custom = || {
+        let n = upper.strip_prefix("CUST")?.parse::<i32>().ok()?;
+        Some(n + 7).filter(|_| (1..=56).contains(&n))
+    };
+    COLORS
+        .iter()
+        .position(|&c| c == upper)
+        .map(|c| c as i32)
+        .or_else(custom)
+        .ok_or_else(|| error_at(text, value, ParseError::InvalidValue("color")))
+}
+
+/// Typeface, bold and italic of fonts like `HELV-BOLD` or `TIMES-ITALIC-BOLD`.
+fn option_font(text: &str, value: &str) -> Result<(&'static str, bool, bool)> {
+    const FAMILIES: [(&str, &str); 7] = [
+        ("HELV", "Helvetica"),
+        ("TIMES", "Times"),
+        ("COURIER", "Courier"),
+        ("GREEK", "Greek"),
+        ("MATH", "Math"),
+        ("USER-DEF", "User Defined"),
+        ("EXTENDED", "Extended"),
+    ];
+    let invalid = || error_at(text, value, ParseError::InvalidValue("font"));
+    let upper = value.to_ascii_uppercase();
+    let (prefix, family) = FAMILIES
+        .iter()
+        .find(|(prefix, _)| upper == *prefix || upper.starts_with(&format!("{}-", prefix)))
+        .ok_or_else(invalid)?;
+    let (mut bold, mut italic) = (false, false);
+    for style in upper[prefix.len()..].split('-').skip(1) {
+        match style {
+            "BOLD" => bold = true,
+            "ITALIC" => italic = true,
+            _ => return Err(invalid()),
+        }
+    }
+    Ok((family, bold, italic))
+}
+
+/// Parses a TEXT record, which is positioned in frame coordinates unless `CS` says otherwise.
+fn parse_text_record<'a>(text: &str, input: &'a str) -> Result<(&'a str, TextAnnotation)> {
+    let (rest, options) = preceded(tag_no_case("TEXT"), record_options)(input)
+        .map_err(|e| TecioError::from(e).at(Location::in_text(text, input), None, None))?;
+    let mut annotation = TextAnnotation::new("", [0.0; 3]);
+    annotation.coord_sys = CoordSys::Frame;
+    let mut string = None;
+    for (name, value) in options {
+        let number = |what| option_number::<f64>(text, value, what);
+        match name.to_ascii_uppercase().as_str() {
+            "X" => annotation.anchor[0] = number("X")?,
+            "Y" => annotation.anchor[1] = number("Y")?,
+            "Z" => annotation.anchor[2] = number("Z")?,
+            "T" => string = Some(value),
+            "CS" => annotation.coord_sys = named(text, value, COORD_SYS, "CS")?,
+            "S" => annotation.scope = named(text, value, SCOPES, "S")?,
+            "ZN" => annotation.zone = Some(option_zone(text, value)?),
+            "F" => {
+                let (typeface, bold, italic) = option_font(text, value)?;
+                annotation.typeface = typeface.to_owned();
+                annotation.bold = bold;
+                annotation.italic = italic;
+            }
+            "H" => annotation.height = number("H")?,
+            "HU" => {
+                let units = [
+                    ("GRID", SizeUnits::Grid),
+                    ("FRAME", SizeUnits::Frame),
+                    ("POINT", SizeUnits::Point),
+                ];
+                annotation.size_units = named(text, value, &units, "HU")?
+            }
+            "A" => annotation.angle = number("A")?,
+            "LS" => annotation.line_spacing = number("LS")?,
+            "AN" => {
+                let anchors = [
+                    ("LEFT", TextAnchor::Left),
+                    ("CENTER", TextAnchor::Center),
+                    ("RIGHT", TextAnchor::Right),
+                    ("MIDLEFT", TextAnchor::MidLeft),
+                    ("MIDCENTER", TextAnchor::MidCenter),
+                    ("MIDRIGHT", TextAnchor::MidRight),
+                    ("HEADLEFT", TextAnchor::HeadLeft),
+                    ("HEADCENTER", TextAnchor::HeadCenter),
+                    ("HEADRIGHT", TextAnchor::HeadRight),
+                ];
+                annotation.alignment = named(text, value, &anchors, "AN")?
+            }
+            "BX" => {
+                let boxes = [
+                    ("NOBOX", TextBox::None),
+                    ("FILLED", TextBox::Filled),
+                    ("HOLLOW", TextBox::Hollow),
+                ];
+                annotation.box_type = named(text, value, &boxes, "BX")?
+            }
+            "BXM" => annotation.box_margin = number("BXM")?,
+            "LT" => annotation.box_line_thickness = number("LT")?,
+            "BXO" => annotation.box_color = option_color(text, value)?,
+            "BXF" => annotation.box_fill_color = option_color(text, value)?,
+            "C" => annotation.color = option_color(text, value)?,
+            "MFC" => annotation.macro_function = unescape(value),
+            "CLIPPING" => annotation.clipping = named(text, value, CLIPPINGS, "CLIPPING")?,
+            _ => return Err(error_at(text, name, ParseError::InvalidValue("TEXT option"))),
+        }
+    }
+    let string = string.ok_or_else(|| error_at(text, input, ParseError::InvalidValue("TEXT")))?;
+    annotation.text = unescape(string);
+    Ok((rest, annotation))
+}
+
+/// Parses the options of a GEOMETRY record, with the type id of its shape and the packing of
+/// line points. The shape itself is read by [`read_geometry_shape`].
+fn parse_geometry_options<'a>(
+    text: &str,
+    input: &'a str,
+) -> Result<(&'a str, (Geometry, i32, DataPacking))> {
+    let (rest, options) = preceded(tag_no_case("GEOMETRY"), record_options)(input)
+        .map_err(|e| TecioError::from(e).at(Location::in_text(text, input), None, None))?;
+    let mut geometry = Geometry::new(GeometryShape::Lines(vec![]), [0.0; 3]);
+    // Type ids of `GeometryShape`, 3D lines are 5.
+    let mut shape = 0;
+    let mut packing = DataPacking::Point;
+    for (name, value) in options {
+        let number = |what| option_number::<f64>(text, value, what);
+        match name.to_ascii_uppercase().as_str() {
+            "X" => geometry.anchor[0] = number("X")?,
+            "Y" => geometry.anchor[1] = number("Y")?,
+            "Z" => geometry.anchor[2] = number("Z")?,
+            "T" => {
+                let shapes = [
+                    ("LINE", 0),
+                    ("RECTANGLE", 1),
+                    ("SQUARE", 2),
+                    ("CIRCLE", 3),
+                    ("ELLIPSE", 4),
+                    ("LINE3D", 5),
+                ];
+                shape = named(text, value, &shapes, "geometry type")?
+            }
+            "CS" => geometry.coord_sys = named(text, value, COORD_SYS, "CS")?,
+            "S" => geometry.scope = named(text, value, SCOPES, "S")?,
+            "ZN" => geometry.zone = Some(option_zone(text, value)?),
+            "C" => geometry.color = option_color(text, value)?,
+            "FC" => geometry.fill_color = Some(option_color(text, value)?),
+            "L" => {
+                let patterns = [
+                    ("SOLID", LinePattern::Solid),
+                    ("DASHED", LinePattern::Dashed),
+                    ("DASHDOT", LinePattern::DashDot),
+                    ("DASHDOTDOT", LinePattern::DashDotDot),
+                    ("DOTTED", LinePattern::Dotted),
+                    ("LONGDASH", LinePattern::LongDash),
+                ];
+                geometry.line_pattern = named(text, value, &patterns, "L")?
+            }
+            "PL" => geometry.pattern_length = number("PL")?,
+            "LT" => geometry.line_thickness = number("LT")?,
+            "EP" => geometry.num_ellipse_points = option_number(text, value, "EP")?,
+            "AST" => {
+                let styles = [
+                    ("PLAIN", ArrowheadStyle::Plain),
+                    ("FILLED", ArrowheadStyle::Filled),
+                    ("HOLLOW", ArrowheadStyle::Hollow),
+                ];
+                geometry.arrowhead_style = named(text, value, &styles, "AST")?
+            }
+            "AAT" => {
+                let attachments = [
+                    ("NONE", ArrowheadAttachment::None),
+                    ("BEGINNING", ArrowheadAttachment::Beginning),
+                    ("END", ArrowheadAttachment::End),
+                    ("BOTH", ArrowheadAttachment::Both),
+                ];
+                geometry.arrowhead_attachment = named(text, value, &attachments, "AAT")?
+            }
+            "ASZ" => geometry.arrowhead_size = number("ASZ")?,
+            "AAN" => geometry.arrowhead_angle = number("AAN")?,
+            "MFC" => geometry.macro_function = value.to_owned(),
+            "CLIPPING" => geometry.clipping = named(text, value, CLIPPINGS, "CLIPPING")?,
+            "DRAWORDER" => {
+                let orders =
+                    [("AFTERDATA", DrawOrder::AfterData), ("BEFOREDATA", DrawOrder::BeforeData)];
+                geometry.draw_order = named(text, value, &orders, "DRAWORDER")?
+            }
+            "F" => {
+                let packings = [("POINT", DataPacking::Point), ("BLOCK", DataPacking::Block)];
+                packing = named(text, value, &packings, "F")?
+            }
+            // Values are always read as doubles.
+            "DT" => {
+                named(text, value, &[("SINGLE", ()), ("DOUBLE", ())], "DT")?;
+            }
+            _ => return Err(error_at(text, name, ParseError::InvalidValue("GEOMETRY option"))),
+        }
+    }
+
+    if shape == 5 {
+        geometry.coord_sys = CoordSys::Grid3D;
+    }
+    Ok((rest, (geometry, shape, packing)))
+}
+
+/// Reads the size of a shape or, for lines, the number of polylines and the point count and
+/// points of each of them.
+fn read_geometry_shape<R: BufRead>(
+    reader: &mut TokenReader<R>,
+    shape: i32,
+    packing: DataPacking,
+) -> Result<GeometryShape> {
+    Ok(match shape {
+        0 | 5 => {
+            let dims = if shape == 5 { 3 } else { 2 };
+            let num_lines = reader.count()?;
+            let mut lines = vec![];
+            for _ in 0..num_lines {
+                let n = reader.count()?;
+                let v = reader.values::<f64>(n.saturating_mul(dims), None)?;
+                let coord = |i: usize| -> Vec<f64> {
+                    match packing {
+                        DataPacking::Point => v.iter().skip(i).step_by(dims).copied().collect(),
+                        DataPacking::Block => v[i * n..(i + 1) * n].to_vec(),
+                    }
+                };
+                let z = if dims == 3 { coord(2) } else { vec![] };
+                lines.push(Polyline { x: coord(0), y: coord(1), z });
+            }
+            GeometryShape::Lines(lines)
+        }
+        1 => {
+            let v = reader.values(2, None)?;
+            GeometryShape::Rectangle { width: v[0], height: v[1] }
+        }
+        2 => GeometryShape::Square { size: reader.values(1, None)?[0] },
+        3 => GeometryShape::Circle { radius: reader.values(1, None)?[0] },
+        _ => {
+            let v = reader.values(2, None)?;
+            GeometryShape::Ellipse { horizontal_axis: v[0], vertical_axis: v[1] }
+        }
+    })
+}
+
+/// `CUSTOMLABELS "a" "b"` record.
+fn custom_labels(input: &str) -> IResult<&str, Vec<&str>, ParseError> {
+    preceded(tuple((tag_no_case("CUSTOMLABELS"), space1)), separated_list(separ, word))(input)
+}
+
+/// `DATASETAUXDATA name="value"` record.
+fn dataset_aux_data(input: &str) -> IResult<&str, (&str, &str), ParseError> {
+    preceded(tag_no_case("DATASETAUXDATA"), aux_pair)(input)
+}
+
+/// `VARAUXDATA 1 name="value"` record, the variable is one-based.
+fn var_aux_data(input: &str) -> IResult<&str, (&str, &str, &str), ParseError> {
+    let (rest, (var, (name, value))) =
+        preceded(tag_no_case("VARAUXDATA"), pair(preceded(space1, digit1), aux_pair))(input)?;
+    Ok((rest, (var, name, value)))
+}
 
+/// Fails unless only separators are left of a record.
+fn finished(text: &str, rest: &str, error: ParseError) -> Result<()> {
+    let rest = rest.trim_start_matches(is_separator);
+    match rest.is_empty() {
+        true => Ok(()),
+        false => Err(error_at(text, rest, error)),
+    }
+}
 
-        let (rest, header) = parse_header(rest)?;
+/// Zone read by [`DatZones`]. Shared and passive variables are left empty, shared ones are found
+/// in the zone [`TecZone::shared_from`] names.
+#[derive(Debug, Clone)]
+pub struct DatZone {
+    pub zone: TecZone,
+    block: DataBlock,
+}
 
-        let (rest, z) = many0(|rest| parse_zone(rest, header.var_list.len()))(rest)?;
+impl DatZone {
+    /// Values of the one-based variable `var_id`.
+    pub fn get_data(&self, var_id: usize) -> TecData<'_> {
+        self.block.get_data(var_id - 1)
+    }
 
+    /// Node map of a classic FE zone, `None` if it is shared.
+    pub fn get_connectivity(&self) -> Option<TecData<'_>> {
+        self.block.connectivity.as_ref().map(|c| TecData::from(c.as_view()))
+    }
+
+    /// Face connectivity of a polygonal or polyhedral zone, `None` if it is shared.
+    pub fn get_poly_faces(&self) -> Option<&PolyFaces> {
+        self.block.poly_faces.as_ref()
+    }
+}
+
+/// Iterator over the zones of a DAT file, which reads one zone at a time. Records between the
+/// zones are added to [`DatZones::dataset`] as they are passed.
+pub struct DatZones<R> {
+    reader: TokenReader<R>,
+    dataset: Dataset,
+    zones: Vec<TecZone>,
+    done: bool,
+}
+
+impl<R: BufRead> DatZones<R> {
+    fn new(input: R) -> Result<Self> {
+        let mut reader = TokenReader::new(input);
+        let chunk = reader.record()?.unwrap_or(Chunk { text: String::new(), line: 1 });
+        let header = chunk.parse(|text| {
+            let (rest, header) = parse_header(text)
+                .map_err(|e| TecioError::from(e).at(Location::in_text(text, text), None, None))?;
+            finished(text, rest, ParseError::InvalidValue("record"))?;
+            Ok(header)
+        })?;
 
         let dataset = Dataset {
             num_variables: header.var_list.len() as _,
-            num_zones: z.len() as _,
+            num_zones: 0,
             title: header.title,
             var_names: header.var_list,
+            aux_data: header.aux_data,
+            var_aux_data: vec![],
+            geometries: vec![],
+            texts: vec![],
+            custom_labels: vec![],
+            user_records: vec![],
         };
-        let mut zones = Vec::with_capacity(z.len());
-        let mut data_blocks = Vec::with_capacity(z.len());
-        z.into_iter().enumerate().for_each(|(id, (mut zone, block))| {
-            match &mut zone{
-                TecZone::Ordered(o) => o.id = id as i32 + 1,
-                TecZone::ClassicFE(o) => o.id = id as i32 + 1,
-                _ => unimplemented!()
+        Ok(Self {
+            reader,
+            dataset,
+            zones: vec![],
+            done: false,
+        })
+    }
+
+    /// Header of the file with the records read so far, complete once all zones are read.
+    pub fn dataset(&self) -> &Dataset {
+        &self.dataset
+    }
+
+    /// Reads records up to and including the next zone.
+    fn next_zone(&mut self) -> Result<Option<DatZone>> {
+        let var_num = self.dataset.var_names.len();
+        while let Some(chunk) = self.reader.record()? {
+            let dataset = &mut self.dataset;
+            let start = chunk.text.trim_start();
+            let (_, record) = alphanumeric1::<_, ParseError>(start).unwrap_or((start, ""));
+            match record.to_ascii_uppercase().as_str() {
+                "ZONE" => {
+                    let zone_num = Some(self.zones.len() + 1);
+                    let in_zone = |e: TecioError| {
+                        e.at(Location::LineColumn { line: chunk.line, column: 1 }, zone_num, None)
+                    };
+                    let previous = &self.zones;
+                    let (mut zone, data_pack) = chunk
+                        .parse(|text| {
+                            let (rest, zone, data_pack) =
+                                parse_zone_header(text, text.trim_start(), var_num, previous)?;
+                            finished(text, rest, ParseError::WrongHeaderTag)?;
+                            Ok((zone, data_pack))
+                        })
+                        .map_err(in_zone)?;
+                    let block =
+                        read_zone_data(&mut self.reader, &zone, data_pack).map_err(in_zone)?;
+                    let id = self.zones.len() as i32 + 1;
+                    match &mut zone {
+                        TecZone::Ordered(o) => o.id = id,
+                        TecZone::ClassicFE(o) => o.id = id,
+                        TecZone::PolyFE(o) => o.id = id,
+                    }
+                    dataset.num_zones = id;
+                    self.zones.push(zone.clone());
+                    return Ok(Some(DatZone { zone, block }));
+                }
+                "TEXT" => {
+                    let text = chunk.parse(|text| {
+                        let (rest, annotation) = parse_text_record(text, text.trim_start())?;
+                        finished(text, rest, ParseError::InvalidValue("TEXT option"))?;
+                        Ok(annotation)
+                    })?;
+                    dataset.texts.push(text);
+                }
+                "GEOMETRY" => {
+                    let (mut geometry, shape, packing) = chunk.parse(|text| {
+                        let (rest, options) = parse_geometry_options(text, text.trim_start())?;
+                        finished(text, rest, ParseError::InvalidValue("GEOMETRY option"))?;
+                        Ok(options)
+                    })?;
+                    geometry.shape = read_geometry_shape(&mut self.reader, shape, packing)?;
+                    dataset.geometries.push(geometry);
+                }
+                "CUSTOMLABELS" => {
+                    let labels = chunk.parse(|text| {
+                        let located =
+                            |e| TecioError::from(e).at(Location::in_text(text, start), None, None);
+                        let (rest, labels) = custom_labels(text.trim_start()).map_err(located)?;
+                        let error = ParseError::InvalidValue("CUSTOMLABELS");
+                        if labels.is_empty() {
+                            return Err(error_at(text, text.trim_start(), error));
+                        }
+                        finished(text, rest, error)?;
+                        Ok(labels.into_iter().map(unescape).collect())
+                    })?;
+                    dataset.custom_labels.push(labels);
+                }
+                "DATASETAUXDATA" => {
+                    let (name, value) = chunk.parse(|text| {
+                        let located =
+                            |e| TecioError::from(e).at(Location::in_text(text, start), None, None);
+                        let (rest, (name, value)) =
+                            dataset_aux_data(text.trim_start()).map_err(located)?;
+                        finished(text, rest, ParseError::InvalidValue("DATASETAUXDATA"))?;
+                        Ok((name.to_owned(), unescape(value)))
+                    })?;
+                    dataset.aux_data.insert(name, value);
+                }
+                "VARAUXDATA" => {
+                    let (var, name, value) = chunk.parse(|text| {
+                        let located =
+                            |e| TecioError::from(e).at(Location::in_text(text, start), None, None);
+                        let (rest, (var, name, value)) =
+                            var_aux_data(text.trim_start()).map_err(located)?;
+                        finished(text, rest, ParseError::InvalidValue("VARAUXDATA"))?;
+                        let index = var.parse::<usize>().unwrap_or(usize::MAX);
+                        if !(1..=var_num).contains(&index) {
+                            let max = var_num as i64;
+                            let error = ParseError::IndexOutOfRange { index: index as i64, max };
+                            return Err(error_at(text, var, error));
+                        }
+                        Ok((index, name.to_owned(), unescape(value)))
+                    })?;
+                    dataset.var_aux_data.resize(var_num, AuxData::new());
+                    dataset.var_aux_data[var - 1].insert(name, value);
+                }
+                _ => {
+                    let error = ParseError::InvalidValue("record");
+                    return Err(chunk.parse(|text| Err(error_at(text, text.trim_start(), error)))?);
+                }
             }
-            zones.push(zone);
-            data_blocks.push(block);
-        });
-        Ok(
-            Self {
-                dataset,
-                zones,
-                data_blocks,
+        }
+        Ok(None)
+    }
+}
+
+impl<R: BufRead> Iterator for DatZones<R> {
+    type Item = Result<DatZone>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let zone = self.next_zone().transpose();
+        self.done = !matches!(zone, Some(Ok(_)));
+        zone
+    }
+}
+
+impl DatFormat {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_zones(Self::stream(BufReader::new(File::open(path)?))?)
+    }
+
+    /// Parses the contents of a DAT file.
+    pub fn read(file: &str) -> Result<Self> {
+        Self::from_zones(Self::stream(file.as_bytes())?)
+    }
+
+    /// Reads the header of a DAT file, the zones are read one at a time by the returned iterator.
+    pub fn stream<R: BufRead>(input: R) -> Result<DatZones<R>> {
+        DatZones::new(input)
+    }
+
+    fn from_zones<R: BufRead>(mut stream: DatZones<R>) -> Result<Self> {
+        let mut zones = vec![];
+        let mut data_blocks = vec![];
+        for zone in &mut stream {
+            let zone = zone?;
+            zones.push(zone.zone);
+            data_blocks.push(zone.block);
+        }
+        Ok(Self {
+            dataset: stream.dataset,
+            zones,
+            data_blocks,
+        })
+    }
+
+    /// Values of a variable, following variable sharing.
+    pub fn get_data(&self, zone_id: usize, var_id: usize) -> TecData<'_> {
+        match self.zones[zone_id - 1].shared_from(var_id) {
+            Some(source) => self.get_data(source, var_id),
+            None => self.data_blocks[zone_id - 1].get_data(var_id - 1),
+        }
+    }
+
+    /// Node map of a classic FE zone, following connectivity sharing.
+    pub fn get_connectivity(&self, zone_id: usize) -> Option<TecData<'_>> {
+        match self.zones[zone_id - 1].connectivity_shared_from() {
+            Some(source) => self.get_connectivity(source),
+            None => {
+                let connectivity = self.data_blocks[zone_id - 1].connectivity.as_ref();
+                connectivity.map(|c| TecData::from(c.as_view()))
             }
-        )
+        }
+    }
+
+    /// Face connectivity of a polygonal or polyhedral zone, following connectivity sharing.
+    pub fn get_poly_faces(&self, zone_id: usize) -> Option<PolyFaces> {
+        match self.zones[zone_id - 1].connectivity_shared_from() {
+            Some(source) => self.get_poly_faces(source),
+            None => self.data_blocks[zone_id - 1].poly_faces.clone(),
+        }
     }
 }
 
@@ -749,12 +1825,7 @@ pub struct DatHeader {
     title: String,
     filetype: FileType,
     var_list: Vec<String>,
-}
-
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-enum DataPacking {
-    Point,
-    Block,
+    aux_data: AuxData,
 }
 
 
@@ -771,11 +1842,302 @@ mod tests {
             println!("{:?}", c);
         }
     }
-}
 
+    #[test]
+    fn polyhedron_test() {
+        use crate::{PolyFaces, TecZone};
+
+        let dat = DatFormat::open("./tests/polyhedron.dat").unwrap();
+        match &dat.zones[0] {
+            TecZone::PolyFE(z) => assert_eq!((z.nodes, z.cells, z.faces), (4, 1, 4)),
+            z => panic!("Expected polyhedral zone, got {:?}", z),
+        }
+        assert_eq!(dat.data_blocks[0].get_data(3).as_f64(), vec![42.0]);
+        assert_eq!(
+            dat.data_blocks[0].poly_faces,
+            Some(PolyFaces {
+                face_node_counts: vec![3; 4],
+                face_nodes: vec![1, 3, 2, 1, 2, 4, 2, 3, 4, 3, 1, 4],
+                face_left_elements: vec![1; 4],
+                face_right_elements: vec![0, 0, 0, -1],
+                boundary_connection_counts: vec![1],
+                boundary_connection_elements: vec![1],
+                boundary_connection_zones: vec![2],
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_poly_faces() {
+        use crate::{Location, ParseError, TecioError};
+
+        let original = std::fs::read_to_string("./tests/polyhedron.dat").unwrap();
+        let with_line = |line: usize, text: &str| {
+            let mut lines = original.lines().collect::<Vec<_>>();
+            lines[line - 1] = text;
+            lines.join("\n")
+        };
+        for &(line, text, error, column) in [
+            (14, " 3 3 3 2", "TOTALNUMFACENODES", 8),
+            (14, " 3 3 -3 3", "face node count", 6),
+            (14, " 3 3 3 3000000000", "face node count", 8),
+            (19, " 1 1 2 1", "face element", 6),
+            (20, " 0 0 0 -2", "face element", 8),
+            (21, " 2", "TOTALNUMBOUNDARYCONNECTIONS", 2),
+            (21, " 0", "boundary connection count", 2),
+            (23, " 3000000000", "boundary connection zone", 2),
+        ]
+        .iter()
+        {
+            match DatFormat::read(&with_line(line, text)) {
+                Err(TecioError::Malformed {
+                    error: ParseError::InvalidValue(what),
+                    location,
+                    zone: Some(1),
+                    var: None,
+                }) => {
+                    assert_eq!(what, error);
+                    assert_eq!(location, Location::LineColumn { line, column });
+                }
+                r => panic!("Expected invalid {}, got {:?}", error, r.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_input() {
+        use crate::{Location, ParseError, TecioError};
+
+        let bad_number = "VARIABLES = \"X\" \"Y\"\nZONE I=2, DATAPACKING=BLOCK\n1 2\n3 x4\n";
+        match DatFormat::read(bad_number) {
+            Err(TecioError::Malformed {
+                error: ParseError::InvalidValue("number"),
+                location,
+                zone: Some(1),
+                var: Some(2),
+            }) => assert_eq!(location, Location::LineColumn { line: 4, column: 3 }),
+            r => panic!("Expected invalid number, got {:?}", r.map(|_| ())),
+        }
+
+        let bad_location = "VARIABLES = \"X\" \"Y\"\n\
+                            ZONE I=2, DATAPACKING=BLOCK, VARLOCATION=([3]=CELLCENTERED)\n\
+                            1 2\n3 4\n";
+        match DatFormat::read(bad_location) {
+            Err(e @ TecioError::Malformed { .. }) => assert_eq!(
+                e.parse_error(),
+                Some(ParseError::IndexOutOfRange { index: 3, max: 2 })
+            ),
+            r => panic!("Expected out of range var, got {:?}", r.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn oversized_counts() {
+        use crate::{ParseError, TecioError};
+
+        let huge = DatFormat::read("VARIABLES = \"X\"\nZONE I=1e30\n1 2 3\n");
+        match huge {
+            Err(e @ TecioError::Malformed { .. }) => assert!(matches!(
+                e.parse_error(),
+                Some(ParseError::TooLarge { count: _, available: 6 })
+            )),
+            r => panic!("Expected too many values, got {:?}", r.map(|_| ())),
+        }
+        let negative = DatFormat::read("VARIABLES = \"X\"\nZONE N=-3, E=1, ZONETYPE=FETRIANGLE\n");
+        assert_eq!(
+            negative.err().and_then(|e| e.parse_error()),
+            Some(ParseError::InvalidValue("zone size"))
+        );
+    }
+
+    #[test]
+    fn point_packing() {
+        use crate::{Location, ParseError, TecData, TecioError};
+
+        let fe = "VARIABLES = \"X\" \"Y\" \"N\"\n\
+                  ZONE N=4, E=2, ZONETYPE=FETRIANGLE, DATAPACKING=POINT, DT=(SINGLE DOUBLE SHORTINT)\n\
+                  0 0 1\n1 0 2\n1 1 3\n0 1 4\n\
+                  1 2 3\n1 3 4\n";
+        let dat = DatFormat::read(fe).unwrap();
+        let block = &dat.data_blocks[0];
+        assert_eq!(block.get_data(0).as_f32(), vec![0.0, 1.0, 1.0, 0.0]);
+        assert_eq!(block.get_data(1).as_f64(), vec![0.0, 0.0, 1.0, 1.0]);
+        match &block.data[2].1 {
+            TecData::I16(v) => assert_eq!(v.as_ref(), &[1, 2, 3, 4]),
+            d => panic!("Expected short integers, got {:?}", d),
+        }
+        let connectivity = block.connectivity.as_ref().unwrap();
+        assert_eq!(connectivity.to_vec::<i32>().unwrap(), vec![1, 2, 3, 1, 3, 4]);
+
+        let cell_centered = "VARIABLES = \"X\" \"P\"\n\
+                             ZONE I=2, DATAPACKING=POINT, VARLOCATION=([2]=CELLCENTERED)\n\
+                             0 1\n1 1\n";
+        match DatFormat::read(cell_centered) {
+            Err(TecioError::Malformed { error: ParseError::InvalidValue(_), var: Some(2), .. }) => {}
+            r => panic!("Expected cell centered var to be rejected, got {:?}", r.map(|_| ())),
+        }
 
+        let bad_node = fe.replace("1 3 4\n", "1 3 5\n");
+        match DatFormat::read(&bad_node) {
+            Err(TecioError::Malformed { error, location, zone: Some(1), .. }) => {
+                assert_eq!(error, ParseError::IndexOutOfRange { index: 5, max: 4 });
+                assert_eq!(location, Location::LineColumn { line: 8, column: 5 });
+            }
+            r => panic!("Expected node index out of range, got {:?}", r.map(|_| ())),
+        }
+    }
 
+    #[test]
+    fn zone_sharing() {
+        use crate::{ParseError, TecioError};
+
+        let shared = "VARIABLES = \"X\" \"Y\" \"P\"\n\
+                      ZONE T=\"first\", N=3, E=1, ZONETYPE=FETRIANGLE, DATAPACKING=BLOCK\n\
+                      0 1 0\n0 0 1\n5 6 7\n1 2 3\n\
+                      ZONE T=\"second\", N=3, E=1, ZONETYPE=FETRIANGLE, DATAPACKING=BLOCK,\n\
+                      VARSHARELIST=([1]=1), PASSIVEVARLIST=[3], PARENTZONE=1,\n\
+                      CONNECTIVITYSHAREZONE=1, AUXDATA Kind=\"copy\"\n\
+                      2 3 4\n";
+        let dat = DatFormat::read(shared).unwrap();
+        let second = &dat.zones[1];
+        assert_eq!(second.shared_from(1), Some(1));
+        assert!(second.is_passive(3));
+        assert_eq!(second.parent_zone(), Some(1));
+        assert_eq!(second.connectivity_shared_from(), Some(1));
+        assert_eq!(dat.get_data(2, 1).as_f32(), vec![0.0, 1.0, 0.0]);
+        assert_eq!(dat.get_data(2, 2).as_f32(), vec![2.0, 3.0, 4.0]);
+        assert!(dat.get_data(2, 3).as_f32().is_empty());
+        assert_eq!(dat.get_connectivity(2).unwrap().to_vec::<i32>().unwrap(), vec![1, 2, 3]);
+
+        let ahead = shared.replace("([1]=1)", "([1]=2)");
+        match DatFormat::read(&ahead) {
+            Err(TecioError::Malformed { error, zone: Some(2), .. }) => {
+                assert_eq!(error, ParseError::IndexOutOfRange { index: 2, max: 1 })
+            }
+            r => panic!("Expected share zone out of range, got {:?}", r.map(|_| ())),
+        }
+    }
 
+    #[test]
+    fn records() {
+        use crate::{
+            CoordSys, GeometryShape, Location, ParseError, Polyline, TecioError, TextAnchor,
+        };
 
+        let dat = "VARIABLES = \"X\" \"Y\"\n\
+                   TEXT X=10, Y=90, T=\"Probe\", F=TIMES-BOLD, C=RED, AN=MIDCENTER\n\
+                   GEOMETRY X=1, Y=2, T=LINE, CS=GRID, C=BLUE, ZN=1\n\
+                   1\n3\n0 0\n1 0\n1 1\n\
+                   ZONE I=2\n0 1\n0 0\n\
+                   GEOMETRY T=CIRCLE, FC=CUST2\n0.5\n\
+                   CUSTOMLABELS \"low\" \"high\"\n\
+                   DATASETAUXDATA Solver=\"steady\"\n\
+                   VARAUXDATA 2 Units=\"m\"\n";
+        let dat = DatFormat::read(dat).unwrap();
+        let dataset = &dat.dataset;
+        assert_eq!(dat.zones.len(), 1);
+
+        let text = &dataset.texts[0];
+        assert_eq!(text.text, "Probe");
+        assert_eq!(text.anchor, [10.0, 90.0, 0.0]);
+        assert_eq!(text.coord_sys, CoordSys::Frame);
+        assert_eq!((text.typeface.as_str(), text.bold, text.italic), ("Times", true, false));
+        assert_eq!(text.color, 1);
+        assert_eq!(text.alignment, TextAnchor::MidCenter);
+
+        let line = &dataset.geometries[0];
+        let points = Polyline { x: vec![0.0, 1.0, 1.0], y: vec![0.0, 0.0, 1.0], z: vec![] };
+        assert_eq!(line.shape, GeometryShape::Lines(vec![points]));
+        assert_eq!(line.anchor, [1.0, 2.0, 0.0]);
+        assert_eq!((line.color, line.zone), (3, Some(1)));
+        let circle = &dataset.geometries[1];
+        assert_eq!(circle.shape, GeometryShape::Circle { radius: 0.5 });
+        assert_eq!(circle.fill_color, Some(9));
+
+        assert_eq!(dataset.custom_labels, vec![vec!["low".to_owned(), "high".to_owned()]]);
+        assert_eq!(dataset.aux_data["Solver"], "steady");
+        assert!(dataset.var_aux_data(1).unwrap().is_empty());
+        assert_eq!(dataset.var_aux_data(2).unwrap()["Units"], "m");
+
+        let unknown = "VARIABLES = \"X\"\nZONE I=1\n0\nLABELS \"a\"\n";
+        match DatFormat::read(unknown) {
+            Err(TecioError::Malformed { error, location, .. }) => {
+                assert_eq!(error, ParseError::InvalidValue("record"));
+                assert_eq!(location, Location::LineColumn { line: 4, column: 1 });
+            }
+            r => panic!("Expected unknown record to be rejected, got {:?}", r.map(|_| ())),
+        }
+    }
 
+    #[test]
+    fn loose_grammar() {
+        use crate::{Location, ParseError, TecioError};
+
+        let dat = "# Written by another tool\n\
+                   title = \"Loose\"\n\
+                   variables = \"X\" \"P\"\n\
+                   zone t=\"block\", i=4, datapacking=block, dt=(single double)\n\
+                   \t# comments may be indented\n\
+                   -.5 .25 2*1e-1\n\
+                   1.5D+00, 2*-.5 0\n\
+                   Zone I=2, DataPacking=Point\n\
+                   2*7 1 -2.\n";
+        let dat = DatFormat::read(dat).unwrap();
+        assert_eq!(dat.dataset.title, "Loose");
+        assert_eq!(dat.zones[0].name(), "block");
+        assert_eq!(dat.get_data(1, 1).as_f32(), vec![-0.5, 0.25, 0.1, 0.1]);
+        assert_eq!(dat.get_data(1, 2).as_f64(), vec![1.5, -0.5, -0.5, 0.0]);
+        assert_eq!(dat.get_data(2, 1).as_f32(), vec![7.0, 1.0]);
+        assert_eq!(dat.get_data(2, 2).as_f32(), vec![7.0, -2.0]);
+
+        let overlong = "# header\nVARIABLES = \"X\"\nZONE I=2\n3*1\n";
+        match DatFormat::read(overlong) {
+            Err(TecioError::Malformed { error, location, var: Some(1), .. }) => {
+                assert_eq!(error, ParseError::InvalidValue("repeat count"));
+                assert_eq!(location, Location::LineColumn { line: 4, column: 1 });
+            }
+            r => panic!("Expected repeat past the zone to be rejected, got {:?}", r.map(|_| ())),
+        }
+    }
 
+    #[test]
+    fn zone_stream() {
+        use crate::{ParseError, TecioError};
+        use std::io::BufReader;
+
+        let dat = "VARIABLES = \"X\" \"P\"\n\
+                   ZONE T=\"first\", I=3, VARLOCATION=([2]=CELLCENTERED)\n\
+                   0 1 2\n5 6\n\
+                   TEXT X=50, Y=50, T=\"Between\"\n\
+                   ZONE T=\"second\", N=3, E=1, ZONETYPE=FETRIANGLE, DATAPACKING=POINT\n\
+                   0 1\n1 2\n2 3\n1 2 3\n";
+        // A tiny buffer makes lines arrive in pieces.
+        let mut stream = DatFormat::stream(BufReader::with_capacity(4, dat.as_bytes())).unwrap();
+        assert_eq!(stream.dataset().var_names.len(), 2);
+
+        let first = stream.next().unwrap().unwrap();
+        assert_eq!(first.zone.name(), "first");
+        assert_eq!(first.get_data(1).as_f32(), vec![0.0, 1.0, 2.0]);
+        assert_eq!(first.get_data(2).as_f32(), vec![5.0, 6.0]);
+        assert!(stream.dataset().texts.is_empty());
+
+        let second = stream.next().unwrap().unwrap();
+        assert_eq!(stream.dataset().texts[0].text, "Between");
+        assert_eq!(second.get_data(2).as_f32(), vec![1.0, 2.0, 3.0]);
+        assert_eq!(second.get_connectivity().unwrap().to_vec::<i32>().unwrap(), vec![1, 2, 3]);
+        assert!(stream.next().is_none());
+        assert_eq!(stream.dataset().num_zones, 2);
+
+        let truncated = dat.replace("1 2 3\n", "1 2\n");
+        let mut stream = DatFormat::stream(truncated.as_bytes()).unwrap();
+        assert!(stream.next().unwrap().is_ok());
+        match stream.next() {
+            Some(Err(e @ TecioError::Malformed { zone: Some(2), .. })) => assert!(matches!(
+                e.parse_error(),
+                Some(ParseError::TooLarge { count: 3, available: 5 })
+            )),
+            r => panic!("Expected truncated node map, got {:?}", r.map(|r| r.map(|_| ()))),
+        }
+        assert!(stream.next().is_none());
+    }
+}
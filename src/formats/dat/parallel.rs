@@ -0,0 +1,369 @@
+//! Parallel parsing of DAT values, enabled by the `parallel` feature.
+//!
+//! The lines holding a block of values are read ahead in windows, split at line ends into chunks
+//! and parsed on the rayon thread pool. Values, errors and the position the reader is left at are
+//! the same as when reading the values one by one.
+
+use std::{borrow::Cow, io::BufRead, str::FromStr};
+
+use rayon::prelude::*;
+
+use super::{bytes, is_separator, parse_number, TokenReader, PREALLOCATED};
+use crate::{
+    common::{Location, ParseError, Result, TecDataType, TecioError},
+    TecData,
+};
+
+/// Whether to read `n` values in parallel. Reading ahead only pays off for large blocks and with
+/// more than one thread, tests take the parallel path for any block.
+pub(super) fn enabled(n: usize) -> bool {
+    cfg!(test) || (n >= 1 << 12 && rayon::current_num_threads() > 1)
+}
+
+/// Bytes of value text read ahead at once.
+const WINDOW: usize = if cfg!(test) { 1 << 8 } else { 1 << 22 };
+/// Bytes of value text parsed by one task.
+const CHUNK: usize = if cfg!(test) { 1 << 5 } else { 1 << 16 };
+
+/// A line read ahead, its values are in `text[skip..end]` of the section.
+struct Line {
+    start: usize,
+    skip: usize,
+    end: usize,
+    line_no: usize,
+    /// Index of the first value of the line in the section.
+    first: usize,
+    count: usize,
+}
+
+/// Text of the lines holding the next values of a block.
+struct Section {
+    text: String,
+    lines: Vec<Line>,
+    count: usize,
+}
+
+/// Count of a `k*value` token, invalid counts are reported when the token is parsed.
+fn repeat_count(token: &str) -> Option<(usize, usize)> {
+    let i = token.find('*')?;
+    token[..i].parse().ok().filter(|&k| k > 1).map(|k| (k, i))
+}
+
+impl<R: BufRead> TokenReader<R> {
+    /// Reads ahead the lines holding up to `n` values, stopping after about `WINDOW` bytes. The
+    /// reader is left after the last value, a `k*value` token reaching past it stays pending.
+    fn section(&mut self, n: usize) -> Result<Section> {
+        let mut section = Section {
+            text: String::new(),
+            lines: Vec::new(),
+            count: 0,
+        };
+        while section.count < n && section.text.len() < WINDOW {
+            if !self.skip_separators()? {
+                break;
+            }
+            let (skip, first) = (self.pos, section.count);
+            let mut last = self.pos;
+            while section.count < n {
+                let start = match self.line[self.pos..].find(|c| !is_separator(c)) {
+                    Some(i) => self.pos + i,
+                    None => {
+                        self.pos = self.line.len();
+                        break;
+                    }
+                };
+                let end =
+                    self.line[start..].find(is_separator).map_or(self.line.len(), |i| start + i);
+                let token = &self.line[start..end];
+                let left = n - section.count;
+                match repeat_count(token) {
+                    Some((k, i)) if k > left => {
+                        let value = token[i + 1..].to_owned();
+                        self.repeat = Some((k - left, value, self.location(start)));
+                        section.count = n;
+                    }
+                    Some((k, _)) => section.count += k,
+                    None => section.count += 1,
+                }
+                self.pos = end;
+                last = start;
+            }
+            self.at = self.location(last);
+            let start = section.text.len();
+            section.text.push_str(&self.line[..self.pos]);
+            section.lines.push(Line {
+                start,
+                skip: start + skip,
+                end: section.text.len(),
+                line_no: self.line_no,
+                first,
+                count: section.count - first,
+            });
+        }
+        Ok(section)
+    }
+}
+
+impl Section {
+    /// Parses the values in chunks on the thread pool. `push` adds the value with the given index
+    /// in the section to the buffer of a chunk, `var` gives the variable of an index for errors.
+    fn parse<S, P, V>(&self, buffer: impl Fn() -> S + Sync, push: P, var: V) -> Result<Vec<S>>
+    where
+        S: Send,
+        P: Fn(&mut S, usize, &str) -> std::result::Result<(), ParseError> + Sync,
+        V: Fn(usize) -> Option<usize> + Sync,
+    {
+        let mut chunks = Vec::new();
+        let mut first = 0;
+        for (i, line) in self.lines.iter().enumerate() {
+            if line.end - self.lines[first].start >= CHUNK || i + 1 == self.lines.len() {
+                chunks.push(first..i + 1);
+                first = i + 1;
+            }
+        }
+        let parsed = chunks
+            .into_par_iter()
+            .map(|lines| {
+                let mut values = buffer();
+                for line in &self.lines[lines] {
+                    self.parse_line(line, &mut values, &push, &var)?;
+                }
+                Ok(values)
+            })
+            .collect::<Vec<Result<S>>>();
+        // The first error in the order of the values, as when reading them one by one.
+        parsed.into_iter().collect()
+    }
+
+    fn parse_line<S, P, V>(&self, line: &Line, values: &mut S, push: &P, var: &V) -> Result<()>
+    where
+        P: Fn(&mut S, usize, &str) -> std::result::Result<(), ParseError>,
+        V: Fn(usize) -> Option<usize>,
+    {
+        let text = &self.text[..line.end];
+        let error = |error: ParseError, at: usize, index: usize| {
+            let location = Location::LineColumn {
+                line: line.line_no,
+                column: text[line.start..at].chars().count() + 1,
+            };
+            TecioError::from(error).at(location, None, var(index))
+        };
+        let (mut pos, mut index, end) = (line.skip, line.first, line.first + line.count);
+        while index < end {
+            let start = text[pos..].find(|c| !is_separator(c)).map_or(text.len(), |i| pos + i);
+            pos = text[start..].find(is_separator).map_or(text.len(), |i| start + i);
+            let token = &text[start..pos];
+            let (count, value, at) = match token.find('*') {
+                Some(i) => match token[..i].parse::<usize>().ok().filter(|&k| k >= 1) {
+                    Some(k) => (k, &token[i + 1..], start + i + 1),
+                    None => {
+                        let repeat = ParseError::InvalidValue("repeat count");
+                        return Err(error(repeat, start, index));
+                    }
+                },
+                None => (1, token, start),
+            };
+            for copy in 0..count.min(end - index) {
+                // Copies after the first are located at the token, like in `TokenReader::value`.
+                let at = if copy == 0 { at } else { start };
+                push(values, index, value).map_err(|e| error(e, at, index))?;
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a block of `n` values, like [`TokenReader::values`].
+fn values<T: FromStr + Send, R: BufRead>(
+    reader: &mut TokenReader<R>,
+    n: usize,
+    var: Option<usize>,
+) -> Result<Vec<T>> {
+    let start = reader.mark();
+    let mut values = Vec::with_capacity(n.min(PREALLOCATED));
+    while values.len() < n {
+        let section = reader.section(n - values.len())?;
+        let chunks = section.parse(
+            Vec::new,
+            |chunk: &mut Vec<T>, _, token| {
+                chunk.push(parse_number(token, "number")?);
+                Ok(())
+            },
+            |_| var,
+        )?;
+        if section.count == 0 {
+            return Err(reader.truncated(start, n, var));
+        }
+        for chunk in chunks {
+            values.extend(chunk);
+        }
+    }
+    reader.end_block(var)?;
+    Ok(values)
+}
+
+/// Reads `n` values of a variable stored as `ty`, like [`super::read_values`].
+pub(super) fn read_values<R: BufRead>(
+    reader: &mut TokenReader<R>,
+    n: usize,
+    ty: TecDataType,
+    var: Option<usize>,
+) -> Result<TecData<'static>> {
+    Ok(match ty {
+        TecDataType::F32 => TecData::from(values::<f32, R>(reader, n, var)?),
+        TecDataType::F64 => TecData::from(values::<f64, R>(reader, n, var)?),
+        TecDataType::I32 => TecData::from(values::<i32, R>(reader, n, var)?),
+        TecDataType::I16 => TecData::from(values::<i16, R>(reader, n, var)?),
+        TecDataType::I8 | TecDataType::I1 => {
+            TecData::from(bytes(values::<u8, R>(reader, n, var)?))
+        }
+    })
+}
+
+/// Parses `token` onto the end of `values`.
+fn push(values: &mut TecData<'static>, token: &str) -> std::result::Result<(), ParseError> {
+    fn push<T: FromStr + Clone>(
+        values: &mut Cow<'static, [T]>,
+        token: &str,
+    ) -> std::result::Result<(), ParseError> {
+        values.to_mut().push(parse_number(token, "number")?);
+        Ok(())
+    }
+    match values {
+        TecData::F32(v) => push(v, token),
+        TecData::F64(v) => push(v, token),
+        TecData::I32(v) => push(v, token),
+        TecData::I16(v) => push(v, token),
+        TecData::I8(v) => {
+            v.to_mut().push(parse_number::<u8>(token, "number")? as i8);
+            Ok(())
+        }
+        TecData::I64(v) => push(v, token),
+        TecData::U64(v) => push(v, token),
+        TecData::U32(v) => push(v, token),
+    }
+}
+
+/// Moves the values of `more` to the end of `values`, which holds the same type.
+fn append(values: &mut TecData<'static>, more: TecData<'static>) {
+    match (values, more) {
+        (TecData::F32(v), TecData::F32(more)) => v.to_mut().extend_from_slice(&more),
+        (TecData::F64(v), TecData::F64(more)) => v.to_mut().extend_from_slice(&more),
+        (TecData::I32(v), TecData::I32(more)) => v.to_mut().extend_from_slice(&more),
+        (TecData::I16(v), TecData::I16(more)) => v.to_mut().extend_from_slice(&more),
+        (TecData::I8(v), TecData::I8(more)) => v.to_mut().extend_from_slice(&more),
+        (TecData::I64(v), TecData::I64(more)) => v.to_mut().extend_from_slice(&more),
+        (TecData::U64(v), TecData::U64(more)) => v.to_mut().extend_from_slice(&more),
+        (TecData::U32(v), TecData::U32(more)) => v.to_mut().extend_from_slice(&more),
+        _ => unreachable!("chunks are parsed into columns of the same types"),
+    }
+}
+
+/// Reads `nodes` points into the empty columns of `data`, like [`super::read_points`].
+pub(super) fn read_points<R: BufRead>(
+    reader: &mut TokenReader<R>,
+    data: &mut [(usize, TecData<'static>)],
+    stored: &[usize],
+    nodes: usize,
+) -> Result<()> {
+    let n = nodes.saturating_mul(stored.len());
+    let empty = data.iter().map(|(_, values)| values.clone()).collect::<Vec<_>>();
+    let start = reader.mark();
+    let mut read = 0;
+    while read < n {
+        let section = reader.section(n - read)?;
+        let column = |index: usize| stored[(read + index) % stored.len()];
+        let chunks = section.parse(
+            || empty.clone(),
+            |columns, index, token| push(&mut columns[column(index)], token),
+            |index| Some(column(index) + 1),
+        )?;
+        if section.count == 0 {
+            return Err(reader.truncated(start, n, None));
+        }
+        for chunk in chunks {
+            for ((_, values), more) in data.iter_mut().zip(chunk) {
+                append(values, more);
+            }
+        }
+        read += section.count;
+    }
+    reader.end_block(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DatFormat;
+    use std::io::Cursor;
+
+    #[test]
+    fn same_as_serial() {
+        let mut numbers = String::from("# values\n");
+        for n in 0..200 {
+            numbers.push_str(&format!(" {:.3}E+000", n as f64 * 0.7));
+            numbers.push_str(if n % 7 == 6 { "\n" } else { ", " });
+        }
+        let cases = [
+            numbers.clone(),
+            numbers.replacen("4.200E+000", "3*4.2", 1),
+            numbers.replacen("4.200E+000", "3*4,2", 1),
+            numbers.replacen("4.200E+000", "0*4.2", 1),
+            numbers.replacen("91.000E+000", "1.0D+00", 1),
+            numbers.replacen("91.000E+000", "x", 1),
+            numbers.replace("\n", "\n# comment\n\n"),
+            format!("{} 500*1.5 7", numbers),
+        ];
+        let read = |text: &str, n: usize, parallel: bool| {
+            let mut reader = TokenReader::new(Cursor::new(text.as_bytes()));
+            let values = if parallel {
+                values::<f64, _>(&mut reader, n, Some(2))
+            } else {
+                reader.values::<f64>(n, Some(2))
+            };
+            // Reading stops at the first error, the reader is only compared after values.
+            match values {
+                Ok(values) => {
+                    let next = reader.value::<f64>("number", None);
+                    format!("{:?} {:?} {:?}", values, reader.at, next)
+                }
+                Err(e) => format!("{:?}", e),
+            }
+        };
+        for text in &cases {
+            for &n in &[1, 7, 150, 199, 200, 203, 400, 700, 701] {
+                assert_eq!(read(text, n, true), read(text, n, false), "{} values of {}", n, text);
+            }
+        }
+    }
+
+    #[test]
+    fn points() {
+        let mut text = String::from(
+            "VARIABLES = \"X\" \"Y\" \"N\"\n\
+             ZONE I=100, DATAPACKING=POINT, DT=(SINGLE DOUBLE LONGINT)\n",
+        );
+        for n in 0..100 {
+            text.push_str(&format!("{} {}E-1 {}\n", n, n, n % 9));
+        }
+        let dat = DatFormat::read(&text).unwrap();
+        let block = &dat.data_blocks[0];
+        let y = (0..100).map(|n| format!("{}E-1", n).parse().unwrap()).collect::<Vec<f64>>();
+        assert_eq!(block.get_data(0).as_f32(), (0..100).map(|n| n as f32).collect::<Vec<_>>());
+        assert_eq!(block.get_data(1).as_f64(), y);
+        let ints = block.get_data(2).to_vec::<i32>().unwrap();
+        assert_eq!(ints, (0..100).map(|n| n % 9).collect::<Vec<_>>());
+
+        // The second copy of a repeat goes into the integer column.
+        let text = text.replacen("\n43 43E-1 7\n", "\n43 2*4.5 7\n", 1);
+        match DatFormat::read(&text) {
+            Err(TecioError::Malformed { error, location, zone, var }) => {
+                assert_eq!(error, ParseError::InvalidValue("number"));
+                assert_eq!(location, Location::LineColumn { line: 46, column: 4 });
+                assert_eq!((zone, var), (Some(1), Some(3)));
+            }
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+}
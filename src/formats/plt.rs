@@ -1,98 +1,57 @@
 use libc::c_char;
 use std::{
     borrow::Cow,
-    convert::From,
+    convert::{From, TryFrom},
     ffi::{c_void, CString, OsStr},
     fs::{read, File},
     io::{BufRead, Cursor, Read},
-    mem::transmute,
     path::Path,
     ptr::null_mut,
 };
 
-use nom::{bytes::complete::{tag, take, take_while, take_while_m_n, *}, character::is_alphabetic, combinator::{cond, map_res, not, opt}, error::ErrorKind, multi::{count, fold_many0, many0, many1, many_till}, number::complete::{be_u8, le_f32, le_f64, le_i32, le_u32}, sequence::tuple, IResult, AsBytes};
+use nom::{bytes::complete::{tag, take, take_while, take_while_m_n, *}, character::is_alphabetic, combinator::{cond, map_res, not, opt}, error::ErrorKind, multi::{count, fold_many0, many1, many_till}, number::complete::{be_u8, le_f32, le_f64, le_i16, le_i32, le_u32}, sequence::tuple, IResult, AsBytes};
 
 use crate::{
-    common::{try_err, Dataset, OrderedZone, Result, TecDataType, TecZone, TecioError, ZoneType, ParseError},
-    ClassicFEZone, FaceNeighborMode, FileType, TecData, ValueLocation,
+    common::{sized_count, try_err, AuxData, CoordSys, Dataset, Geometry, GeometryShape, Location, Polyline, OrderedZone, Result, TecDataType, TecZone, TecioError, TextAnnotation, ZoneType, ParseError},
+    ClassicFEZone, FaceNeighborMode, FileType, PolyFE, PolyFaces, TecData, ValueLocation,
 };
 
 const MIN_VERSION: i32 = 110;
 
 
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct PltFormat<'a> {
     version: i32,
+    source: Source<'a>,
     pub dataset: Dataset,
     pub zones: Vec<TecZone>,
-    pub(crate) data_blocks: Vec<DataBlock<'a>>,
+    pub(crate) data_blocks: Vec<DataBlock>,
 }
 
-impl PltFormat<'static>{
-    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        use ParseError::*;
-        let data = vmap::Map::open(path)?;
-        let data = unsafe{ std::slice::from_raw_parts(data.as_ptr(), data.len()) };
-        let mut rest = data;
-
-        let (rest, t): (&[u8], &[u8]) = tag::<&str, &[u8], ParseError>("#!TDV")(rest)?;
-        let (rest, version): (&[u8], _) =
-            take::<_, _, ParseError>(3u32)(rest).map(|(r, b)| (r, std::str::from_utf8(b).map(|n| n.parse::<i32>())))?;
-        let version = match version {
-            Ok(Ok(v)) => {
-                if v > MIN_VERSION {
-                    v
-                } else {
-                    Err(VersionMismatch {
-                        min: MIN_VERSION,
-                        current: v,
-                    })?
-                }
-            }
-            Ok(Err(e)) => Err(ParseError::Utf8Error)?,
-            Err(err) => Err(ParseError::Utf8Error)?,
-        };
-        let (rest, _) = is_number(1, rest)?;
-        let (mut rest, file_type) = le_i32(rest).map(|(r, f)| (r, FileType::from(f)))?;
-        let (rest, title) = parse_utf8_null_terminated(rest)?;
-        let (rest, num_vars) = le_i32(rest)?;
-        let (rest, var_names) = count(parse_utf8_null_terminated, num_vars as usize)(rest)?;
-        let (rest, header_blocks) = many0(|input| parse_header_block(input, num_vars))(rest)?;
-        let (rest, t) = le_f32(rest)?;
-        assert_eq!(t, 357.0f32);
-
-        let mut zones = header_blocks
-            .into_iter()
-            .filter_map(|bl| match bl {
-                HeaderBlock::Zone(zone) => Some(zone),
-                _ => None,
-            })
-            .collect::<Vec<_>>();
-
-        let dataset = Dataset {
-            num_variables: num_vars as _,
-            num_zones: zones.len() as _,
-            title,
-            var_names,
-        };
-        let mut rest = rest;
-        let mut data_blocks = vec![];
-        for (i, z) in zones.iter_mut().enumerate() {
-            match z {
-                TecZone::Ordered(z) => z.id = i as i32 + 1,
-                TecZone::ClassicFE(z) => z.id = i as i32 + 1,
-                T_ => unimplemented!(),
-            }
-
-            let (r, bl) = parse_data_block(rest, num_vars, z, true)?;
+#[derive(Debug)]
+pub(crate) enum Source<'a> {
+    Map(vmap::Map),
+    Slice(&'a [u8]),
+}
 
-            rest = r;
-            data_blocks.push(bl);
+impl<'a> Source<'a> {
+    pub(crate) fn bytes(&self) -> &[u8] {
+        match self {
+            Source::Map(map) => map,
+            Source::Slice(data) => data,
         }
+    }
+}
+
+impl PltFormat<'static> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let (map, _) = vmap::Map::with_options().open(path)?;
+        let (version, dataset, zones, data_blocks) = parse(&map)?;
 
         Ok(PltFormat {
             version,
+            source: Source::Map(map),
             dataset,
             zones,
             data_blocks,
@@ -102,76 +61,220 @@ impl PltFormat<'static>{
 
 impl<'a> PltFormat<'a> {
     pub fn read(data: &'a [u8]) -> Result<Self> {
-        use ParseError::*;
-
-        let mut rest = data;
-
-        let (rest, t): (&[u8], &[u8]) = tag::<&str, &[u8], ParseError>("#!TDV")(rest)?;
-        let (rest, version): (&[u8], _) =
-            take::<_, _, ParseError>(3u32)(rest).map(|(r, b)| (r, std::str::from_utf8(b).map(|n| n.parse::<i32>())))?;
-        let version = match version {
-            Ok(Ok(v)) => {
-                if v > MIN_VERSION {
-                    v
-                } else {
-                    Err(VersionMismatch {
-                        min: MIN_VERSION,
-                        current: v,
-                    })?
+        let (version, dataset, zones, data_blocks) = parse(data)?;
+
+        Ok(PltFormat {
+            version,
+            source: Source::Slice(data),
+            dataset,
+            zones,
+            data_blocks,
+        })
+    }
+
+    /// Single and double precision values are borrowed from the file when they are suitably
+    /// aligned and copied otherwise.
+    pub fn get_data(&self, zone_id: usize, var_id: usize) -> Result<TecData<'_>> {
+        let zone = &self.zones[zone_id - 1];
+        if let Some(source) = zone.shared_from(var_id) {
+            return self.get_data(source, var_id);
+        }
+        let offset = match self.data_blocks[zone_id - 1].var_offsets[var_id - 1] {
+            Some(offset) => offset,
+            None => return Ok(TecData::F32(Cow::Owned(vec![]))),
+        };
+        let loc = zone.var_locs()[var_id - 1];
+        let ty = zone.data_types().unwrap()[var_id - 1];
+        let len = stored_len(zone, loc);
+        let input = &self.source.bytes()[offset..];
+        let data = match ty {
+            TecDataType::F64 => match borrow_values::<f64>(input, len) {
+                Some(v) => TecData::F64(Cow::Borrowed(v)),
+                None => parse_values(input, ty, len)?.1,
+            },
+            TecDataType::F32 => match borrow_values::<f32>(input, len) {
+                Some(v) => TecData::F32(Cow::Borrowed(v)),
+                None => parse_values(input, ty, len)?.1,
+            },
+            _ => parse_values(input, ty, len)?.1,
+        };
+
+        Ok(match (zone, loc) {
+            (TecZone::Ordered(z), ValueLocation::CellCentered) => {
+                strip_ghost_cells(&data, &ghost_cell_indices(z))
+            }
+            _ => data,
+        })
+    }
+
+    /// Node map of a FE zone. Node indices are one-based, as returned by TecIO.
+    pub fn get_connectivity(&self, zone_id: usize) -> Result<Option<TecData<'_>>> {
+        if let Some(source) = self.zones[zone_id - 1].connectivity_shared_from() {
+            return self.get_connectivity(source);
+        }
+        match (&self.zones[zone_id - 1], self.data_blocks[zone_id - 1].connectivity) {
+            (TecZone::ClassicFE(z), Some(offset)) => {
+                let (_, c) = count(le_i32, z.num_connections())(&self.source.bytes()[offset..])?;
+                if let Some(i) = c.iter().position(|&n| n < 0 || n as i64 >= z.nodes) {
+                    let error = ParseError::IndexOutOfRange {
+                        index: c[i] as i64 + 1,
+                        max: z.nodes,
+                    };
+                    let location = Location::Offset(offset + 4 * i);
+                    Err(TecioError::from(error).at(location, Some(zone_id), None))?
                 }
+                // Stored zero-based.
+                Ok(Some(TecData::from(c.into_iter().map(|n| n + 1).collect::<Vec<_>>())))
             }
-            Ok(Err(e)) => Err(ParseError::Utf8Error)?,
-            Err(err) => Err(ParseError::Utf8Error)?,
-        };
-        let (rest, _) = is_number(1, rest)?;
-        let (mut rest, file_type) = le_i32(rest).map(|(r, f)| (r, FileType::from(f)))?;
-        let (rest, title) = parse_utf8_null_terminated(rest)?;
-        let (rest, num_vars) = le_i32(rest)?;
-        let (rest, var_names) = count(parse_utf8_null_terminated, num_vars as usize)(rest)?;
-        let (rest, header_blocks) = many0(|input| parse_header_block(input, num_vars))(rest)?;
-        let (rest, t) = le_f32(rest)?;
-        assert_eq!(t, 357.0f32);
-
-        let mut zones = header_blocks
-            .into_iter()
-            .filter_map(|bl| match bl {
-                HeaderBlock::Zone(zone) => Some(zone),
-                _ => None,
-            })
-            .collect::<Vec<_>>();
+            _ => Ok(None),
+        }
+    }
 
-        let dataset = Dataset {
-            num_variables: num_vars as _,
-            num_zones: zones.len() as _,
-            title,
-            var_names,
-        };
-        let mut rest = rest;
-        let mut data_blocks = vec![];
-        for (i, z) in zones.iter_mut().enumerate() {
-            match z {
-                TecZone::Ordered(z) => z.id = i as i32 + 1,
-                TecZone::ClassicFE(z) => z.id = i as i32 + 1,
-                T_ => unimplemented!(),
+    /// Face connectivity of a polygonal or polyhedral zone.
+    pub fn get_poly_faces(&self, zone_id: usize) -> Result<Option<PolyFaces>> {
+        if let Some(source) = self.zones[zone_id - 1].connectivity_shared_from() {
+            return self.get_poly_faces(source);
+        }
+        match (&self.zones[zone_id - 1], self.data_blocks[zone_id - 1].connectivity) {
+            (TecZone::PolyFE(z), Some(offset)) => {
+                let (_, faces) = parse_poly_faces(&self.source.bytes()[offset..], z).map_err(|e| {
+                    TecioError::from(e).at(Location::Offset(offset), Some(zone_id), None)
+                })?;
+                Ok(Some(faces))
             }
+            _ => Ok(None),
+        }
+    }
+
+    pub fn get_var_min_max(&self, zone_id: usize, var_id: usize) -> (f64, f64) {
+        if let Some(source) = self.zones[zone_id - 1].shared_from(var_id) {
+            return self.get_var_min_max(source, var_id);
+        }
+        self.data_blocks[zone_id - 1].min_max[var_id - 1]
+    }
+}
 
-            let (r, bl) = parse_data_block(rest, num_vars, z, false)?;
+fn parse(data: &[u8]) -> Result<(i32, Dataset, Vec<TecZone>, Vec<DataBlock>)> {
+    use ParseError::*;
 
+    let rest = data;
+    let located = |input: &[u8], zone: Option<usize>| {
+        let location = Location::Offset(data.len() - input.len());
+        move |e: nom::Err<ParseError>| TecioError::from(e).at(location, zone, None)
+    };
+
+    let (rest, _) = tag::<&str, &[u8], ParseError>("#!TDV")(rest)
+        .map_err(|_| ParseError::HeaderVersionMissing)?;
+    let (rest, version): (&[u8], _) =
+        take::<_, _, ParseError>(3u32)(rest).map(|(r, b)| (r, std::str::from_utf8(b).map(|n| n.parse::<i32>())))
+            .map_err(located(rest, None))?;
+    let version = match version {
+        Ok(Ok(v)) => {
+            if v > MIN_VERSION {
+                v
+            } else {
+                Err(VersionMismatch {
+                    min: MIN_VERSION,
+                    current: v,
+                })?
+            }
+        }
+        Ok(Err(_)) | Err(_) => Err(HeaderVersionMissing)?,
+    };
+    let (rest, _) = is_number(1, rest).map_err(located(rest, None))?;
+    let (rest, _file_type) = enum_value::<FileType>(rest).map_err(located(rest, None))?;
+    let (rest, title) = parse_utf8_null_terminated(rest).map_err(located(rest, None))?;
+    let (rest, num_vars) = le_i32(rest).map_err(located(rest, None))?;
+    if num_vars < 0 {
+        Err(TecioError::from(UnexpectedValue(num_vars)).at(
+            Location::Offset(data.len() - rest.len() - 4),
+            None,
+            None,
+        ))?
+    }
+    let (mut rest, var_names) =
+        sized_count(parse_utf8_null_terminated, num_vars as usize, 4)(rest)
+            .map_err(located(rest, None))?;
+
+    let mut header_blocks = vec![];
+    let mut num_zones = 0;
+    loop {
+        let (r, marker) = le_f32(rest).map_err(located(rest, None))?;
+        if marker == 357.0 {
             rest = r;
-            data_blocks.push(bl);
+            break;
+        }
+        let zone = match marker == 299.0 {
+            true => Some(num_zones + 1),
+            false => None,
+        };
+        let (r, block) = parse_header_block(rest, num_vars).map_err(located(rest, zone))?;
+        if let HeaderBlock::AuxVar(var, ..) = block {
+            if var < 0 || var >= num_vars {
+                Err(TecioError::from(IndexOutOfRange {
+                    index: var as i64 + 1,
+                    max: num_vars as i64,
+                })
+                .at(Location::Offset(data.len() - rest.len()), None, None))?
+            }
         }
+        num_zones += zone.is_some() as usize;
+        header_blocks.push(block);
+        rest = r;
+    }
 
-        Ok(PltFormat {
-            version,
-            dataset,
-            zones,
-            data_blocks,
-        })
+    let mut zones = vec![];
+    let mut aux_data = AuxData::new();
+    let mut var_aux_data = vec![];
+    let mut geometries = vec![];
+    let mut texts = vec![];
+    let mut custom_labels = vec![];
+    let mut user_records = vec![];
+    for block in header_blocks {
+        match block {
+            HeaderBlock::Zone(zone) => zones.push(zone),
+            HeaderBlock::AuxDataset(name, value) => {
+                aux_data.insert(name, value);
+            }
+            HeaderBlock::Geom(geometry) => geometries.push(geometry),
+            HeaderBlock::Text(text) => texts.push(text),
+            HeaderBlock::CustomLabels(labels) => custom_labels.push(labels),
+            HeaderBlock::UserRec(record) => user_records.push(record),
+            HeaderBlock::AuxVar(var, name, value) => {
+                var_aux_data.resize(num_vars as usize, AuxData::new());
+                var_aux_data[var as usize].insert(name, value);
+            }
+        }
     }
 
-    pub fn get_data(&'a self, zone: usize, var: usize) -> Result<TecData<'a>>{
-        Ok(self.data_blocks[zone - 1].get_data(var - 1))
+    let dataset = Dataset {
+        num_variables: num_vars as _,
+        num_zones: zones.len() as _,
+        title,
+        var_names,
+        aux_data,
+        var_aux_data,
+        geometries,
+        texts,
+        custom_labels,
+        user_records,
+    };
+    let mut data_blocks = vec![];
+    for (i, z) in zones.iter_mut().enumerate() {
+        match z {
+            TecZone::Ordered(z) => z.id = i as i32 + 1,
+            TecZone::ClassicFE(z) => z.id = i as i32 + 1,
+            TecZone::PolyFE(z) => z.id = i as i32 + 1,
+        }
+
+        let (r, bl) = parse_data_block(rest, data.len(), num_vars, z)
+            .map_err(|e| e.at(Location::Offset(data.len() - rest.len()), Some(i + 1), None))?;
+
+        rest = r;
+        data_blocks.push(bl);
     }
+
+    Ok((version, dataset, zones, data_blocks))
 }
 
 fn is_number(num: i32, input: &[u8]) -> IResult<&[u8], (), ParseError> {
@@ -179,7 +282,7 @@ fn is_number(num: i32, input: &[u8]) -> IResult<&[u8], (), ParseError> {
         if n == num {
             Ok((r, ()))
         } else {
-            Err(nom::Err::Error(ParseError::Utf8Error))
+            Err(nom::Err::Error(ParseError::UnexpectedValue(n)))
         }
     })
 }
@@ -195,7 +298,7 @@ fn parse_utf8_null_terminated(mut input: &[u8]) -> IResult<&[u8], String, ParseE
             title.push(i as u8);
         }
     };
-    let s = String::from_utf8(title).unwrap();
+    let s = String::from_utf8(title).map_err(|_| nom::Err::Error(ParseError::Utf8Error))?;
     Ok((rest, s))
 }
 
@@ -205,21 +308,27 @@ fn parse_header_zone(input: &[u8], num_vars: i32) -> IResult<&[u8], TecZone, Par
         return Err(nom::Err::Error(ParseError::WrongHeaderTag));
     }
     let (rest, name) = parse_utf8_null_terminated(rest)?;
+    // Stored zero-based, -1 if there is no parent zone.
     let (rest, parent_zone) = le_i32(rest)?;
-    if parent_zone != -1 {
-        return Err(nom::Err::Error(ParseError::NotSupportedFeature));
-    }
+    check_sizes(&[parent_zone], -1)?;
+    let parent_zone = parent_zone
+        .checked_add(1)
+        .ok_or(nom::Err::Error(ParseError::UnexpectedValue(parent_zone)))?;
     let (rest, strand_id) = le_i32(rest)?;
     let (rest, solution_time) = le_f64(rest)?;
     let (rest, _) = tag(&i32::to_le_bytes(-1))(rest)?;
-    let (rest, zone_type) = le_i32(rest).map(|(r, z)| (r, ZoneType::from(z)))?;
+    let (rest, zone_type) = enum_value::<ZoneType>(rest)?;
     let (rest, specify_var_loc) = le_i32(rest)?;
     let (rest, var_location) = if specify_var_loc == 1 {
-        count(le_i32, num_vars as usize)(rest).map(|(r, v)| {
+        // Stored as 0 for nodal and 1 for cell centered values.
+        count(|input| enum_value::<ValueLocation>(input), num_vars as usize)(rest).map(|(r, v)| {
             (
                 r,
                 v.into_iter()
-                    .map(|v| ValueLocation::from(1 - v))
+                    .map(|v| match v {
+                        ValueLocation::Nodal => ValueLocation::CellCentered,
+                        ValueLocation::CellCentered => ValueLocation::Nodal,
+                    })
                     .collect::<Vec<_>>(),
             )
         })?
@@ -228,18 +337,10 @@ fn parse_header_zone(input: &[u8], num_vars: i32) -> IResult<&[u8], TecZone, Par
     };
     let (rest, raw_local_supplied) = le_i32(rest)?;
     let (rest, misc_face_connect) = le_i32(rest)?;
-    let (rest, neighbor_mode, is_specified) = if misc_face_connect != 0 {
-        unimplemented!();
-        let (rest, n) = le_i32(rest).map(|(r, m)| (r, FaceNeighborMode::from(m)))?;
-        let (rest, is_spec) = if zone_type.is_fe() {
-            le_i32(rest)?
-        } else {
-            (rest, 0)
-        };
-        (rest, n, is_spec)
-    } else {
-        (rest, FaceNeighborMode::LocalOneToOne, 0)
-    };
+    if raw_local_supplied != 0 || misc_face_connect != 0 {
+        // Neither raw face neighbors nor face neighbor connections are skipped in the data block.
+        return Err(nom::Err::Error(ParseError::NotSupportedFeature));
+    }
 
     match zone_type {
         ZoneType::Ordered => {
@@ -247,9 +348,9 @@ fn parse_header_zone(input: &[u8], num_vars: i32) -> IResult<&[u8], TecZone, Par
                 rest,
                 i_max: le_i32 >> j_max: le_i32 >> k_max: le_i32 >> ((i_max, j_max, k_max))
             )?;
+            check_sizes(&[i_max, j_max, k_max], 1)?;
 
-            let (rest, (aux_data, _)) =
-                many_till(auxiliary_data, |input| is_number(0, input))(rest)?;
+            let (rest, aux_data) = zone_aux_data(rest)?;
 
             Ok((
                 rest,
@@ -258,12 +359,15 @@ fn parse_header_zone(input: &[u8], num_vars: i32) -> IResult<&[u8], TecZone, Par
                     id: strand_id,
                     solution_time,
                     strand: strand_id,
+                    parent_zone,
                     i_max: i_max as i64,
                     j_max: j_max as i64,
                     k_max: k_max as i64,
                     var_location,
                     var_types: None,
+                    var_share_list: vec![],
                     passive_var_list: vec![0; num_vars as usize],
+                    aux_data,
                 }),
             ))
         }
@@ -276,13 +380,13 @@ fn parse_header_zone(input: &[u8], num_vars: i32) -> IResult<&[u8], TecZone, Par
                 rest,
                 num_ptr: le_i32 >> num_elements: le_i32 >> ((num_ptr, num_elements))
             )?;
+            check_sizes(&[nodes, cells], 0)?;
             let (rest, (i_cell_dim, j_cell_dim, k_cell_dim)) = do_parse!(
                 rest,
                 i_max: le_i32 >> j_max: le_i32 >> k_max: le_i32 >> ((i_max, j_max, k_max))
             )?;
 
-            let (rest, (aux_data, _)) =
-                many_till(auxiliary_data, |input| is_number(0, input))(rest)?;
+            let (rest, aux_data) = zone_aux_data(rest)?;
 
             Ok((
                 rest,
@@ -292,14 +396,69 @@ fn parse_header_zone(input: &[u8], num_vars: i32) -> IResult<&[u8], TecZone, Par
                     id: strand_id as _,
                     solution_time,
                     strand: strand_id as _,
+                    parent_zone,
                     nodes: nodes as _,
                     cells: cells as _,
                     var_location,
                     var_types: None,
+                    var_share_list: vec![],
+                    passive_var_list: vec![0; num_vars as usize],
+                    connectivity_share_zone: 0,
+                    aux_data,
                 }),
             ))
         }
-        _ => unimplemented!(),
+        ZoneType::FEPolygon | ZoneType::FEPolyhedron => {
+            let (rest, (nodes, faces, total_num_face_nodes, boundary_faces, boundary_connections)) = do_parse!(
+                rest,
+                nodes: le_i32
+                    >> faces: le_i32
+                    >> face_nodes: le_i32
+                    >> boundary_faces: le_i32
+                    >> boundary_connections: le_i32
+                    >> ((nodes, faces, face_nodes, boundary_faces, boundary_connections))
+            )?;
+            let (rest, cells) = le_i32(rest)?;
+            let (rest, _cell_dims) = count(le_i32, 3)(rest)?;
+            check_sizes(
+                &[nodes, faces, total_num_face_nodes, boundary_faces, boundary_connections, cells],
+                0,
+            )?;
+
+            let (rest, aux_data) = zone_aux_data(rest)?;
+
+            Ok((
+                rest,
+                TecZone::PolyFE(PolyFE {
+                    name,
+                    zone_type,
+                    id: strand_id as _,
+                    solution_time,
+                    strand: strand_id as _,
+                    parent_zone,
+                    nodes: nodes as _,
+                    cells: cells as _,
+                    faces: faces as _,
+                    total_num_face_nodes: total_num_face_nodes as _,
+                    num_connected_boundary_faces: boundary_faces as _,
+                    total_num_boundary_connections: boundary_connections as _,
+                    var_location,
+                    var_types: None,
+                    var_share_list: vec![],
+                    passive_var_list: vec![0; num_vars as usize],
+                    connectivity_share_zone: 0,
+                    aux_data,
+                }),
+            ))
+        }
+    }
+}
+
+/// Rejects zone sizes below `min`, negative counts would wrap around when used as lengths.
+fn check_sizes(sizes: &[i32], min: i32) -> std::result::Result<(), nom::Err<ParseError>> {
+    match sizes.iter().find(|&&n| n < min) {
+        Some(&n) => Err(nom::Err::Error(ParseError::UnexpectedValue(n))),
+        None => Ok(()),
     }
 }
 
@@ -308,8 +467,10 @@ pub enum HeaderBlock {
     Zone(TecZone),
     AuxDataset(String, String),
     AuxVar(i32, String, String),
-    Text,
-    Geom,
+    Text(TextAnnotation),
+    Geom(Geometry),
+    CustomLabels(Vec<String>),
+    UserRec(String),
 }
 
 fn parse_header_block(input: &[u8], num_vars: i32) -> IResult<&[u8], HeaderBlock, ParseError> {
@@ -319,7 +480,22 @@ fn parse_header_block(input: &[u8], num_vars: i32) -> IResult<&[u8], HeaderBlock
             let (rest, zone) = parse_header_zone(input, num_vars)?;
             Ok((rest, HeaderBlock::Zone(zone)))
         }
-        399.0 => unimplemented!(),
+        399.0 => {
+            let (rest, geometry) = parse_geom(input)?;
+            Ok((rest, HeaderBlock::Geom(geometry)))
+        }
+        499.0 => {
+            let (rest, text) = parse_text(input)?;
+            Ok((rest, HeaderBlock::Text(text)))
+        }
+        599.0 => {
+            let (rest, labels) = parse_custom_label(input)?;
+            Ok((rest, HeaderBlock::CustomLabels(labels)))
+        }
+        699.0 => {
+            let (rest, record) = parse_user_recs(input)?;
+            Ok((rest, HeaderBlock::UserRec(record)))
+        }
         799.0 => {
             let (rest, data) = parse_dataset_aux(input)?;
             Ok((rest, HeaderBlock::AuxDataset(data.0, data.1)))
@@ -328,38 +504,36 @@ fn parse_header_block(input: &[u8], num_vars: i32) -> IResult<&[u8], HeaderBlock
             let (rest, data) = parse_var_aux(input)?;
             Ok((rest, HeaderBlock::AuxVar(data.0, data.1, data.2)))
         }
-        357.0 => {
-            return Err(nom::Err::Error(ParseError::EndOfHeader));
-        }
-        p => panic!("Not implemented for block of type {:?}", p),
+        357.0 => Err(nom::Err::Error(ParseError::EndOfHeader)),
+        p => Err(nom::Err::Error(ParseError::UnknownMarker(p))),
     }
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct DataBlock<'a> {
-    pub(crate) data: Vec<(usize, TecData<'a>)>,
-    pub(crate) connectivity: Option<TecData<'a>>,
+pub(crate) struct DataBlock {
+    pub(crate) var_offsets: Vec<Option<usize>>,
+    pub(crate) connectivity: Option<usize>,
     pub(crate) min_max: Vec<(f64, f64)>,
 }
 
-impl<'a> DataBlock<'a> {
-    pub fn get_data(&'a self, var_id: usize) -> TecData<'a> {
-        self.data[var_id].1.get()
-    }
-}
-
+/// Parses a zone's data block, recording where every variable starts instead of decoding it.
+/// Offsets are counted from the beginning of the file, which is `file_len` bytes long.
 fn parse_data_block<'a>(
     input: &'a [u8],
+    file_len: usize,
     num_vars: i32,
     zone: &mut TecZone,
-    copy: bool,
-) -> IResult<&'a [u8], DataBlock<'a>, ParseError> {
+) -> Result<(&'a [u8], DataBlock)> {
+    let located = |input: &[u8], var: Option<usize>| {
+        let location = Location::Offset(file_len - input.len());
+        move |e: nom::Err<ParseError>| TecioError::from(e).at(location, None, var)
+    };
     let (rest, t) = le_f32(input)?;
     if t != 299.0 {
-        return Err(nom::Err::Error(ParseError::WrongDataTag));
+        Err(ParseError::WrongDataTag)?
     }
-    let (rest, data_format): (_, Vec<TecDataType>) =
-        count(le_i32, num_vars as _)(rest).map(|(r, v)| (r, unsafe { transmute(v) }))?;
+    let (rest, data_format) = count(|input| enum_value::<TecDataType>(input), num_vars as _)(rest)
+        .map_err(located(rest, None))?;
     let zone_data_types = zone.data_types_mut();
     *zone_data_types = Some(data_format);
     let (rest, has_passive) = le_i32(rest)?;
@@ -369,172 +543,437 @@ fn parse_data_block<'a>(
         (rest, vec![0; num_vars as usize])
     };
 
-    if has_passive != 0{
-        match zone{
-            TecZone::Ordered(o) => o.passive_var_list = passive_list.clone(),
-            _ => unimplemented!(),
+    if has_passive != 0 {
+        match zone {
+            TecZone::Ordered(z) => z.passive_var_list = passive_list.clone(),
+            TecZone::ClassicFE(z) => z.passive_var_list = passive_list.clone(),
+            TecZone::PolyFE(z) => z.passive_var_list = passive_list.clone(),
         }
     }
 
 
     //println!("{:?}, {:?}", passive_list, zone.zone_type());
     let (rest, has_share) = le_i32(rest)?;
+    let share_list_start = rest;
     let (rest, share_list): (_, Vec<i32>) = if has_share != 0 {
-        unimplemented!()
+        count(le_i32, num_vars as _)(rest)?
     } else {
-        (rest, vec![])
+        (rest, vec![-1; num_vars as usize])
     };
+    let zone_id = match zone {
+        TecZone::Ordered(z) => z.id,
+        TecZone::ClassicFE(z) => z.id,
+        TecZone::PolyFE(z) => z.id,
+    };
+    if let Some(var) = share_list.iter().position(|&z| z < -1 || z >= zone_id - 1) {
+        let error = nom::Err::Error(ParseError::IndexOutOfRange {
+            index: share_list[var] as i64 + 1,
+            max: zone_id as i64 - 1,
+        });
+        Err(located(share_list_start, Some(var + 1))(error))?
+    }
+    let var_share_list = share_list.iter().map(|&z| z.max(-1) + 1).collect();
+    match zone {
+        TecZone::Ordered(z) => z.var_share_list = var_share_list,
+        TecZone::ClassicFE(z) => z.var_share_list = var_share_list,
+        TecZone::PolyFE(z) => z.var_share_list = var_share_list,
+    }
+
     let (rest, share_connectivity) = le_i32(rest)?;
-    let non_shared_non_passive = num_vars - passive_list.iter().fold(0, |x, y| x + *y);
-    let (mut rest, min_max) = count(
+    let non_shared_non_passive = passive_list
+        .iter()
+        .zip(share_list.iter())
+        .filter(|(&p, &s)| p == 0 && s == -1)
+        .count();
+    let (mut rest, stored_min_max) = count(
         |input: &[u8]| do_parse!(input, min: le_f64 >> max: le_f64 >> ((min, max))),
-        non_shared_non_passive as usize,
+        non_shared_non_passive,
     )(rest)?;
 
-    let mut data = vec![];
+    let mut var_offsets = vec![];
+    let mut min_max = vec![];
+    let mut stored_min_max = stored_min_max.into_iter();
 
     for (n, (&loc, &format)) in zone
         .var_locs()
         .iter()
-        .zip(zone.data_types().unwrap().iter())
+        .zip(zone.data_types().unwrap_or(&[]).iter())
         .enumerate()
     {
-        if passive_list.get(n) == Some(&1) {
-            data.push((n, TecData::F32(Cow::Owned(vec![]))));
+        if passive_list.get(n) == Some(&1) || share_list[n] != -1 {
+            var_offsets.push(None);
+            min_max.push((0.0, 0.0));
             continue;
         }
 
-        let len = match loc {
-            ValueLocation::Nodal => zone.node_count(),
-            ValueLocation::CellCentered => match &zone {
-                TecZone::ClassicFE(z) => z.cells as _,
-                TecZone::Ordered(z) => (z.i_max * z.j_max * (z.k_max - 1)) as _,
-                _ => unimplemented!(),
-            },
-        };
-
-        let d = match format {
-            TecDataType::F64 => {
-
-                let (r, d) = count(le_f64, len)(rest)?;
-                rest = r;
-                TecData::F64(Cow::Owned(d))
-            }
-            TecDataType::F32 => {
-                if copy {
-                    let (r, d) = count(le_f32, len)(rest)?;
-                    rest = r;
-                    TecData::F32(Cow::Owned(d))
-                } else{
-                    let d = unsafe{ std::slice::from_raw_parts(rest.as_ptr() as *const f32, len) };
-                    rest = &rest[len * 4..];
-                    TecData::F32(Cow::Borrowed(d))
-                }
-
-
-            }
-            _ => unimplemented!(),
-        };
-
-        let d = match &zone {
-            TecZone::Ordered(z) => {
-                match loc {
-                    ValueLocation::CellCentered => {
-                        match d {
-                            TecData::F64(Cow::Owned(v)) => {
-                                println!("Here");
-                                let mut out = Vec::with_capacity(zone.cell_count());
-                                //TODO! Optimize!
-                                for k in 0..z.k_max - 1 {
-                                    for j in 0..z.j_max - 1 {
-                                        for i in 0..z.i_max - 1 {
-                                            let ind =
-                                                (i + j * (z.i_max) + k * (z.i_max) * (z.j_max))
-                                                    as usize;
-                                            out.push(v[ind]);
-                                        }
-                                    }
-                                }
-                                TecData::F64(Cow::Owned(out))
-                            }
-                            _ => unimplemented!(),
-                        }
-                    }
-                    _ => d,
-                }
-            }
-            _ => d,
-        };
-
-        data.push((n, d));
+        let len = stored_len(zone, loc);
+        var_offsets.push(Some(file_len - rest.len()));
+        min_max.push(stored_min_max.next().unwrap_or((0.0, 0.0)));
+        let (r, _) = take(data_type_size(format, len))(rest).map_err(located(rest, Some(n + 1)))?;
+        rest = r;
     }
 
+    let invalid_share = located(rest, None)(nom::Err::Error(ParseError::IndexOutOfRange {
+        index: share_connectivity as i64 + 1,
+        max: zone_id as i64 - 1,
+    }));
     let connectivity = match zone {
         TecZone::Ordered(_) => None,
         TecZone::ClassicFE(z) => {
             if share_connectivity == -1 {
-                let len = match z.zone_type {
-                    ZoneType::FELine => 2,
-                    ZoneType::FETriangle => 3,
-                    ZoneType::FEQuad => 4,
-                    ZoneType::FETetra => 4,
-                    ZoneType::FEBrick => 8,
-                    _ => unreachable!(),
-                };
-                let (r, c) = count(le_i32, len as _)(rest)?;
+                let offset = file_len - rest.len();
+                let size = z.num_connections().saturating_mul(4);
+                let (r, _) = take(size)(rest).map_err(located(rest, None))?;
                 rest = r;
-                Some(TecData::I32(Cow::Owned(c)))
+                Some(offset)
+            } else if share_connectivity >= 0 && share_connectivity < zone_id - 1 {
+                z.connectivity_share_zone = share_connectivity + 1;
+                None
             } else {
-                unimplemented!()
+                Err(invalid_share)?
+            }
+        }
+        TecZone::PolyFE(z) => {
+            if share_connectivity == -1 {
+                let offset = file_len - rest.len();
+                let (r, _) = take(poly_faces_size(z))(rest).map_err(located(rest, None))?;
+                rest = r;
+                Some(offset)
+            } else if share_connectivity >= 0 && share_connectivity < zone_id - 1 {
+                z.connectivity_share_zone = share_connectivity + 1;
+                None
+            } else {
+                Err(invalid_share)?
             }
         }
-        _ => unimplemented!(),
     };
 
     Ok((
         rest,
         DataBlock {
-            data,
+            var_offsets,
             connectivity,
             min_max,
         },
     ))
 }
 
-fn parse_geom(input: &[u8]) -> IResult<&[u8], (), ParseError> {
+/// Number of values stored for a variable, ghost cells of ordered zones included.
+fn stored_len(zone: &TecZone, loc: ValueLocation) -> usize {
+    match loc {
+        ValueLocation::Nodal => zone.node_count(),
+        ValueLocation::CellCentered => match zone {
+            TecZone::ClassicFE(z) => z.cells as _,
+            TecZone::PolyFE(z) => z.cells as _,
+            TecZone::Ordered(z) => ghost_cell_dims(z).iter().fold(1, |n, &d| n.saturating_mul(d)),
+        },
+    }
+}
+
+/// Size of the face data of a polygonal or polyhedral zone.
+fn poly_faces_size(zone: &PolyFE) -> usize {
+    let faces = zone.faces as usize;
+    let node_offsets = match zone.zone_type {
+        ZoneType::FEPolyhedron => faces + 1,
+        _ => 0,
+    };
+    let boundary = match zone.num_connected_boundary_faces as usize {
+        0 => 0,
+        n => n + 1 + 2 * zone.total_num_boundary_connections as usize,
+    };
+    4 * (node_offsets + zone.total_num_face_nodes as usize + 2 * faces + boundary)
+}
+
+/// Face data is stored zero-based with node counts and boundary connection counts as offsets.
+fn parse_poly_faces<'a>(input: &'a [u8], zone: &PolyFE) -> IResult<&'a [u8], PolyFaces, ParseError> {
+    let faces = zone.faces as usize;
+    // Offsets have to be increasing, anything else would give negative or overflowing counts.
+    let counts = |offsets: Vec<i32>| {
+        offsets
+            .windows(2)
+            .map(|w| match w[1].checked_sub(w[0]) {
+                Some(n) if n >= 0 => Ok(n),
+                _ => Err(nom::Err::Error(ParseError::UnexpectedValue(w[1]))),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()
+    };
+    let one_based = |values: Vec<i32>| values.into_iter().map(|v| v as i64 + 1).collect::<Vec<_>>();
+
+    let (rest, face_node_counts) = match zone.zone_type {
+        ZoneType::FEPolyhedron => {
+            let (r, offsets) = count(le_i32, faces + 1)(input)?;
+            (r, counts(offsets)?)
+        }
+        _ => (input, vec![2; faces]),
+    };
+    let (rest, face_nodes) = count(le_i32, zone.total_num_face_nodes as usize)(rest)?;
+    let (rest, face_left_elements) = count(le_i32, faces)(rest)?;
+    let (mut rest, face_right_elements) = count(le_i32, faces)(rest)?;
+
+    let mut faces = PolyFaces {
+        face_node_counts,
+        face_nodes: one_based(face_nodes),
+        face_left_elements: one_based(face_left_elements),
+        face_right_elements: one_based(face_right_elements),
+        ..PolyFaces::default()
+    };
+    if zone.num_connected_boundary_faces > 0 {
+        let connections = zone.total_num_boundary_connections as usize;
+        let (r, offsets) = count(le_i32, zone.num_connected_boundary_faces as usize + 1)(rest)?;
+        let (r, elements) = count(le_i32, connections)(r)?;
+        let (r, zones) = count(le_i32, connections)(r)?;
+        rest = r;
+        faces.boundary_connection_counts = counts(offsets)?;
+        faces.boundary_connection_elements = one_based(elements);
+        faces.boundary_connection_zones = zones
+            .into_iter()
+            .map(|z| z.checked_add(1).ok_or(nom::Err::Error(ParseError::UnexpectedValue(z))))
+            .collect::<std::result::Result<_, _>>()?;
+    }
+    Ok((rest, faces))
+}
+
+fn data_type_size(ty: TecDataType, len: usize) -> usize {
+    match ty {
+        TecDataType::F64 => len.saturating_mul(8),
+        TecDataType::F32 | TecDataType::I32 => len.saturating_mul(4),
+        TecDataType::I16 => len.saturating_mul(2),
+        TecDataType::I8 => len,
+        TecDataType::I1 => len.div_ceil(8),
+    }
+}
+
+/// Reinterprets the stored little endian values in place, `None` if `input` is too short, not
+/// aligned for `T` or the target is big endian.
+fn borrow_values<T: Copy>(input: &[u8], len: usize) -> Option<&[T]> {
+    let size = std::mem::size_of::<T>().saturating_mul(len);
+    if cfg!(target_endian = "big")
+        || input.len() < size
+        || input.as_ptr().align_offset(std::mem::align_of::<T>()) != 0
+    {
+        return None;
+    }
+    // Only used for f32 and f64, for which every bit pattern is a valid value.
+    Some(unsafe { std::slice::from_raw_parts(input.as_ptr() as *const T, len) })
+}
+
+fn parse_values(input: &[u8], ty: TecDataType, len: usize) -> IResult<&[u8], TecData<'static>, ParseError> {
+    match ty {
+        TecDataType::F64 => count(le_f64, len)(input).map(|(r, v)| (r, TecData::from(v))),
+        TecDataType::F32 => count(le_f32, len)(input).map(|(r, v)| (r, TecData::from(v))),
+        TecDataType::I32 => count(le_i32, len)(input).map(|(r, v)| (r, TecData::from(v))),
+        TecDataType::I16 => count(le_i16, len)(input).map(|(r, v)| (r, TecData::from(v))),
+        TecDataType::I8 => take(len)(input).map(|(r, v): (_, &[u8])| {
+            (r, TecData::from(v.iter().map(|&b| b as i8).collect::<Vec<_>>()))
+        }),
+        // Packed eight to a byte, starting with the lowest bit.
+        TecDataType::I1 => take(len.div_ceil(8))(input).map(|(r, v): (_, &[u8])| {
+            let bits = (0..len).map(|i| (v[i / 8] >> (i % 8) & 1) as i8).collect::<Vec<_>>();
+            (r, TecData::from(bits))
+        }),
+    }
+}
+
+/// Cell centered values of ordered zones are stored with a ghost layer in every dimension but
+/// the slowest varying one.
+pub(crate) fn ghost_cell_dims(zone: &OrderedZone) -> [usize; 3] {
+    let mut dims = [zone.i_max as usize, zone.j_max as usize, zone.k_max as usize];
+    if let Some(last) = dims.iter_mut().rev().find(|d| **d > 1) {
+        *last -= 1;
+    }
+    dims
+}
+
+/// Positions of the zone's cells in the stored (ghost padded) array.
+pub(crate) fn ghost_cell_indices(zone: &OrderedZone) -> Vec<usize> {
+    let [gi, gj, _] = ghost_cell_dims(zone);
+    let cells = |max: i64| (max - 1).max(1) as usize;
+    let mut indices =
+        Vec::with_capacity(cells(zone.i_max) * cells(zone.j_max) * cells(zone.k_max));
+    for k in 0..cells(zone.k_max) {
+        for j in 0..cells(zone.j_max) {
+            indices.extend((0..cells(zone.i_max)).map(|i| i + j * gi + k * gi * gj));
+        }
+    }
+    indices
+}
+
+fn strip_ghost_cells(data: &TecData, indices: &[usize]) -> TecData<'static> {
+    macro_rules! strip {
+        ($v: expr) => {
+            TecData::from(indices.iter().map(|&i| $v[i]).collect::<Vec<_>>())
+        };
+    }
+    match data {
+        TecData::F64(v) => strip!(v),
+        TecData::F32(v) => strip!(v),
+        TecData::I64(v) => strip!(v),
+        TecData::I32(v) => strip!(v),
+        TecData::I16(v) => strip!(v),
+        TecData::I8(v) => strip!(v),
+        TecData::U64(v) => strip!(v),
+        TecData::U32(v) => strip!(v),
+    }
+}
+
+fn parse_geom(input: &[u8]) -> IResult<&[u8], Geometry, ParseError> {
     let (rest, t) = le_f32(input)?;
     if t != 399.0 {
         return Err(nom::Err::Error(ParseError::WrongHeaderTag));
     }
+    let (rest, coord_sys) = enum_value(rest)?;
+    let (rest, scope) = enum_value(rest)?;
+    let (rest, draw_order) = enum_value(rest)?;
+    let (rest, anchor) = count(le_f64, 3)(rest)?;
+    let (rest, zone) = le_i32(rest)?;
+    let (rest, color) = le_i32(rest)?;
+    let (rest, fill_color) = le_i32(rest)?;
+    let (rest, is_filled) = le_i32(rest)?;
+    let (rest, geom_type) = le_i32(rest)?;
+    let (rest, line_pattern) = enum_value(rest)?;
+    let (rest, pattern_length) = le_f64(rest)?;
+    let (rest, line_thickness) = le_f64(rest)?;
+    let (rest, num_ellipse_points) = le_i32(rest)?;
+    let (rest, arrowhead_style) = enum_value(rest)?;
+    let (rest, arrowhead_attachment) = enum_value(rest)?;
+    let (rest, arrowhead_size) = le_f64(rest)?;
+    let (rest, arrowhead_angle) = le_f64(rest)?;
+    let (rest, macro_function) = parse_utf8_null_terminated(rest)?;
+    let (rest, data_type) = le_i32(rest)?;
+    let (rest, clipping) = enum_value(rest)?;
 
-    unimplemented!()
+    let values = |input, n| geom_values(input, data_type, n);
+    let (rest, shape) = match geom_type {
+        0 => {
+            let (mut rest, num_lines) = le_i32(rest)?;
+            let mut lines = vec![];
+            for _ in 0..num_lines {
+                let (r, n) = le_i32(rest)?;
+                let (r, x) = values(r, n as usize)?;
+                let (r, y) = values(r, n as usize)?;
+                let (r, z) = match coord_sys {
+                    CoordSys::Grid3D => values(r, n as usize)?,
+                    _ => (r, vec![]),
+                };
+                rest = r;
+                lines.push(Polyline { x, y, z });
+            }
+            (rest, GeometryShape::Lines(lines))
+        }
+        1 => values(rest, 2).map(|(r, v)| {
+            (r, GeometryShape::Rectangle { width: v[0], height: v[1] })
+        })?,
+        2 => values(rest, 1).map(|(r, v)| (r, GeometryShape::Square { size: v[0] }))?,
+        3 => values(rest, 1).map(|(r, v)| (r, GeometryShape::Circle { radius: v[0] }))?,
+        4 => values(rest, 2).map(|(r, v)| {
+            (r, GeometryShape::Ellipse { horizontal_axis: v[0], vertical_axis: v[1] })
+        })?,
+        t => return Err(nom::Err::Error(ParseError::UnexpectedValue(t))),
+    };
+
+    Ok((
+        rest,
+        Geometry {
+            shape,
+            anchor: [anchor[0], anchor[1], anchor[2]],
+            coord_sys,
+            scope,
+            draw_order,
+            zone: Some(zone).filter(|&z| z > 0),
+            color,
+            fill_color: Some(fill_color).filter(|_| is_filled != 0),
+            line_pattern,
+            pattern_length,
+            line_thickness,
+            num_ellipse_points,
+            arrowhead_style,
+            arrowhead_attachment,
+            arrowhead_size,
+            arrowhead_angle,
+            macro_function,
+            clipping,
+        },
+    ))
+}
+
+/// Geometry coordinates stored as single (1) or double (2) precision.
+fn geom_values(input: &[u8], data_type: i32, n: usize) -> IResult<&[u8], Vec<f64>, ParseError> {
+    match data_type {
+        1 => sized_count(le_f32, n, 4)(input)
+            .map(|(r, v)| (r, v.into_iter().map(f64::from).collect())),
+        2 => sized_count(le_f64, n, 8)(input),
+        t => Err(nom::Err::Error(ParseError::UnexpectedValue(t))),
+    }
 }
 
-fn parse_text(input: &[u8]) -> IResult<&[u8], (), ParseError> {
+fn enum_value<T: TryFrom<i32, Error = ParseError>>(input: &[u8]) -> IResult<&[u8], T, ParseError> {
+    let (rest, value) = le_i32(input)?;
+    T::try_from(value)
+        .map(|v| (rest, v))
+        .map_err(nom::Err::Error)
+}
+
+fn parse_text(input: &[u8]) -> IResult<&[u8], TextAnnotation, ParseError> {
     let (rest, t) = le_f32(input)?;
     if t != 499.0 {
         return Err(nom::Err::Error(ParseError::WrongHeaderTag));
     }
+    let (rest, coord_sys) = enum_value(rest)?;
+    let (rest, scope) = enum_value(rest)?;
+    let (rest, anchor) = count(le_f64, 3)(rest)?;
+    let (rest, font) = le_i32(rest)?;
+    let (rest, size_units) = enum_value(rest)?;
+    let (rest, height) = le_f64(rest)?;
+    let (rest, box_type) = enum_value(rest)?;
+    let (rest, box_margin) = le_f64(rest)?;
+    let (rest, box_line_thickness) = le_f64(rest)?;
+    let (rest, box_color) = le_i32(rest)?;
+    let (rest, box_fill_color) = le_i32(rest)?;
+    let (rest, angle) = le_f64(rest)?;
+    let (rest, line_spacing) = le_f64(rest)?;
+    let (rest, alignment) = enum_value(rest)?;
+    let (rest, zone) = le_i32(rest)?;
+    let (rest, color) = le_i32(rest)?;
+    let (rest, macro_function) = parse_utf8_null_terminated(rest)?;
+    let (rest, clipping) = enum_value(rest)?;
+    let (rest, string) = parse_utf8_null_terminated(rest)?;
 
-    unimplemented!()
+    let mut text = TextAnnotation::new(string, [anchor[0], anchor[1], anchor[2]]);
+    text.set_font_id(font).map_err(nom::Err::Error)?;
+    text.coord_sys = coord_sys;
+    text.scope = scope;
+    text.size_units = size_units;
+    text.height = height;
+    text.box_type = box_type;
+    text.box_margin = box_margin;
+    text.box_line_thickness = box_line_thickness;
+    text.box_color = box_color;
+    text.box_fill_color = box_fill_color;
+    text.angle = angle;
+    text.line_spacing = line_spacing;
+    text.alignment = alignment;
+    text.zone = Some(zone).filter(|&z| z > 0);
+    text.color = color;
+    text.macro_function = macro_function;
+    text.clipping = clipping;
+    Ok((rest, text))
 }
 
-fn parse_custom_label(input: &[u8]) -> IResult<&[u8], (), ParseError> {
+fn parse_custom_label(input: &[u8]) -> IResult<&[u8], Vec<String>, ParseError> {
     let (rest, t) = le_f32(input)?;
     if t != 599.0 {
         return Err(nom::Err::Error(ParseError::WrongHeaderTag));
     }
-
-    unimplemented!()
+    let (rest, num_labels) = le_i32(rest)?;
+    sized_count(parse_utf8_null_terminated, num_labels.max(0) as usize, 4)(rest)
 }
 
-fn parse_user_recs(input: &[u8]) -> IResult<&[u8], (), ParseError> {
+fn parse_user_recs(input: &[u8]) -> IResult<&[u8], String, ParseError> {
     let (rest, t) = le_f32(input)?;
     if t != 699.0 {
         return Err(nom::Err::Error(ParseError::WrongHeaderTag));
     }
-
-    unimplemented!()
+    parse_utf8_null_terminated(rest)
 }
 
 fn parse_dataset_aux(input: &[u8]) -> IResult<&[u8], (String, String), ParseError> {
@@ -562,6 +1001,16 @@ fn parse_var_aux(input: &[u8]) -> IResult<&[u8], (i32, String, String), ParseErr
     Ok((rest, data))
 }
 
+/// Zone auxiliary data, every name/value pair is preceded by 1 and the list is terminated by 0.
+fn zone_aux_data(input: &[u8]) -> IResult<&[u8], AuxData, ParseError> {
+    let pair = |input| {
+        let (rest, _) = is_number(1, input)?;
+        auxiliary_data(rest)
+    };
+    let (rest, (pairs, _)) = many_till(pair, |input| is_number(0, input))(input)?;
+    Ok((rest, pairs.into_iter().collect()))
+}
+
 fn auxiliary_data(input: &[u8]) -> IResult<&[u8], (String, String), ParseError> {
     let (rest, (name, format, value)) = do_parse!(
         input,
@@ -579,16 +1028,259 @@ mod tests {
 
     #[test]
     fn simple_test() {
-        let f = PltFormat::open(r".\tests\heated_fin.plt");
+        let f = PltFormat::open("./tests/heated_fin.plt");
 
         if let Ok(format) = f {
             println!("{:?}", format.zones);
-            println!("Min max: {:?}", format.data_blocks[2].min_max);
-            let xi = &format.data_blocks[2].data[3].1;
+            println!("Min max: {:?}", format.get_var_min_max(3, 1));
+            let xi = format.get_data(3, 4).unwrap();
             println!("xi: {:?}", xi);
         } else {
             println!("{:?}", f);
             assert!(false);
         }
     }
+
+    #[test]
+    fn read_matches_open() {
+        let bytes = std::fs::read("./tests/heat.plt").unwrap();
+        let read = PltFormat::read(&bytes).unwrap();
+        let open = PltFormat::open("./tests/heat.plt").unwrap();
+        assert_eq!(read.zones.len(), 18);
+        for zone in 1..=read.zones.len() {
+            for var in 1..=read.dataset.num_variables as usize {
+                assert_eq!(
+                    read.get_data(zone, var).unwrap(),
+                    open.get_data(zone, var).unwrap()
+                );
+                assert_eq!(
+                    read.get_var_min_max(zone, var),
+                    open.get_var_min_max(zone, var)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn borrowed_when_aligned() {
+        use crate::TecData;
+        use std::borrow::Cow;
+
+        let open = PltFormat::open("./tests/heat.plt").unwrap();
+        let borrowed = open.get_data(1, 1).unwrap();
+        assert!(matches!(borrowed, TecData::F32(Cow::Borrowed(_))));
+
+        let bytes = std::fs::read("./tests/heat.plt").unwrap();
+        let mut shifted = vec![0u8; bytes.len() + 4];
+        let start = (1..4)
+            .find(|i| shifted[*i..].as_ptr().align_offset(4) != 0)
+            .unwrap();
+        shifted[start..start + bytes.len()].copy_from_slice(&bytes);
+        let read = PltFormat::read(&shifted[start..start + bytes.len()]).unwrap();
+        let copied = read.get_data(1, 1).unwrap();
+        assert!(matches!(copied, TecData::F32(Cow::Owned(_))));
+        assert_eq!(borrowed, copied);
+    }
+
+    #[test]
+    fn fe_zones() {
+        use crate::ZoneType;
+
+        let plt = PltFormat::open("./tests/fe_zones.plt").unwrap();
+        let types = plt.zones.iter().map(|z| z.zone_type()).collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![ZoneType::FETriangle, ZoneType::FEQuad, ZoneType::FETetra, ZoneType::FEBrick]
+        );
+        let maps = (1..=4)
+            .map(|z| plt.get_connectivity(z).unwrap().unwrap().to_vec::<i32>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(maps[0], vec![1, 2, 3, 1, 3, 4]);
+        assert_eq!(maps[1], vec![1, 2, 5, 4, 2, 3, 6, 5]);
+        assert_eq!(maps[2], vec![1, 2, 3, 4]);
+        assert_eq!(maps[3], vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(plt.get_data(2, 1).unwrap().as_f32(), vec![0.0, 1.0, 2.0, 0.0, 1.0, 2.0]);
+        assert_eq!(plt.get_data(2, 3).unwrap().as_f64(), vec![3.0, 4.0]);
+        assert_eq!(plt.get_data(4, 3).unwrap().as_f64(), vec![6.0]);
+    }
+
+    #[test]
+    fn polygon_zone() {
+        use crate::{PolyFaces, TecZone, ZoneType};
+
+        let mut bytes = b"#!TDV112".to_vec();
+        let i32s = |bytes: &mut Vec<u8>, values: &[i32]| {
+            values.iter().for_each(|v| bytes.extend(&v.to_le_bytes()))
+        };
+        i32s(&mut bytes, &[1, 0, 'P' as i32, 0, 2, 'X' as i32, 0, 'Y' as i32, 0]);
+        bytes.extend(&299.0f32.to_le_bytes());
+        i32s(&mut bytes, &['p' as i32, 0, -1, 0]);
+        let parent_zone = bytes.len() - 8;
+        bytes.extend(&0.0f64.to_le_bytes());
+        // zone color, type, var location, face neighbors
+        i32s(&mut bytes, &[-1, 6, 0, 0, 0]);
+        // nodes, faces, face nodes, boundary faces and connections, cells, cell dims, aux data
+        i32s(&mut bytes, &[4, 4, 8, 0, 0, 1, 0, 0, 0, 0]);
+        bytes.extend(&357.0f32.to_le_bytes());
+        bytes.extend(&299.0f32.to_le_bytes());
+        // var types, passive, shared vars and connectivity
+        i32s(&mut bytes, &[1, 1, 0, 0, -1]);
+        let share_connectivity = bytes.len() - 4;
+        for &(min, max) in &[(0.0f64, 1.0f64), (0.0, 1.0)] {
+            bytes.extend(&min.to_le_bytes());
+            bytes.extend(&max.to_le_bytes());
+        }
+        for &v in &[0.0f32, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0] {
+            bytes.extend(&v.to_le_bytes());
+        }
+        i32s(&mut bytes, &[0, 1, 1, 2, 2, 3, 3, 0]);
+        i32s(&mut bytes, &[0, 0, 0, 0, -1, -1, -1, -1]);
+
+        let plt = PltFormat::read(&bytes).unwrap();
+        match &plt.zones[0] {
+            TecZone::PolyFE(z) => {
+                assert_eq!(z.zone_type, ZoneType::FEPolygon);
+                assert_eq!((z.nodes, z.faces, z.cells), (4, 4, 1));
+            }
+            z => panic!("Expected polygonal zone, got {:?}", z),
+        }
+        assert_eq!(plt.get_data(1, 2).unwrap().as_f32(), vec![0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(
+            plt.get_poly_faces(1).unwrap().unwrap(),
+            PolyFaces {
+                face_node_counts: vec![2; 4],
+                face_nodes: vec![1, 2, 2, 3, 3, 4, 4, 1],
+                face_left_elements: vec![1; 4],
+                face_right_elements: vec![0; 4],
+                ..PolyFaces::default()
+            }
+        );
+
+        // Indices one past the largest representable value.
+        for &at in &[parent_zone, share_connectivity] {
+            let mut overflowing = bytes.clone();
+            overflowing[at..at + 4].copy_from_slice(&i32::MAX.to_le_bytes());
+            assert!(PltFormat::read(&overflowing).is_err());
+        }
+    }
+
+    #[test]
+    fn out_of_range_connectivity() {
+        use crate::{Location, ParseError, TecioError};
+
+        let mut bytes = std::fs::read("./tests/fe_zones.plt").unwrap();
+        let offset = PltFormat::read(&bytes).unwrap().data_blocks[1].connectivity.unwrap();
+        bytes[offset + 4..offset + 8].copy_from_slice(&i32::MAX.to_le_bytes());
+        match PltFormat::read(&bytes).unwrap().get_connectivity(2) {
+            Err(TecioError::Malformed {
+                error: ParseError::IndexOutOfRange { index, max: 6 },
+                location: Location::Offset(at),
+                zone: Some(2),
+                var: None,
+            }) => assert_eq!((index, at), (i32::MAX as i64 + 1, offset + 4)),
+            r => panic!("Expected out of range node, got {:?}", r.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn malformed_input() {
+        use crate::{Location, ParseError, TecioError};
+
+        let bytes = std::fs::read("./tests/heat.plt").unwrap();
+        for len in (0..bytes.len()).step_by(61) {
+            assert!(PltFormat::read(&bytes[..len]).is_err());
+        }
+
+        let find = |pattern: f32, from: usize| {
+            from + bytes[from..]
+                .windows(4)
+                .position(|w| w == pattern.to_le_bytes())
+                .unwrap()
+        };
+        let zone_marker = find(299.0, 0);
+        let mut unknown_marker = bytes.clone();
+        unknown_marker[zone_marker..zone_marker + 4].copy_from_slice(&999.0f32.to_le_bytes());
+        match PltFormat::read(&unknown_marker) {
+            Err(TecioError::Malformed {
+                error: ParseError::UnknownMarker(marker),
+                location: Location::Offset(offset),
+                zone: None,
+                var: None,
+            }) => assert_eq!((marker, offset), (999.0, zone_marker)),
+            r => panic!("Expected unknown marker, got {:?}", r.map(|_| ())),
+        }
+
+        let data_block = find(299.0, find(357.0, 0));
+        let mut bad_type = bytes.clone();
+        bad_type[data_block + 8..data_block + 12].copy_from_slice(&9i32.to_le_bytes());
+        match PltFormat::read(&bad_type) {
+            Err(e @ TecioError::Malformed { .. }) => {
+                assert_eq!(e.parse_error(), Some(ParseError::UnexpectedValue(9)));
+                assert_eq!(
+                    e.to_string(),
+                    format!("Unexpected value 9 at byte {} (zone 1)", data_block + 4)
+                );
+            }
+            r => panic!("Expected unexpected data type, got {:?}", r.map(|_| ())),
+        }
+
+        // Skips name, parent zone, strand, solution time, color and zone type of the first zone.
+        let word = |at: usize| {
+            i32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+        let name_end = (zone_marker + 4..).step_by(4).find(|&at| word(at) == 0).unwrap();
+        let var_loc = name_end + 28;
+        let num_vars = PltFormat::read(&bytes).unwrap().dataset.num_variables as usize;
+        let raw_local = var_loc + 4 + if word(var_loc) == 1 { 4 * num_vars } else { 0 };
+        assert_eq!((word(raw_local), word(raw_local + 4)), (0, 0));
+        let mut raw_local_supplied = bytes.clone();
+        raw_local_supplied[raw_local..raw_local + 4].copy_from_slice(&1i32.to_le_bytes());
+        match PltFormat::read(&raw_local_supplied) {
+            Err(e) => assert_eq!(e.parse_error(), Some(ParseError::NotSupportedFeature)),
+            r => panic!("Expected unsupported raw face neighbors, got {:?}", r.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn oversized_counts() {
+        use crate::{ParseError, TecioError};
+
+        let mut bytes = b"#!TDV112".to_vec();
+        for v in &[1, 0, 'P' as i32, 0, i32::MAX] {
+            bytes.extend(&v.to_le_bytes());
+        }
+        match PltFormat::read(&bytes) {
+            Err(e @ TecioError::Malformed { .. }) => assert_eq!(
+                e.parse_error(),
+                Some(ParseError::TooLarge { count: i32::MAX as usize, available: 0 })
+            ),
+            r => panic!("Expected too many variables, got {:?}", r.map(|_| ())),
+        }
+
+        // Overwrites words of a valid file with values likely to end up as sizes.
+        let original = std::fs::read("./tests/heat.plt").unwrap();
+        let mut seed = 0x2545_f491u32;
+        let mut random = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as usize
+        };
+        for _ in 0..500 {
+            let mut bytes = original.clone();
+            for _ in 0..4 {
+                let at = random() % (bytes.len() / 4) * 4;
+                let value = [-1, 0, 1, i32::MAX, i32::MIN, random() as i32][random() % 6];
+                bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+            }
+            if let Ok(plt) = PltFormat::read(&bytes) {
+                for zone in 1..=plt.zones.len() {
+                    for var in 1..=plt.dataset.var_names.len() {
+                        let _ = plt.get_data(zone, var);
+                    }
+                    let _ = plt.get_connectivity(zone);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,798 @@
+use std::{borrow::Cow, collections::HashMap, convert::TryFrom, path::Path};
+
+use nom::{
+    bytes::complete::{tag, take, take_until},
+    error::ErrorKind,
+    number::complete::{le_f32, le_f64, le_i16, le_i32, le_i64, le_u16, le_u32, le_u64, le_u8},
+    IResult,
+};
+
+use super::plt::Source;
+use crate::{
+    common::{
+        sized_count, AuxData, Dataset, Location, OrderedZone, ParseError, Result, TecDataType,
+        TecZone, TecioError, ZoneType,
+    },
+    ClassicFEZone, TecData, ValueLocation,
+};
+
+const MAGIC: &str = "#!SZPLT ";
+const BYTE_ORDER_MARK: u64 = 0x0102_0304_0506_0708;
+const NO_OFFSET: u64 = u64::MAX;
+
+const TAG_NUM_ZONES: u16 = 0x0001;
+const TAG_NUM_VARS: u16 = 0x0002;
+const TAG_SUBZONE_I: u16 = 0x0003;
+const TAG_SUBZONE_J: u16 = 0x0004;
+const TAG_SUBZONE_K: u16 = 0x0005;
+const TAG_TITLE: u16 = 0x000c;
+const TAG_ZONE_TITLES: u16 = 0x0100;
+const TAG_ZONE_TYPES: u16 = 0x0101;
+const TAG_ZONE_HEADERS: u16 = 0x0102;
+const TAG_SOLUTION_TIMES: u16 = 0x0104;
+const TAG_STRAND_IDS: u16 = 0x0105;
+const TAG_I_MAX: u16 = 0x0107;
+const TAG_J_MAX: u16 = 0x0108;
+const TAG_K_MAX: u16 = 0x0109;
+const TAG_VAR_NAMES: u16 = 0x0200;
+const TAG_MIN_MAX: u16 = 0x0300;
+const TAG_VAR_LOCATIONS: u16 = 0x0301;
+const TAG_VAR_TYPES: u16 = 0x0302;
+const TAG_PASSIVE_VARS: u16 = 0x0303;
+const TAG_SHARED_VARS: u16 = 0x0304;
+
+const TAG_NUM_CELL_SUBZONES: u16 = 0x0400;
+const TAG_NUM_NODE_SUBZONES: u16 = 0x0401;
+const TAG_VAR_OFFSETS: u16 = 0x0600;
+const TAG_NODE_MAP: u16 = 0x0601;
+
+/// Records of the file and zone headers this reader knows, either decoded or without effect on
+/// the data. Files with any other record, e.g. auxiliary data, geometries, texts, custom labels
+/// or user records, are only readable through TecIO.
+const HEADER_TAGS: &[u16] = &[
+    0x0001, 0x0002, 0x0003, 0x0004, 0x0005, 0x0006, 0x0007, 0x0008, 0x0009, 0x000a, 0x000c,
+    0x0100, 0x0101, 0x0102, 0x0103, 0x0104, 0x0105, 0x0106, 0x0107, 0x0108, 0x0109, 0x010c,
+    0x0200, 0x0300, 0x0301, 0x0302, 0x0303, 0x0304,
+];
+const ZONE_HEADER_TAGS: &[u16] = &[0x0400, 0x0401, 0x0600, 0x0601, 0x0602];
+
+/// Native reader of subzone loaded (`.szplt`) files.
+///
+/// Unlike [`SzpltFormat`](crate::SzpltFormat) it does not call into the tecio library, the
+/// file is memory mapped and every variable is decoded on request. FE zones partitioned into
+/// subzones, polygonal and polyhedral zones fail to open with
+/// [`ParseError::NotSupportedFeature`], they are only readable through
+/// [`SzpltFormat`](crate::SzpltFormat). So do files with records it doesn't decode, like
+/// auxiliary data, geometries, texts, custom labels or user records.
+#[derive(Debug)]
+pub struct SzlFormat<'a> {
+    source: Source<'a>,
+    pub dataset: Dataset,
+    pub zones: Vec<TecZone>,
+    pub(crate) data_blocks: Vec<DataBlock>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DataBlock {
+    pub(crate) var_offsets: Vec<u64>,
+    pub(crate) shared_from: Vec<Option<usize>>,
+    pub(crate) node_map: Option<u64>,
+    pub(crate) subzones: Option<Subzones>,
+    pub(crate) min_max: Vec<(f64, f64)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Subzones {
+    cells: u64,
+    nodes: u64,
+    size: [usize; 3],
+}
+
+impl SzlFormat<'static> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let (map, _) = vmap::Map::with_options().open(path)?;
+        let (dataset, zones, data_blocks) = parse(&map)?;
+
+        Ok(Self {
+            source: Source::Map(map),
+            dataset,
+            zones,
+            data_blocks,
+        })
+    }
+}
+
+impl<'a> SzlFormat<'a> {
+    pub fn read(data: &'a [u8]) -> Result<Self> {
+        let (dataset, zones, data_blocks) = parse(data)?;
+
+        Ok(Self {
+            source: Source::Slice(data),
+            dataset,
+            zones,
+            data_blocks,
+        })
+    }
+
+    pub fn get_data(&self, zone_id: usize, var_id: usize) -> Result<TecData<'static>> {
+        let block = &self.data_blocks[zone_id - 1];
+        if let Some(source) = block.shared_from[var_id - 1] {
+            return self.get_data(source + 1, var_id);
+        }
+
+        let zone = &self.zones[zone_id - 1];
+        let ty = zone.data_types().unwrap()[var_id - 1];
+        let offset = block.var_offsets[var_id - 1];
+        if offset == NO_OFFSET {
+            return Ok(TecData::F32(Cow::Owned(vec![])));
+        }
+
+        let loc = zone.var_locs()[var_id - 1];
+        let len = match loc {
+            ValueLocation::Nodal => zone.node_count(),
+            ValueLocation::CellCentered => zone.cell_count(),
+        };
+
+        let input = at(self.source.bytes(), offset)?;
+        match (zone, block.subzones) {
+            (_, None) => Ok(parse_values(input, ty, len)?.1),
+            (TecZone::Ordered(z), Some(subzones)) => {
+                let dims = match loc {
+                    ValueLocation::Nodal => [z.i_max, z.j_max, z.k_max],
+                    ValueLocation::CellCentered => [
+                        (z.i_max - 1).max(1),
+                        (z.j_max - 1).max(1),
+                        (z.k_max - 1).max(1),
+                    ],
+                };
+                let skip = subzones.cells.saturating_add(subzones.nodes).saturating_mul(2);
+                let skip = skip.saturating_mul(data_type_size(ty) as u64);
+                let (_, values) = parse_values(at(input, skip)?, ty, len)?;
+                // The order has an entry per value, it is only computed once they are read.
+                let order = subzone_order(dims, z, subzones)?;
+                Ok(reorder(values, &order))
+            }
+            // FE zones partitioned into subzones are rejected on open.
+            (_, Some(_)) => Err(ParseError::NotSupportedFeature.into()),
+        }
+    }
+
+    /// Node map of a FE zone. Node indices are one-based, as returned by TecIO.
+    pub fn get_connectivity(&self, zone_id: usize) -> Result<Option<TecData<'static>>> {
+        match &self.zones[zone_id - 1] {
+            TecZone::ClassicFE(zone) => {
+                let block = &self.data_blocks[zone_id - 1];
+                match (block.node_map, block.subzones) {
+                    (Some(offset), None) => {
+                        let input = at(self.source.bytes(), offset)?;
+                        let (_, map) = sized_count(le_i32, zone.num_connections(), 4)(input)?;
+                        if let Some(i) = map.iter().position(|&n| n < 0 || n as i64 >= zone.nodes) {
+                            let error = ParseError::IndexOutOfRange {
+                                index: map[i] as i64 + 1,
+                                max: zone.nodes,
+                            };
+                            let location = Location::Offset(offset as usize + 4 * i);
+                            Err(TecioError::from(error).at(location, Some(zone_id), None))?
+                        }
+                        // Stored zero-based.
+                        let map = map.into_iter().map(|n| n + 1).collect();
+                        Ok(Some(TecData::I32(Cow::Owned(map))))
+                    }
+                    _ => Err(TecioError::Other {
+                        message: format!("Could not get zone's {} nodemap", zone_id),
+                        code: -1,
+                    }),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub fn get_var_min_max(&self, zone_id: usize, var_id: usize) -> (f64, f64) {
+        self.data_blocks[zone_id - 1].min_max[var_id - 1]
+    }
+}
+
+fn parse(data: &[u8]) -> Result<(Dataset, Vec<TecZone>, Vec<DataBlock>)> {
+    let (rest, _) = tag::<_, _, ParseError>(MAGIC)(data)?;
+    let (rest, _) = take_until::<_, _, ParseError>("\r\n")(rest)?;
+    let (rest, _) = take::<_, _, ParseError>(2u32)(rest)?;
+    let (rest, bom) = le_u64::<ParseError>(rest)?;
+    if bom != BYTE_ORDER_MARK {
+        Err(ParseError::NotSupportedFeature)?
+    }
+    let (_, header_offset) = le_u64::<ParseError>(rest)?;
+    let header = parse_tags(at(data, header_offset)?)?.1;
+    let at_header = |e: TecioError| e.at(Location::Offset(header_offset as usize), None, None);
+    check_tags(&header, HEADER_TAGS).map_err(at_header)?;
+
+    let num_zones = header_value(&header, TAG_NUM_ZONES)? as usize;
+    let num_vars = header_value(&header, TAG_NUM_VARS)? as usize;
+    let zone_var = num_zones.checked_mul(num_vars).ok_or_else(|| {
+        let count = num_zones.saturating_mul(num_vars);
+        at_header(ParseError::TooLarge { count, available: data.len() }.into())
+    })?;
+
+    let title = match header.get(&TAG_TITLE) {
+        Some(&offset) => parse_title(at(data, offset)?)?.1,
+        None => String::new(),
+    };
+    let zone_names = table(data, &header, TAG_ZONE_TITLES, |s| parse_names(s, num_zones))?;
+    let var_names = table(data, &header, TAG_VAR_NAMES, |s| parse_names(s, num_vars))?;
+
+    let zone_types = table(data, &header, TAG_ZONE_TYPES, sized_count(le_u8, num_zones, 1))?;
+    let zone_headers = table(data, &header, TAG_ZONE_HEADERS, sized_count(le_u64, num_zones, 8))?;
+    let times = table(data, &header, TAG_SOLUTION_TIMES, sized_count(le_f64, num_zones, 8))?;
+    let strands = table(data, &header, TAG_STRAND_IDS, sized_count(le_i32, num_zones, 4))?;
+    let i_max = table(data, &header, TAG_I_MAX, sized_count(le_i64, num_zones, 8))?;
+    let j_max = table(data, &header, TAG_J_MAX, sized_count(le_i64, num_zones, 8))?;
+    let k_max = table(data, &header, TAG_K_MAX, sized_count(le_i64, num_zones, 8))?;
+
+    // Per zone and variable tables are stored variable by variable.
+    let locations = table(data, &header, TAG_VAR_LOCATIONS, sized_count(le_u8, zone_var, 1))?;
+    let types = table(data, &header, TAG_VAR_TYPES, sized_count(le_u8, zone_var, 1))?;
+    let passive = table(data, &header, TAG_PASSIVE_VARS, sized_count(le_u8, zone_var, 1))?;
+    let shared = table(data, &header, TAG_SHARED_VARS, sized_count(le_i32, zone_var, 4))?;
+    let types_offset = header_value(&header, TAG_VAR_TYPES)? as usize;
+    let types = types
+        .into_iter()
+        .enumerate()
+        .map(|(i, ty)| {
+            data_type(ty).map_err(|e| {
+                TecioError::from(e).at(Location::Offset(types_offset + i), None, None)
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut min_max = Vec::with_capacity(zone_var);
+    let mut rest = section(data, &header, TAG_MIN_MAX)?;
+    for &ty in types.iter() {
+        let (r, mm) = parse_min_max(rest, ty).map_err(|e| {
+            TecioError::from(e).at(Location::Offset(data.len() - rest.len()), None, None)
+        })?;
+        rest = r;
+        min_max.push(mm);
+    }
+
+    let subzone_size = [
+        header.get(&TAG_SUBZONE_I).copied().unwrap_or(6) as usize,
+        header.get(&TAG_SUBZONE_J).copied().unwrap_or(6) as usize,
+        header.get(&TAG_SUBZONE_K).copied().unwrap_or(6) as usize,
+    ];
+
+    let mut zones = Vec::with_capacity(num_zones);
+    let mut data_blocks = Vec::with_capacity(num_zones);
+    for (z, name) in zone_names.into_iter().enumerate() {
+        let index = |v: usize| v * num_zones + z;
+        let located = |offset: u64| {
+            move |e: TecioError| e.at(Location::Offset(offset as usize), Some(z + 1), None)
+        };
+        if let Some(v) = (0..num_vars).find(|&v| shared[index(v)] >= z as i32) {
+            let error = ParseError::IndexOutOfRange {
+                index: shared[index(v)] as i64 + 1,
+                max: z as i64,
+            };
+            let offset = header_value(&header, TAG_SHARED_VARS)? + 4 * index(v) as u64;
+            Err(TecioError::Malformed {
+                error,
+                location: Location::Offset(offset as usize),
+                zone: Some(z + 1),
+                var: Some(v + 1),
+            })?
+        }
+        let var_location = (0..num_vars)
+            .map(|v| match locations[index(v)] {
+                0 => ValueLocation::CellCentered,
+                _ => ValueLocation::Nodal,
+            })
+            .collect::<Vec<_>>();
+        let var_types = (0..num_vars).map(|v| types[index(v)]).collect::<Vec<_>>();
+        let passive_var_list = (0..num_vars)
+            .map(|v| passive[index(v)] as i32)
+            .collect::<Vec<_>>();
+        let var_share_list = (0..num_vars)
+            .map(|v| shared[index(v)].max(-1) + 1)
+            .collect::<Vec<_>>();
+
+
+        let zone_type_offset = header_value(&header, TAG_ZONE_TYPES)? + z as u64;
+        let ty = zone_type(zone_types[z]).map_err(|e| located(zone_type_offset)(e.into()))?;
+        let zone = match ty {
+            ZoneType::Ordered => TecZone::Ordered(OrderedZone {
+                name,
+                id: z as i32 + 1,
+                solution_time: times[z],
+                strand: strands[z],
+                parent_zone: 0,
+                i_max: i_max[z],
+                j_max: j_max[z],
+                k_max: k_max[z],
+                var_location,
+                var_types: Some(var_types),
+                var_share_list,
+                passive_var_list,
+                aux_data: AuxData::new(),
+            }),
+            zone_type @ ZoneType::FELine
+            | zone_type @ ZoneType::FETriangle
+            | zone_type @ ZoneType::FEQuad
+            | zone_type @ ZoneType::FETetra
+            | zone_type @ ZoneType::FEBrick => TecZone::ClassicFE(ClassicFEZone {
+                name,
+                zone_type,
+                id: z as i32 + 1,
+                solution_time: times[z],
+                strand: strands[z],
+                parent_zone: 0,
+                nodes: i_max[z],
+                cells: j_max[z],
+                var_location,
+                var_types: Some(var_types),
+                var_share_list,
+                passive_var_list,
+                connectivity_share_zone: 0,
+                aux_data: AuxData::new(),
+            }),
+            _ => Err(located(zone_type_offset)(ParseError::NotSupportedFeature.into()))?,
+        };
+        // Sizes are used as value counts, FE zones store node and cell counts as I and J.
+        let sizes: &[_] = match zone {
+            TecZone::Ordered(_) => {
+                &[(TAG_I_MAX, &i_max, 1), (TAG_J_MAX, &j_max, 1), (TAG_K_MAX, &k_max, 1)]
+            }
+            _ => &[(TAG_I_MAX, &i_max, 0), (TAG_J_MAX, &j_max, 0)],
+        };
+        for &(tag, sizes, min) in sizes {
+            if sizes[z] < min {
+                let offset = header_value(&header, tag)? + 8 * z as u64;
+                Err(located(offset)(ParseError::InvalidValue("zone size").into()))?
+            }
+        }
+
+        let zone_header = parse_tags(at(data, zone_headers[z])?)
+            .map_err(|e| located(zone_headers[z])(e.into()))?
+            .1;
+        check_tags(&zone_header, ZONE_HEADER_TAGS).map_err(located(zone_headers[z]))?;
+        let var_offsets_offset = header_value(&zone_header, TAG_VAR_OFFSETS)?;
+        let var_offsets = sized_count(le_u64, num_vars, 8)(at(data, var_offsets_offset)?)
+            .map_err(|e| located(var_offsets_offset)(e.into()))?
+            .1;
+        let subzones = match (
+            zone_header.get(&TAG_NUM_CELL_SUBZONES),
+            zone_header.get(&TAG_NUM_NODE_SUBZONES),
+        ) {
+            (Some(&cells), Some(&nodes)) => Some(Subzones {
+                cells,
+                nodes,
+                size: subzone_size,
+            }),
+            _ => None,
+        };
+        // Subzone blocks and node maps of FE zones are only decoded by TecIO.
+        match (&zone, subzones) {
+            (TecZone::Ordered(zone), Some(subzones)) => {
+                check_subzones(zone, subzones).map_err(located(zone_headers[z]))?
+            }
+            (TecZone::Ordered(_), None) => {}
+            (_, None) if zone_types[z].is_ascii_lowercase() => {}
+            _ => Err(located(zone_type_offset)(ParseError::NotSupportedFeature.into()))?,
+        }
+
+        data_blocks.push(DataBlock {
+            var_offsets,
+            shared_from: (0..num_vars)
+                .map(|v| match shared[index(v)] {
+                    s if s >= 0 => Some(s as usize),
+                    _ => None,
+                })
+                .collect(),
+            node_map: zone_header
+                .get(&TAG_NODE_MAP)
+                .copied()
+                .filter(|&offset| offset != NO_OFFSET),
+            subzones,
+            min_max: (0..num_vars).map(|v| min_max[index(v)]).collect(),
+        });
+        zones.push(zone);
+    }
+
+    let dataset = Dataset {
+        num_variables: num_vars as _,
+        num_zones: num_zones as _,
+        title,
+        var_names,
+        aux_data: AuxData::new(),
+        var_aux_data: vec![],
+        geometries: vec![],
+        texts: vec![],
+        custom_labels: vec![],
+        user_records: vec![],
+    };
+
+    Ok((dataset, zones, data_blocks))
+}
+
+fn at(data: &[u8], offset: u64) -> Result<&[u8]> {
+    data.get(offset as usize..).ok_or_else(|| {
+        TecioError::from(ParseError::NomError(ErrorKind::Eof)).at(
+            Location::Offset(offset as usize),
+            None,
+            None,
+        )
+    })
+}
+
+/// Parses the section of `tag`, errors are located at the start of the section.
+fn table<'a, T, P>(data: &'a [u8], header: &HashMap<u16, u64>, tag: u16, parser: P) -> Result<T>
+where
+    P: Fn(&'a [u8]) -> IResult<&'a [u8], T, ParseError>,
+{
+    let input = section(data, header, tag)?;
+    parser(input).map(|(_, value)| value).map_err(|e| {
+        TecioError::from(e).at(Location::Offset(data.len() - input.len()), None, None)
+    })
+}
+
+fn header_value(header: &HashMap<u16, u64>, tag: u16) -> Result<u64> {
+    header
+        .get(&tag)
+        .copied()
+        .ok_or(TecioError::ParseError(ParseError::WrongHeaderTag))
+}
+
+fn section<'a>(data: &'a [u8], header: &HashMap<u16, u64>, tag: u16) -> Result<&'a [u8]> {
+    at(data, header_value(header, tag)?)
+}
+
+fn check_tags(tags: &HashMap<u16, u64>, known: &[u16]) -> Result<()> {
+    if tags.keys().all(|tag| known.contains(tag)) {
+        Ok(())
+    } else {
+        Err(ParseError::NotSupportedFeature.into())
+    }
+}
+
+fn parse_tags(input: &[u8]) -> IResult<&[u8], HashMap<u16, u64>, ParseError> {
+    let (rest, n) = le_u16(input)?;
+    let (rest, tags) = sized_count(le_u16, n as usize, 2)(rest)?;
+    let (rest, values) = sized_count(le_u64, n as usize, 8)(rest)?;
+    Ok((rest, tags.into_iter().zip(values).collect()))
+}
+
+fn parse_title(input: &[u8]) -> IResult<&[u8], String, ParseError> {
+    let (rest, len) = le_u32(input)?;
+    let (rest, title) = take(len)(rest)?;
+    let title = std::str::from_utf8(title).map_err(|_| nom::Err::Error(ParseError::Utf8Error))?;
+    Ok((rest, title.to_owned()))
+}
+
+fn parse_names(input: &[u8], num: usize) -> IResult<&[u8], Vec<String>, ParseError> {
+    let (rest, len) = le_u64(input)?;
+    let (rest, names) = take(len)(rest)?;
+    let names = std::str::from_utf8(names).map_err(|_| nom::Err::Error(ParseError::Utf8Error))?;
+    let names = names
+        .split_terminator('\r')
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    if names.len() != num {
+        return Err(nom::Err::Error(ParseError::WrongHeaderTag));
+    }
+    Ok((rest, names))
+}
+
+fn parse_min_max(input: &[u8], ty: TecDataType) -> IResult<&[u8], (f64, f64), ParseError> {
+    let (rest, values) = parse_values(input, ty, 2)?;
+    let values = values.as_f64();
+    Ok((rest, (values[0], values[1])))
+}
+
+fn parse_values(input: &[u8], ty: TecDataType, len: usize) -> IResult<&[u8], TecData<'static>, ParseError> {
+    match ty {
+        TecDataType::F32 => sized_count(le_f32, len, 4)(input).map(|(r, v)| (r, TecData::from(v))),
+        TecDataType::F64 => sized_count(le_f64, len, 8)(input).map(|(r, v)| (r, TecData::from(v))),
+        TecDataType::I32 => sized_count(le_i32, len, 4)(input).map(|(r, v)| (r, TecData::from(v))),
+        TecDataType::I16 => sized_count(le_i16, len, 2)(input).map(|(r, v)| (r, TecData::from(v))),
+        TecDataType::I8 => sized_count(le_u8, len, 1)(input)
+            .map(|(r, v)| (r, TecData::from(v.into_iter().map(|b| b as i8).collect::<Vec<_>>()))),
+        // Packed eight to a byte, starting with the lowest bit, like in `.plt` files.
+        TecDataType::I1 => take(len.div_ceil(8))(input).map(|(r, v): (_, &[u8])| {
+            let bits = (0..len).map(|i| (v[i / 8] >> (i % 8) & 1) as i8).collect::<Vec<_>>();
+            (r, TecData::from(bits))
+        }),
+    }
+}
+
+fn data_type_size(ty: TecDataType) -> usize {
+    match ty {
+        TecDataType::F64 => 8,
+        TecDataType::F32 | TecDataType::I32 => 4,
+        TecDataType::I16 => 2,
+        TecDataType::I8 | TecDataType::I1 => 1,
+    }
+}
+
+fn data_type(ty: u8) -> std::result::Result<TecDataType, ParseError> {
+    match ty {
+        1..=6 => TecDataType::try_from(ty as i32),
+        _ => Err(ParseError::WrongDataTag),
+    }
+}
+
+/// Zone types are stored as a single character, `o` for ordered zones and `p` + zone type for
+/// finite element ones. Upper case is used for zones partitioned into several subzones.
+fn zone_type(ty: u8) -> std::result::Result<ZoneType, ParseError> {
+    match ty.to_ascii_lowercase() {
+        b'o' => Ok(ZoneType::Ordered),
+        t @ b'q'..=b'w' => ZoneType::try_from((t - b'p') as i32),
+        _ => Err(ParseError::WrongHeaderTag),
+    }
+}
+
+/// Splits `len` items into subzones of `size`, the remainder is shared between the first and
+/// the last subzone.
+fn subzone_ranges(len: usize, size: usize) -> Vec<(usize, usize)> {
+    if len < size || size == 0 {
+        return vec![(0, len)];
+    }
+    let inner = (len - size) / size;
+    let rest = len - size * inner;
+    let first = rest.div_ceil(2);
+    let mut ranges = Vec::with_capacity(inner + 2);
+    ranges.push((0, first));
+    ranges.extend((0..inner).map(|n| (first + n * size, first + (n + 1) * size)));
+    ranges.push((first + inner * size, len));
+    ranges
+}
+
+/// Number of ranges returned by [`subzone_ranges`].
+fn num_subzone_ranges(len: usize, size: usize) -> usize {
+    if len < size || size == 0 {
+        1
+    } else {
+        (len - size) / size + 2
+    }
+}
+
+/// Subzone counts of the zone header have to match the default partitioning.
+fn check_subzones(zone: &OrderedZone, subzones: Subzones) -> Result<()> {
+    let num = |dims: [i64; 3]| {
+        dims.iter()
+            .zip(subzones.size.iter())
+            .map(|(&d, &s)| num_subzone_ranges(d as usize, s) as u64)
+            .fold(1, u64::saturating_mul)
+    };
+    let cells = num([
+        (zone.i_max - 1).max(1),
+        (zone.j_max - 1).max(1),
+        (zone.k_max - 1).max(1),
+    ]);
+    if cells != subzones.cells || num([zone.i_max, zone.j_max, zone.k_max]) != subzones.nodes {
+        Err(ParseError::NotSupportedFeature)?
+    }
+    Ok(())
+}
+
+/// Returns IJK index of every value in the order they are stored in the file.
+fn subzone_order(dims: [i64; 3], zone: &OrderedZone, subzones: Subzones) -> Result<Vec<usize>> {
+    let ranges = |dims: [i64; 3]| {
+        let mut ranges = vec![];
+        for (&d, &s) in dims.iter().zip(subzones.size.iter()) {
+            ranges.push(subzone_ranges(d as usize, s));
+        }
+        ranges
+    };
+    check_subzones(zone, subzones)?;
+    let [ni, nj, _] = [dims[0] as usize, dims[1] as usize, dims[2] as usize];
+    let ranges = ranges(dims);
+    let mut order = Vec::with_capacity(dims.iter().fold(1, |n, &d| n.saturating_mul(d as usize)));
+    for &(k0, k1) in ranges[2].iter() {
+        for &(j0, j1) in ranges[1].iter() {
+            for &(i0, i1) in ranges[0].iter() {
+                for k in k0..k1 {
+                    for j in j0..j1 {
+                        order.extend((i0..i1).map(|i| i + j * ni + k * ni * nj));
+                    }
+                }
+            }
+        }
+    }
+    Ok(order)
+}
+
+fn reorder(data: TecData<'static>, order: &[usize]) -> TecData<'static> {
+    macro_rules! reorder {
+        ($v: expr) => {{
+            let mut out = $v.to_vec();
+            for (value, &ind) in $v.iter().zip(order) {
+                out[ind] = *value;
+            }
+            TecData::from(out)
+        }};
+    }
+    match data {
+        TecData::F64(v) => reorder!(v),
+        TecData::F32(v) => reorder!(v),
+        TecData::I64(v) => reorder!(v),
+        TecData::I32(v) => reorder!(v),
+        TecData::I16(v) => reorder!(v),
+        TecData::I8(v) => reorder!(v),
+        TecData::U64(v) => reorder!(v),
+        TecData::U32(v) => reorder!(v),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{num_subzone_ranges, parse_values, subzone_ranges, SzlFormat};
+    use crate::PltFormat;
+
+    #[test]
+    fn ranges_test() {
+        assert_eq!(subzone_ranges(5, 6), vec![(0, 5)]);
+        assert_eq!(subzone_ranges(12, 6), vec![(0, 3), (3, 9), (9, 12)]);
+        assert_eq!(subzone_ranges(25, 6), vec![(0, 4), (4, 10), (10, 16), (16, 22), (22, 25)]);
+        for len in 0..40 {
+            assert_eq!(num_subzone_ranges(len, 6), subzone_ranges(len, 6).len());
+        }
+    }
+
+    #[test]
+    fn bit_values() {
+        use crate::{TecData, TecDataType};
+
+        let (rest, bits) = parse_values(&[0b1000_0101, 0b11, 0xff], TecDataType::I1, 10).unwrap();
+        assert_eq!(bits, TecData::from(vec![1i8, 0, 1, 0, 0, 0, 0, 1, 1, 1]));
+        assert_eq!(rest, &[0xff]);
+        assert!(parse_values(&[0xff], TecDataType::I1, 9).is_err());
+    }
+
+    #[test]
+    fn compare_with_plt() {
+        for (szplt, plt) in [
+            ("./tests/heated_fin.szplt", "./tests/heated_fin.plt"),
+            ("./tests/test_heat.szplt", "./tests/heat.plt"),
+        ]
+        .iter()
+        {
+            let s = SzlFormat::open(szplt).unwrap();
+            let p = PltFormat::open(plt).unwrap();
+            assert_eq!(s.zones.len(), p.zones.len());
+            assert_eq!(s.dataset.var_names, p.dataset.var_names);
+            for z in 1..=s.zones.len() {
+                for v in 1..=s.dataset.num_variables as usize {
+                    assert_eq!(
+                        s.get_data(z, v).unwrap(),
+                        p.get_data(z, v).unwrap(),
+                        "Data in zone {}, var {}, is not equal",
+                        z,
+                        v
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fe_test() {
+        // Written by TecIO, the same file as `simple_write.szplt` of the writer tests.
+        let s = SzlFormat::open("./tests/fe_triangle.szplt").unwrap();
+        assert_eq!(s.get_data(1, 2).unwrap().as_f64(), vec![0.0, 0.0, 1.0]);
+        assert_eq!(s.get_data(1, 4).unwrap().as_f64(), vec![1e3]);
+        assert_eq!(s.get_connectivity(1).unwrap().unwrap().to_vec::<i32>().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn partitioned_fe_zone() {
+        use crate::ParseError;
+
+        // The zone type is stored upper case for FE zones split into subzones.
+        let mut bytes = std::fs::read("./tests/fe_triangle.szplt").unwrap();
+        let zone_types = bytes.windows(4).position(|w| w == b"123\r").unwrap() + 4;
+        assert_eq!(bytes[zone_types], b'r');
+        bytes[zone_types] = b'R';
+        let error = SzlFormat::read(&bytes).unwrap_err();
+        assert_eq!(error.parse_error(), Some(ParseError::NotSupportedFeature));
+    }
+
+    #[test]
+    fn unknown_records() {
+        use crate::ParseError;
+
+        let original = std::fs::read("./tests/fe_triangle.szplt").unwrap();
+        let u64_at = |at: usize| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&original[at..at + 8]);
+            u64::from_le_bytes(bytes) as usize
+        };
+        // Tag tables are a count followed by all tags and then all values.
+        let find_tag = |table: usize, tag: u16| {
+            let n = u16::from_le_bytes([original[table], original[table + 1]]) as usize;
+            let i = (0..n)
+                .find(|i| original[table + 2 + 2 * i..][..2] == tag.to_le_bytes())
+                .unwrap();
+            (table + 2 + 2 * i, u64_at(table + 2 + 2 * n + 8 * i))
+        };
+        let header = u64_at(original.windows(2).position(|w| w == b"\r\n").unwrap() + 10);
+        let (_, zone_headers) = find_tag(header, 0x0102);
+        for &(table, tag) in [(header, 0x010c), (u64_at(zone_headers), 0x0602)].iter() {
+            let (position, _) = find_tag(table, tag);
+            let mut bytes = original.clone();
+            bytes[position..position + 2].copy_from_slice(&0x0fffu16.to_le_bytes());
+            let error = SzlFormat::read(&bytes).unwrap_err();
+            assert_eq!(error.parse_error(), Some(ParseError::NotSupportedFeature));
+        }
+    }
+
+    #[test]
+    fn malformed_input() {
+        use crate::{Location, ParseError, TecioError};
+
+        let original = std::fs::read("./tests/test_heat.szplt").unwrap();
+        let u64_at = |at: usize| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&original[at..at + 8]);
+            u64::from_le_bytes(bytes) as usize
+        };
+        let read_all = |bytes: &[u8]| {
+            if let Ok(szl) = SzlFormat::read(bytes) {
+                for zone in 1..=szl.zones.len() {
+                    for var in 1..=szl.dataset.var_names.len() {
+                        let _ = szl.get_data(zone, var);
+                    }
+                    let _ = szl.get_connectivity(zone);
+                }
+            }
+        };
+        for len in (0..original.len()).step_by(97) {
+            read_all(&original[..len]);
+        }
+
+        // Position of the value of `tag` in the tag table at `table`.
+        let value_of = |table: usize, tag: u16| {
+            let n = u16::from_le_bytes([original[table], original[table + 1]]) as usize;
+            let i = (0..n)
+                .find(|i| original[table + 2 + 2 * i..][..2] == tag.to_le_bytes())
+                .unwrap();
+            table + 2 + 2 * n + 8 * i
+        };
+        let header = u64_at(original.windows(2).position(|w| w == b"\r\n").unwrap() + 10);
+        let with_u64 = |at: usize, value: u64| {
+            let mut bytes = original.clone();
+            bytes[at..at + 8].copy_from_slice(&value.to_le_bytes());
+            bytes
+        };
+        let num_zones = value_of(header, 0x0001);
+        let error = SzlFormat::read(&with_u64(num_zones, u64::MAX)).unwrap_err();
+        assert!(matches!(error.parse_error(), Some(ParseError::TooLarge { .. })));
+        assert!(SzlFormat::read(&with_u64(num_zones, 1 << 40)).is_err());
+        let i_max = u64_at(value_of(header, 0x0107));
+        match SzlFormat::read(&with_u64(i_max, -1i64 as u64)) {
+            Err(TecioError::Malformed {
+                error: ParseError::InvalidValue("zone size"),
+                location: Location::Offset(at),
+                zone: Some(1),
+                var: None,
+            }) => assert_eq!(at, i_max),
+            r => panic!("Expected invalid zone size, got {:?}", r.map(|_| ())),
+        }
+
+        // Overwrites words of a valid file with values likely to end up as sizes or offsets.
+        let mut seed = 0x2545_f491u32;
+        let mut random = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as usize
+        };
+        for _ in 0..500 {
+            let mut bytes = original.clone();
+            for _ in 0..4 {
+                let at = random() % (bytes.len() / 8) * 8;
+                let values = [0, 1, u64::MAX, i64::MAX as u64, 1 << 40, random() as u64];
+                let value = values[random() % values.len()];
+                bytes[at..at + 8].copy_from_slice(&value.to_le_bytes());
+            }
+            read_all(&bytes);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    convert::From,
+    convert::{From, TryFrom},
     ffi::{c_void, CString, OsStr},
     path::Path,
     ptr::null_mut,
@@ -11,8 +11,10 @@ use libc::c_char;
 use crate::{
     bindings,
     common::{
-        try_err, ClassicFEZone, Dataset, OrderedZone, Result, TecData, TecDataType, TecZone,
-        TecioError, ValueLocation, ZoneType,
+        try_err, unquote_labels, ArrowheadAttachment, ArrowheadStyle, AuxData, ClassicFEZone, Clipping, CoordSys,
+        Dataset, Geometry, GeometryShape, LinePattern, OrderedZone, ParseError, PolyFE, PolyFaces,
+        Polyline, Result, Scope, SizeUnits, TecData, TecDataType, TecZone, TecioError, TextAnchor,
+        TextAnnotation, TextBox, ValueLocation, ZoneType,
     },
 };
 
@@ -81,8 +83,63 @@ impl SzpltFormat {
                 let name = name.into_string()?;
 
                 dataset.var_names.push(name);
+
+                let mut num_items = 0;
+                try_err(
+                    bindings::tecVarAuxDataGetNumItems(file_handle, i, &mut num_items),
+                    format!("Error reading var aux data, num = {}.", i),
+                )?;
+                dataset.var_aux_data.push(read_aux_data(num_items, |item, name, value| {
+                    bindings::tecVarAuxDataGetItem(file_handle, i, item, name, value)
+                })?);
+            }
+            if dataset.var_aux_data.iter().all(|aux| aux.is_empty()) {
+                dataset.var_aux_data.clear();
+            }
+
+            let mut num_items = 0;
+            try_err(
+                bindings::tecDataSetAuxDataGetNumItems(file_handle, &mut num_items),
+                "Error reading dataset aux data.",
+            )?;
+            dataset.aux_data = read_aux_data(num_items, |item, name, value| {
+                bindings::tecDataSetAuxDataGetItem(file_handle, item, name, value)
+            })?;
+
+            let mut num_geoms = 0;
+            try_err(
+                bindings::tecGeomGetNumGeoms(file_handle, &mut num_geoms),
+                "Error reading geometry number.",
+            )?;
+            for g in 1..=num_geoms {
+                dataset.geometries.push(read_geometry(file_handle, g)?);
+            }
+
+            let mut num_texts = 0;
+            try_err(
+                bindings::tecTextGetNumTexts(file_handle, &mut num_texts),
+                "Error reading text number.",
+            )?;
+            for t in 1..=num_texts {
+                dataset.texts.push(read_text(file_handle, t)?);
             }
 
+            let mut num_sets = 0;
+            try_err(
+                bindings::tecCustomLabelsGetNumSets(file_handle, &mut num_sets),
+                "Error reading custom label set number.",
+            )?;
+            for set in 1..=num_sets {
+                let mut labels = null_mut();
+                try_err(
+                    bindings::tecCustomLabelsGetSet(file_handle, set, &mut labels),
+                    format!("Error reading custom label set {}.", set),
+                )?;
+                let labels = CString::from_raw(labels).into_string()?;
+                dataset.custom_labels.push(unquote_labels(&labels));
+            }
+            // tecio can write user records but has no way of reading them back.
+
             let mut zones = Vec::with_capacity(num_zones as usize);
             for i in 1..num_zones + 1 {
                 let mut zone_type = -1;
@@ -116,6 +173,15 @@ impl SzpltFormat {
 
                 let zone_name = zone_name.into_string()?;
 
+                let mut num_items = 0;
+                try_err(
+                    bindings::tecZoneAuxDataGetNumItems(file_handle, i, &mut num_items),
+                    format!("Error reading zone aux data, num = {}.", i),
+                )?;
+                let aux_data = read_aux_data(num_items, |item, name, value| {
+                    bindings::tecZoneAuxDataGetItem(file_handle, i, item, name, value)
+                })?;
+
                 let mut i_max: i64 = 0;
                 let mut j_max: i64 = 0;
                 let mut k_max: i64 = 0;
@@ -130,6 +196,12 @@ impl SzpltFormat {
                     format!("Error reading zone solution time, num = {}.", i),
                 )?;
 
+                let mut parent_zone: i32 = 0;
+                try_err(
+                    bindings::tecZoneGetParentZone(file_handle, i, &mut parent_zone),
+                    format!("Error reading zone parent zone, num = {}.", i),
+                )?;
+
                 let mut strand_id: i32 = 0;
                 try_err(
                     bindings::tecZoneGetStrandID(file_handle, i, &mut strand_id),
@@ -170,13 +242,16 @@ impl SzpltFormat {
                         id: i,
                         solution_time: time,
                         strand: strand_id,
+                        parent_zone,
                         i_max,
                         j_max,
                         k_max,
 
                         var_location: locs,
                         var_types: None,
+                        var_share_list: vec![],
                         passive_var_list: passive,
+                        aux_data,
                     }),
                     ZoneType::FEQuad
                     | ZoneType::FETriangle
@@ -190,19 +265,70 @@ impl SzpltFormat {
                             id: i,
                             solution_time: time,
                             strand: strand_id,
+                            parent_zone,
 
                             nodes: i_max,
                             cells: j_max,
 
                             var_location: locs,
                             var_types: None,
+                            var_share_list: vec![],
+                            passive_var_list: passive,
+                            connectivity_share_zone: 0,
+                            aux_data,
                         })
                     }
-                    zone => {
-                        return Err(TecioError::Other {
-                            message: format!("Not implemented zone type: {:?}", zone),
-                            code: -1,
-                        });
+                    ZoneType::FEPolygon | ZoneType::FEPolyhedron => {
+                        let mut total_num_face_nodes = 0;
+                        try_err(
+                            bindings::tecZonePolyGetTotalNumFaceNodes(
+                                file_handle,
+                                i,
+                                &mut total_num_face_nodes,
+                            ),
+                            format!("Error reading zone face nodes, num = {}.", i),
+                        )?;
+                        let mut boundary_faces = 0;
+                        try_err(
+                            bindings::tecZonePolyGetNumConnectedBoundaryFaces(
+                                file_handle,
+                                i,
+                                &mut boundary_faces,
+                            ),
+                            format!("Error reading zone boundary faces, num = {}.", i),
+                        )?;
+                        let mut boundary_connections = 0;
+                        try_err(
+                            bindings::tecZonePolyGetTotalNumBoundaryConnections(
+                                file_handle,
+                                i,
+                                &mut boundary_connections,
+                            ),
+                            format!("Error reading zone boundary connections, num = {}.", i),
+                        )?;
+
+                        TecZone::PolyFE(PolyFE {
+                            name: zone_name,
+                            zone_type,
+                            id: i,
+                            solution_time: time,
+                            strand: strand_id,
+                            parent_zone,
+
+                            nodes: i_max,
+                            cells: j_max,
+                            faces: k_max,
+                            total_num_face_nodes,
+                            num_connected_boundary_faces: boundary_faces,
+                            total_num_boundary_connections: boundary_connections,
+
+                            var_location: locs,
+                            var_types: None,
+                            var_share_list: vec![],
+                            passive_var_list: passive,
+                            connectivity_share_zone: 0,
+                            aux_data,
+                        })
                     }
                 };
 
@@ -234,7 +360,7 @@ impl SzpltFormat {
             )?
         };
 
-        Ok(TecDataType::from(data_type))
+        Ok(TecDataType::try_from(data_type)?)
     }
 
     pub fn get_data(&self, zone_id: usize, var_id: usize) -> Result<TecData> {
@@ -271,34 +397,14 @@ impl SzpltFormat {
                 format!("Cannot get var's {} data type", var_id),
             )?;
         }
-        let data_type = TecDataType::from(data_type);
+        let data_type = TecDataType::try_from(data_type)?;
 
-        match data_type {
-            TecDataType::F64 => {
-                let mut vec = vec![0.0; num_values as _];
-                unsafe {
-                    try_err(
-                        bindings::tecZoneVarGetDoubleValues(
-                            self.file_handle,
-                            zone_id as _,
-                            var_id as _,
-                            1,
-                            num_values,
-                            vec.as_mut_ptr(),
-                        ),
-                        format!(
-                            "Cannot get F64 values for var = {} of zone = {}.",
-                            var_id, zone_id
-                        ),
-                    )
-                };
-                Ok(TecData::F64(Cow::Owned(vec)))
-            }
-            TecDataType::F32 => {
-                let mut vec = vec![0.0; num_values as _];
+        macro_rules! values {
+            ($get: ident, $zero: expr, $name: expr) => {{
+                let mut vec = vec![$zero; num_values as _];
                 unsafe {
                     try_err(
-                        bindings::tecZoneVarGetFloatValues(
+                        bindings::$get(
                             self.file_handle,
                             zone_id as _,
                             var_id as _,
@@ -307,15 +413,25 @@ impl SzpltFormat {
                             vec.as_mut_ptr(),
                         ),
                         format!(
-                            "Cannot get F64 values for var = {} of zone = {}.",
-                            var_id, zone_id
+                            "Cannot get {} values for var = {} of zone = {}.",
+                            $name, var_id, zone_id
                         ),
-                    )
+                    )?
                 };
-                Ok(TecData::F32(Cow::Owned(vec)))
-            }
-            _ => unimplemented!(),
+                vec
+            }};
         }
+        Ok(match data_type {
+            TecDataType::F64 => TecData::from(values!(tecZoneVarGetDoubleValues, 0.0, "F64")),
+            TecDataType::F32 => TecData::from(values!(tecZoneVarGetFloatValues, 0.0, "F32")),
+            TecDataType::I32 => TecData::from(values!(tecZoneVarGetInt32Values, 0, "I32")),
+            TecDataType::I16 => TecData::from(values!(tecZoneVarGetInt16Values, 0, "I16")),
+            // Bytes and bits are both read as unsigned bytes.
+            TecDataType::I8 | TecDataType::I1 => {
+                let bytes = values!(tecZoneVarGetUInt8Values, 0u8, "byte");
+                TecData::from(bytes.into_iter().map(|b| b as i8).collect::<Vec<_>>())
+            }
+        })
     }
 
     pub fn get_connectivity(&self, zone_id: i32) -> Result<Option<TecData>> {
@@ -366,8 +482,7 @@ impl SzpltFormat {
                     _ => unreachable!(),
                 }
             }
-            TecZone::Ordered(_) => Ok(None),
-            _ => unimplemented!(),
+            TecZone::Ordered(_) | TecZone::PolyFE(_) => Ok(None),
         }
         //        let mut i_max: i64 = 0;
         //        let mut j_max: i64 = 0;
@@ -428,6 +543,299 @@ impl SzpltFormat {
         //
         //        Ok(vec)
     }
+
+    /// Face connectivity of a polygonal or polyhedral zone with one-based indices.
+    pub fn get_poly_faces(&self, zone_id: i32) -> Result<Option<PolyFaces>> {
+        let zone = match &self.zones[zone_id as usize - 1] {
+            TecZone::PolyFE(zone) => zone,
+            _ => return Ok(None),
+        };
+        let faces = zone.faces;
+
+        let face_node_counts = match zone.zone_type {
+            ZoneType::FEPolyhedron => {
+                let mut counts = vec![0; faces as usize];
+                try_err!(
+                    bindings::tecZonePolyGetFaceNodeCounts(
+                        self.file_handle,
+                        zone_id,
+                        1,
+                        faces,
+                        counts.as_mut_ptr(),
+                    ),
+                    format!("Could not get zone's {} face node counts", zone_id)
+                );
+                counts
+            }
+            _ => vec![2; faces as usize],
+        };
+
+        let mut face_nodes = vec![0; zone.total_num_face_nodes as usize];
+        try_err!(
+            bindings::tecZonePolyGetFaceNodes(
+                self.file_handle,
+                zone_id,
+                1,
+                faces,
+                face_nodes.as_mut_ptr(),
+            ),
+            format!("Could not get zone's {} face nodes", zone_id)
+        );
+
+        let mut left = vec![0; faces as usize];
+        let mut right = vec![0; faces as usize];
+        try_err!(
+            bindings::tecZonePolyGetFaceElems(
+                self.file_handle,
+                zone_id,
+                1,
+                faces,
+                left.as_mut_ptr(),
+                right.as_mut_ptr(),
+            ),
+            format!("Could not get zone's {} face elements", zone_id)
+        );
+
+        let boundary_faces = zone.num_connected_boundary_faces;
+        let mut boundary_counts = vec![0; boundary_faces as usize];
+        let mut boundary_elements = vec![0; zone.total_num_boundary_connections as usize];
+        let mut boundary_zones = vec![0; zone.total_num_boundary_connections as usize];
+        if boundary_faces > 0 {
+            try_err!(
+                bindings::tecZonePolyGetBoundaryConnectionCounts(
+                    self.file_handle,
+                    zone_id,
+                    1,
+                    boundary_faces,
+                    boundary_counts.as_mut_ptr(),
+                ),
+                format!("Could not get zone's {} boundary connection counts", zone_id)
+            );
+            try_err!(
+                bindings::tecZonePolyGetBoundaryConnections(
+                    self.file_handle,
+                    zone_id,
+                    1,
+                    boundary_faces,
+                    boundary_elements.as_mut_ptr(),
+                    boundary_zones.as_mut_ptr(),
+                ),
+                format!("Could not get zone's {} boundary connections", zone_id)
+            );
+        }
+
+        let widen = |values: Vec<i32>| values.into_iter().map(i64::from).collect();
+        Ok(Some(PolyFaces {
+            face_node_counts,
+            face_nodes: widen(face_nodes),
+            face_left_elements: widen(left),
+            face_right_elements: widen(right),
+            boundary_connection_counts: boundary_counts,
+            boundary_connection_elements: widen(boundary_elements),
+            boundary_connection_zones: boundary_zones,
+        }))
+    }
+}
+
+unsafe fn read_geometry(file_handle: *mut c_void, geom: i32) -> Result<Geometry> {
+    macro_rules! get {
+        ($f: ident, $init: expr) => {{
+            let mut value = $init;
+            try_err(
+                bindings::$f(file_handle, geom, &mut value),
+                format!("Error reading geometry {}, {}.", geom, stringify!($f)),
+            )?;
+            value
+        }};
+    }
+    let mut anchor = [0.0; 3];
+    try_err(
+        bindings::tecGeomGetAnchorPos(
+            file_handle,
+            geom,
+            &mut anchor[0],
+            &mut anchor[1],
+            &mut anchor[2],
+        ),
+        format!("Error reading geometry {} position.", geom),
+    )?;
+    let coord_sys = coord_sys(get!(tecGeomGetCoordMode, 0))?;
+
+    let shape = match get!(tecGeomGetType, 0) {
+        0 | 5 => {
+            let mut lines = vec![];
+            for segment in 1..=get!(tecGeomLineGetSegmentCount, 0) {
+                let mut num_points = 0;
+                try_err(
+                    bindings::tecGeomLineSegmentGetPointCount(
+                        file_handle,
+                        geom,
+                        segment,
+                        &mut num_points,
+                    ),
+                    format!("Error reading geometry {} point count.", geom),
+                )?;
+                let mut line = Polyline::default();
+                for index in 1..=num_points {
+                    let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+                    try_err(
+                        bindings::tecGeomLineGetPoint(
+                            file_handle,
+                            geom,
+                            segment,
+                            index,
+                            &mut x,
+                            &mut y,
+                            &mut z,
+                        ),
+                        format!("Error reading geometry {} points.", geom),
+                    )?;
+                    line.x.push(x);
+                    line.y.push(y);
+                    if coord_sys == CoordSys::Grid3D {
+                        line.z.push(z);
+                    }
+                }
+                lines.push(line);
+            }
+            GeometryShape::Lines(lines)
+        }
+        1 => {
+            let (mut width, mut height) = (0.0, 0.0);
+            try_err(
+                bindings::tecGeomRectangleGetSize(file_handle, geom, &mut width, &mut height),
+                format!("Error reading geometry {} size.", geom),
+            )?;
+            GeometryShape::Rectangle { width, height }
+        }
+        2 => GeometryShape::Square {
+            size: get!(tecGeomSquareGetSize, 0.0),
+        },
+        3 => GeometryShape::Circle {
+            radius: get!(tecGeomCircleGetRadius, 0.0),
+        },
+        4 => {
+            let (mut horizontal_axis, mut vertical_axis) = (0.0, 0.0);
+            try_err(
+                bindings::tecGeomEllipseGetSize(
+                    file_handle,
+                    geom,
+                    &mut horizontal_axis,
+                    &mut vertical_axis,
+                ),
+                format!("Error reading geometry {} size.", geom),
+            )?;
+            GeometryShape::Ellipse {
+                horizontal_axis,
+                vertical_axis,
+            }
+        }
+        t => Err(ParseError::UnexpectedValue(t))?,
+    };
+
+    let mut geometry = Geometry::new(shape, anchor);
+    geometry.coord_sys = coord_sys;
+    geometry.scope = Scope::try_from(get!(tecGeomGetScope, 0))?;
+    if get!(tecGeomIsAttached, 0) != 0 {
+        geometry.zone = Some(get!(tecGeomGetZone, 0));
+    }
+    geometry.color = get!(tecGeomGetColor, 0);
+    if get!(tecGeomIsFilled, 0) != 0 {
+        geometry.fill_color = Some(get!(tecGeomGetFillColor, 0));
+    }
+    geometry.line_pattern = LinePattern::try_from(get!(tecGeomGetLinePattern, 0))?;
+    geometry.pattern_length = get!(tecGeomGetPatternLength, 0.0);
+    geometry.line_thickness = get!(tecGeomGetLineThickness, 0.0);
+    geometry.num_ellipse_points = get!(tecGeomEllipseGetNumPoints, 0);
+    geometry.arrowhead_style = ArrowheadStyle::try_from(get!(tecGeomArrowheadGetStyle, 0))?;
+    geometry.arrowhead_attachment =
+        ArrowheadAttachment::try_from(get!(tecGeomArrowheadGetAttach, 0))?;
+    geometry.arrowhead_size = get!(tecGeomArrowheadGetSize, 0.0);
+    geometry.arrowhead_angle = get!(tecGeomArrowheadGetAngle, 0.0);
+    geometry.clipping = Clipping::try_from(get!(tecGeomGetClipping, 0))?;
+    let macro_function = get!(tecGeomGetMacroFunctionCmd, null_mut());
+    geometry.macro_function = CString::from_raw(macro_function).into_string()?;
+    Ok(geometry)
+}
+
+unsafe fn read_text(file_handle: *mut c_void, text_id: i32) -> Result<TextAnnotation> {
+    macro_rules! get {
+        ($f: ident, $init: expr) => {{
+            let mut value = $init;
+            try_err(
+                bindings::$f(file_handle, text_id, &mut value),
+                format!("Error reading text {}, {}.", text_id, stringify!($f)),
+            )?;
+            value
+        }};
+    }
+    let mut anchor = [0.0; 3];
+    try_err(
+        bindings::tecTextGetAnchorPos(
+            file_handle,
+            text_id,
+            &mut anchor[0],
+            &mut anchor[1],
+            &mut anchor[2],
+        ),
+        format!("Error reading text {} position.", text_id),
+    )?;
+    let string = CString::from_raw(get!(tecTextGetString, null_mut())).into_string()?;
+
+    let mut text = TextAnnotation::new(string, anchor);
+    text.coord_sys = coord_sys(get!(tecTextGetCoordMode, 0))?;
+    text.scope = Scope::try_from(get!(tecTextGetScope, 0))?;
+    text.typeface = CString::from_raw(get!(tecTextGetTypeface, null_mut())).into_string()?;
+    text.bold = get!(tecTextIsBold, 0) != 0;
+    text.italic = get!(tecTextIsItalic, 0) != 0;
+    text.size_units = SizeUnits::try_from(get!(tecTextGetSizeUnits, 0))?;
+    text.height = get!(tecTextGetHeight, 0.0);
+    text.angle = get!(tecTextGetAngle, 0.0);
+    text.line_spacing = get!(tecTextGetLineSpacing, 0.0);
+    text.alignment = TextAnchor::try_from(get!(tecTextGetAnchor, 0))?;
+    text.box_type = TextBox::try_from(get!(tecTextBoxGetType, 0))?;
+    text.box_margin = get!(tecTextBoxGetMargin, 0.0);
+    text.box_line_thickness = get!(tecTextBoxGetLineThickness, 0.0);
+    text.box_color = get!(tecTextBoxGetColor, 0);
+    text.box_fill_color = get!(tecTextBoxGetFillColor, 0);
+    text.color = get!(tecTextGetColor, 0);
+    if get!(tecTextIsAttached, 0) != 0 {
+        text.zone = Some(get!(tecTextGetZone, 0));
+    }
+    text.clipping = Clipping::try_from(get!(tecTextGetClipping, 0))?;
+    let macro_function = get!(tecTextGetMacroFunctionCmd, null_mut());
+    text.macro_function = CString::from_raw(macro_function).into_string()?;
+    Ok(text)
+}
+
+/// Annotation positions are reported in the coordinate modes of TecUtil.
+fn coord_sys(mode: i32) -> Result<CoordSys> {
+    match mode {
+        0 => Ok(CoordSys::Grid),
+        1 => Ok(CoordSys::Frame),
+        4 | 6 => Ok(CoordSys::Grid3D),
+        mode => Err(ParseError::UnexpectedValue(mode).into()),
+    }
+}
+
+/// Collects `num_items` auxiliary name/value pairs returned by `get_item`.
+unsafe fn read_aux_data<F>(num_items: i32, mut get_item: F) -> Result<AuxData>
+where
+    F: FnMut(i32, *mut *mut c_char, *mut *mut c_char) -> i32,
+{
+    let mut aux_data = AuxData::new();
+    for item in 1..=num_items {
+        let mut name = null_mut();
+        let mut value = null_mut();
+        try_err(
+            get_item(item, &mut name, &mut value),
+            format!("Error reading aux data item {}.", item),
+        )?;
+        let name = CString::from_raw(name).into_string()?;
+        let value = CString::from_raw(value).into_string()?;
+        aux_data.insert(name, value);
+    }
+    Ok(aux_data)
 }
 
 impl Drop for SzpltFormat {
@@ -11,6 +11,6 @@ extern crate nom;
 extern crate libc;
 
 pub use common::*;
-pub use formats::{PltFormat, SzpltFormat, DatFormat };
+pub use formats::{DatFormat, DatZone, DatZones, PltFormat, SzlFormat, SzpltFormat};
 pub use reader::TecReader;
-pub use writer::{TecWriter, TecZoneWriter, WriterConfig};
+pub use writer::{DatWriter, DatWriterConfig, TecWriter, TecZoneWriter, WriterConfig};
@@ -9,10 +9,13 @@ use std::{
 
 use crate::{
     bindings,
-    common::{try_err, Dataset, OrderedZone, Result, TecDataType, TecZone, TecioError, ZoneType},
+    common::{
+        try_err, Dataset, OrderedZone, ParseError, PolyFaces, Result, TecDataType, TecZone, TecioError,
+        ZoneType,
+    },
     formats::DatFormat,
     reader::InnerReader::SzpltReader,
-    ClassicFEZone, PltFormat, SzpltFormat, TecData, ValueLocation,
+    ClassicFEZone, PltFormat, SzlFormat, SzpltFormat, TecData, ValueLocation,
 };
 
 pub struct TecReader {
@@ -22,6 +25,7 @@ pub struct TecReader {
 pub enum InnerReader {
     PltReader(PltFormat<'static>),
     SzpltReader(SzpltFormat),
+    SzlReader(SzlFormat<'static>),
     DatReader(DatFormat),
 }
 
@@ -29,8 +33,17 @@ impl TecReader {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
         let inner = match path.extension().map(|os| os.to_str().unwrap_or("")) {
-            Some("szplt")  | Some("szplt_geom")=> {
-                let path = path.to_str().unwrap();
+            Some("szplt") => match SzlFormat::open(path) {
+                Ok(szl) => InnerReader::SzlReader(szl),
+                // Zones the native reader can't decode are read through TecIO.
+                Err(e) if e.parse_error() == Some(ParseError::NotSupportedFeature) => {
+                    let path = path.to_str().ok_or(ParseError::Utf8Error)?;
+                    InnerReader::SzpltReader(SzpltFormat::open(path)?)
+                }
+                Err(e) => return Err(e),
+            },
+            Some("szplt_geom") => {
+                let path = path.to_str().ok_or(ParseError::Utf8Error)?;
                 InnerReader::SzpltReader(SzpltFormat::open(path)?)
             }
             Some("plt") => InnerReader::PltReader(PltFormat::open(path)?),
@@ -51,6 +64,7 @@ impl TecReader {
     pub fn dataset(&self) -> &Dataset {
         match &self.inner {
             InnerReader::SzpltReader(szplt) => &szplt.dataset,
+            InnerReader::SzlReader(szl) => &szl.dataset,
             InnerReader::PltReader(plt) => &plt.dataset,
             InnerReader::DatReader(dat) => &dat.dataset,
         }
@@ -59,6 +73,7 @@ impl TecReader {
     pub fn zones(&self) -> &[TecZone] {
         match &self.inner {
             InnerReader::SzpltReader(szplt) => &szplt.zones,
+            InnerReader::SzlReader(szl) => &szl.zones,
             InnerReader::PltReader(plt) => &plt.zones,
             InnerReader::DatReader(dat) => &dat.zones,
         }
@@ -67,37 +82,144 @@ impl TecReader {
     pub fn get_data(&self, zone_id: usize, var_id: usize) -> Result<TecData> {
         match &self.inner {
             InnerReader::SzpltReader(szplt) => szplt.get_data(zone_id, var_id),
-            InnerReader::PltReader(plt) => Ok(plt.data_blocks[zone_id - 1].get_data(var_id - 1)),
-            InnerReader::DatReader(dat) => Ok(dat.data_blocks[zone_id - 1].get_data(var_id - 1)),
+            InnerReader::SzlReader(szl) => szl.get_data(zone_id, var_id),
+            InnerReader::PltReader(plt) => plt.get_data(zone_id, var_id),
+            InnerReader::DatReader(dat) => Ok(dat.get_data(zone_id, var_id)),
         }
     }
     pub fn get_var_min_max(&self, zone_id: usize, var_id: usize) -> Option<(f64, f64)> {
         match &self.inner {
             InnerReader::SzpltReader(szplt) => None,
-            InnerReader::PltReader(plt) => Some(plt.data_blocks[zone_id - 1].min_max[var_id - 1]),
+            InnerReader::SzlReader(szl) => Some(szl.get_var_min_max(zone_id, var_id)),
+            InnerReader::PltReader(plt) => Some(plt.get_var_min_max(zone_id, var_id)),
             InnerReader::DatReader(dat) => None,
         }
     }
 
+    /// Node map of a FE zone with one-based node indices.
     pub fn get_connectivity(&self, zone_id: usize) -> Result<Option<TecData>> {
         match &self.inner {
             InnerReader::SzpltReader(szplt) => szplt.get_connectivity(zone_id as _),
-            InnerReader::PltReader(plt) => Ok(plt.data_blocks[zone_id - 1]
-                .connectivity
-                .as_ref()
-                .map(|c| c.get())),
-            InnerReader::DatReader(dat) => Ok(dat.data_blocks[zone_id - 1]
-                .connectivity
-                .as_ref()
-                .map(|c| c.get())),
+            InnerReader::SzlReader(szl) => szl.get_connectivity(zone_id),
+            InnerReader::PltReader(plt) => plt.get_connectivity(zone_id),
+            InnerReader::DatReader(dat) => Ok(dat.get_connectivity(zone_id)),
+        }
+    }
+
+    /// Face connectivity of a polygonal or polyhedral zone, `None` for other zones.
+    pub fn get_poly_faces(&self, zone_id: usize) -> Result<Option<PolyFaces>> {
+        match &self.inner {
+            InnerReader::SzpltReader(szplt) => szplt.get_poly_faces(zone_id as _),
+            InnerReader::SzlReader(_) => Ok(None),
+            InnerReader::PltReader(plt) => plt.get_poly_faces(zone_id),
+            InnerReader::DatReader(dat) => Ok(dat.get_poly_faces(zone_id)),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{TecReader, TecioError};
+    use crate::{tests::temp_path, TecReader, TecioError};
     use std::borrow::Borrow;
+
+    #[test]
+    fn partitioned_fe_szplt() -> Result<(), TecioError> {
+        use crate::{
+            AuxData, ClassicFEZone, TecWriter, TecZone, ValueLocation, WriterConfig, ZoneType,
+        };
+
+        // Large enough for TecIO to split the zone into several subzones.
+        let n = 40;
+        let x = (0..n * n).map(|i| (i % n) as f32).collect::<Vec<_>>();
+        let y = (0..n * n).map(|i| (i / n) as f32).collect::<Vec<_>>();
+        let nodemap = (0..n - 1)
+            .flat_map(|j| (0..n - 1).map(move |i| i + j * n))
+            .flat_map(|c| vec![c, c + 1, c + n, c + 1, c + n + 1, c + n])
+            .collect::<Vec<i32>>();
+        let path = temp_path("partitioned_fe.szplt");
+        let config = WriterConfig::default();
+        let mut writer = TecWriter::create(path.to_str().unwrap(), "FE", "X Y", 2, &config)?;
+        let mut zone = writer.add_zone(TecZone::ClassicFE(ClassicFEZone {
+            name: "Triangles".to_string(),
+            zone_type: ZoneType::FETriangle,
+            id: 1,
+            solution_time: 0.0,
+            strand: 0,
+            parent_zone: 0,
+            nodes: x.len() as i64,
+            cells: (nodemap.len() / 3) as i64,
+            var_location: vec![ValueLocation::Nodal; 2],
+            var_types: None,
+            var_share_list: vec![],
+            passive_var_list: vec![],
+            connectivity_share_zone: 0,
+            aux_data: AuxData::new(),
+        }))?;
+        zone.write_data(1, x.clone())?;
+        zone.write_data(2, y.clone())?;
+        zone.write_nodemap(nodemap.clone(), false)?;
+        writer.close()?;
+
+        let reader = TecReader::open(&path)?;
+        assert_eq!(reader.get_data(1, 1)?.as_f32(), x);
+        assert_eq!(reader.get_data(1, 2)?.as_f32(), y);
+        let connectivity = reader.get_connectivity(1)?.unwrap().to_vec::<i32>()?;
+        assert_eq!(connectivity, nodemap.iter().map(|n| n + 1).collect::<Vec<_>>());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+    #[test]
+    fn szplt_records() -> Result<(), TecioError> {
+        use crate::{
+            Geometry, GeometryShape, OrderedZone, TecWriter, TecZone, TextAnnotation,
+            ValueLocation, WriterConfig,
+        };
+
+        let shape = GeometryShape::Rectangle { width: 1.0, height: 2.0 };
+        let rectangle = Geometry::new(shape, [0.5; 3]);
+        let caption = TextAnnotation::new("Caption", [10.0, 90.0, 0.0]);
+        let path = temp_path("szplt_records.szplt");
+        let config = WriterConfig::default();
+        let mut writer = TecWriter::create(path.to_str().unwrap(), "Records", "X", 1, &config)?;
+        let mut zone = writer.add_zone(TecZone::Ordered(OrderedZone {
+            name: "Line".to_string(),
+            id: 1,
+            solution_time: 0.0,
+            strand: 0,
+            parent_zone: 0,
+            i_max: 2,
+            j_max: 1,
+            k_max: 1,
+            var_location: vec![ValueLocation::Nodal],
+            var_types: None,
+            var_share_list: vec![],
+            passive_var_list: vec![],
+            aux_data: vec![("Solver".to_string(), "implicit".to_string())]
+                .into_iter()
+                .collect(),
+        }))?;
+        zone.write_data(1, vec![0.0f32, 1.0])?;
+        writer.add_dataset_aux_data("Mach", "0.8")?;
+        writer.add_var_aux_data(1, "Unit", "m")?;
+        writer.add_geometry(&rectangle)?;
+        writer.add_text(&caption)?;
+        writer.add_custom_labels(&["Low", "High"])?;
+        writer.add_user_record("Generated by report")?;
+        writer.close()?;
+
+        let reader = TecReader::open(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(reader.dataset().aux_data["Mach"], "0.8");
+        assert_eq!(reader.dataset().var_aux_data(1).unwrap()["Unit"], "m");
+        assert_eq!(reader.zones()[0].aux_data()["Solver"], "implicit");
+        assert_eq!(reader.dataset().geometries, vec![rectangle]);
+        assert_eq!(reader.dataset().texts, vec![caption]);
+        assert_eq!(reader.dataset().custom_labels, vec![vec!["Low", "High"]]);
+        assert_eq!(reader.dataset().user_records, vec!["Generated by report"]);
+        assert_eq!(reader.get_data(1, 1)?.as_f32(), vec![0.0, 1.0]);
+        Ok(())
+    }
+
     #[test]
     fn test_plt() -> Result<(), TecioError> {
         let plt = TecReader::open("./tests/heated_fin.plt")?;
@@ -1,6 +1,12 @@
 use crate::*;
+use std::path::PathBuf;
 // TODO REWRITE TESTS
 
+/// Unique path in the temporary directory, so tests don't write into the source tree.
+pub(crate) fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tecio-{}-{}", std::process::id(), name))
+}
+
 
 #[test]
 fn test_wrong_filename() {
@@ -1,16 +1,30 @@
-use crate::common::{FileFormat, FileType, Result, TecioError, ZoneType};
-use crate::{bindings, try_err, FaceNeighborMode, TecData, TecDataType, TecZone};
+use crate::common::{
+    quote_labels, AuxData, CoordSys, FileFormat, FileType, Geometry, GeometryShape, Result,
+    TecioError, TextAnnotation, ZoneType,
+};
+use crate::{bindings, try_err, ClassicFEZone, FaceNeighborMode, ParseError, ValueLocation, TecData, TecDataType, TecZone};
 use libc::c_char;
 use std::convert::From;
 use std::ffi::{c_void, CStr, CString, OsStr};
 use std::fmt::Error;
 use std::ptr::{null, null_mut};
 
+mod dat;
+mod plt;
+
+pub use dat::{DatWriter, DatWriterConfig};
+use plt::PltWriter;
+
 pub struct TecWriter {
-    file_handle: *mut c_void,
+    inner: InnerWriter,
     num_vars: usize,
 }
 
+enum InnerWriter {
+    Tecio(*mut c_void),
+    Plt(Box<PltWriter>),
+}
+
 unsafe impl Send for TecWriter {}
 
 pub struct WriterConfig {
@@ -57,6 +71,41 @@ impl TecWriter {
         U: AsRef<[u8]>,
         V: AsRef<[u8]>,
     {
+        if let FileFormat::Binary = config.file_format {
+            let path = std::str::from_utf8(file.as_ref())
+                .map_err(|_| TecioError::ParseError(ParseError::Utf8Error))?;
+            let title = String::from_utf8(dataset_title.as_ref().to_vec())
+                .map_err(|_| TecioError::ParseError(ParseError::Utf8Error))?;
+            let var_names = std::str::from_utf8(var_list.as_ref())
+                .map_err(|_| TecioError::ParseError(ParseError::Utf8Error))?
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_owned())
+                .collect::<Vec<_>>();
+            if var_names.len() != num_vars {
+                return Err(TecioError::Other {
+                    message: format!(
+                        "Expected {} variables, var list contains {}",
+                        num_vars,
+                        var_names.len()
+                    ),
+                    code: -1,
+                });
+            }
+            let file_type = match config.file_type {
+                FileType::SolutionOnly(_) => FileType::SolutionOnly(null_mut()),
+                x => x,
+            };
+            let file = std::fs::File::create(path)?;
+
+            return Ok(Self {
+                inner: InnerWriter::Plt(Box::new(PltWriter::create(
+                    file, file_type, title, var_names,
+                ))),
+                num_vars,
+            });
+        }
+
         let cname = CString::new(file.as_ref())?;
         let dataset_title = CString::new(dataset_title.as_ref())?;
         let var_list = CString::new(var_list.as_ref())?;
@@ -87,15 +136,7 @@ impl TecWriter {
                         &mut file_handle,
                     ),
                 },
-                FileFormat::Binary => {
-                    return Err(TecioError::Other {
-                        message: format!(
-                            "Unsupported file format {:?}! Supported types: [Subzone]",
-                            config.file_format
-                        ),
-                        code: -1,
-                    });
-                }
+                FileFormat::Binary => unreachable!(),
             }
         };
 
@@ -116,22 +157,33 @@ impl TecWriter {
         }
 
         Ok(Self {
-            file_handle,
+            inner: InnerWriter::Tecio(file_handle),
             num_vars,
         })
     }
 
     pub fn add_zone(&mut self, zone: TecZone) -> Result<TecZoneWriter> {
+        if let InnerWriter::Plt(writer) = &mut self.inner {
+            let id = writer.add_zone(zone.clone())?;
+            return Ok(TecZoneWriter {
+                writer: self,
+                zone,
+                id,
+            });
+        }
+        // TecIO reads an entry per variable from every list it is given.
+        check_var_lists(&zone, self.num_vars)?;
         match zone {
             TecZone::Ordered(zone) => {
                 let zone_title = CString::new(zone.name.clone()).unwrap();
                 let mut id = -1;
-                let array_of_nulls = vec![0; self.num_vars];
+                let share_var_list = share_list(&zone.var_share_list, self.num_vars);
+                let passive_var_list = share_list(&zone.passive_var_list, self.num_vars);
 
                 try_err(
                     unsafe {
                         bindings::tecZoneCreateIJK(
-                            self.file_handle,
+                            self.tecio_handle()?,
                             zone_title.as_ptr(),
                             zone.i_max,
                             zone.j_max,
@@ -140,9 +192,9 @@ impl TecWriter {
                                 .as_ref()
                                 .map(|v| v.as_ptr() as *const _)
                                 .unwrap_or(null()),
-                            array_of_nulls.as_ptr(),
+                            share_var_list.as_ptr(),
                             zone.var_location.as_ptr() as *const _,
-                            zone.passive_var_list.as_ptr(),
+                            passive_var_list.as_ptr(),
                             0,
                             0,
                             FaceNeighborMode::GlobalOneToMany as i32,
@@ -152,6 +204,8 @@ impl TecWriter {
                     format!("Error creating zone with parameters: {:?}", zone),
                 )?;
 
+                self.set_parent_zone(id, zone.parent_zone)?;
+                self.add_zone_aux_data(id, &zone.aux_data)?;
                 Ok(TecZoneWriter {
                     writer: self,
                     zone: TecZone::Ordered(zone),
@@ -161,11 +215,12 @@ impl TecWriter {
             TecZone::ClassicFE(zone) => {
                 let zone_title = CString::new(zone.name.clone()).unwrap();
                 let mut id = -1;
-                let array_of_nulls = vec![0; self.num_vars];
+                let share_var_list = share_list(&zone.var_share_list, self.num_vars);
+                let passive_var_list = share_list(&zone.passive_var_list, self.num_vars);
                 try_err(
                     unsafe {
                         bindings::tecZoneCreateFE(
-                            self.file_handle,
+                            self.tecio_handle()?,
                             zone_title.as_ptr(),
                             zone.zone_type as _,
                             zone.nodes,
@@ -174,10 +229,10 @@ impl TecWriter {
                                 .as_ref()
                                 .map(|v| v.as_ptr() as *const _)
                                 .unwrap_or(null()),
-                            array_of_nulls.as_ptr(),
+                            share_var_list.as_ptr(),
                             zone.var_location.as_ptr() as *const _,
-                            array_of_nulls.as_ptr(),
-                            0,
+                            passive_var_list.as_ptr(),
+                            zone.connectivity_share_zone,
                             0,
                             0,
                             &mut id,
@@ -185,20 +240,335 @@ impl TecWriter {
                     },
                     format!("Error creating zone with parameters: {:?}", zone),
                 )?;
+                self.set_parent_zone(id, zone.parent_zone)?;
+                self.add_zone_aux_data(id, &zone.aux_data)?;
                 Ok(TecZoneWriter {
                     writer: self,
                     zone: TecZone::ClassicFE(zone),
                     id,
                 })
             }
-            _ => unimplemented!(),
+            TecZone::PolyFE(zone) => {
+                let zone_title = CString::new(zone.name.clone()).unwrap();
+                let mut id = -1;
+                let share_var_list = share_list(&zone.var_share_list, self.num_vars);
+                let passive_var_list = share_list(&zone.passive_var_list, self.num_vars);
+                try_err(
+                    unsafe {
+                        bindings::tecZoneCreatePoly(
+                            self.tecio_handle()?,
+                            zone_title.as_ptr(),
+                            zone.zone_type as _,
+                            zone.nodes,
+                            zone.faces,
+                            zone.cells,
+                            zone.total_num_face_nodes,
+                            zone.var_types
+                                .as_ref()
+                                .map(|v| v.as_ptr() as *const _)
+                                .unwrap_or(null()),
+                            share_var_list.as_ptr(),
+                            zone.var_location.as_ptr() as *const _,
+                            passive_var_list.as_ptr(),
+                            zone.connectivity_share_zone,
+                            zone.num_connected_boundary_faces,
+                            zone.total_num_boundary_connections,
+                            &mut id,
+                        )
+                    },
+                    format!("Error creating zone with parameters: {:?}", zone),
+                )?;
+                self.set_parent_zone(id, zone.parent_zone)?;
+                self.add_zone_aux_data(id, &zone.aux_data)?;
+                Ok(TecZoneWriter {
+                    writer: self,
+                    zone: TecZone::PolyFE(zone),
+                    id,
+                })
+            }
+        }
+    }
+
+    pub fn add_dataset_aux_data<N, V>(&mut self, name: N, value: V) -> Result<()>
+    where
+        N: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        if let InnerWriter::Plt(writer) = &mut self.inner {
+            writer.add_dataset_aux_data(utf8(name.as_ref())?, utf8(value.as_ref())?);
+            return Ok(());
+        }
+        let (name, value) = (CString::new(name.as_ref())?, CString::new(value.as_ref())?);
+        let handle = self.tecio_handle()?;
+        try_err(
+            unsafe { bindings::tecDataSetAddAuxData(handle, name.as_ptr(), value.as_ptr()) },
+            format!("Error adding dataset aux data {:?}", name),
+        )
+    }
+
+    pub fn add_var_aux_data<N, V>(&mut self, var: i32, name: N, value: V) -> Result<()>
+    where
+        N: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        if let InnerWriter::Plt(writer) = &mut self.inner {
+            return writer.add_var_aux_data(var, utf8(name.as_ref())?, utf8(value.as_ref())?);
+        }
+        let (name, value) = (CString::new(name.as_ref())?, CString::new(value.as_ref())?);
+        let handle = self.tecio_handle()?;
+        try_err(
+            unsafe { bindings::tecVarAddAuxData(handle, var, name.as_ptr(), value.as_ptr()) },
+            format!("Error adding aux data {:?} to var {}", name, var),
+        )
+    }
+
+    pub fn add_geometry(&mut self, geometry: &Geometry) -> Result<()> {
+        check_geometry(geometry)?;
+        if let InnerWriter::Plt(writer) = &mut self.inner {
+            writer.add_geometry(geometry.clone());
+            return Ok(());
+        }
+
+        let handle = self.tecio_handle()?;
+        let macro_function = CString::new(geometry.macro_function.as_str())?;
+        let [x, y, z] = geometry.anchor;
+        let mode = geometry.coord_sys as i32;
+        let er = unsafe {
+            match &geometry.shape {
+                GeometryShape::Lines(lines) => {
+                    let counts = lines.iter().map(|l| l.x.len() as i32).collect::<Vec<_>>();
+                    let xs = lines.iter().flat_map(|l| l.x.iter().copied()).collect::<Vec<_>>();
+                    let ys = lines.iter().flat_map(|l| l.y.iter().copied()).collect::<Vec<_>>();
+                    if geometry.coord_sys == CoordSys::Grid3D {
+                        let zs = lines.iter().flat_map(|l| l.z.iter().copied()).collect::<Vec<_>>();
+                        bindings::tecGeom3DMultiLineSegmentsBegin(
+                            handle,
+                            x,
+                            y,
+                            z,
+                            lines.len() as i32,
+                            counts.as_ptr(),
+                            xs.as_ptr(),
+                            ys.as_ptr(),
+                            zs.as_ptr(),
+                        )
+                    } else {
+                        bindings::tecGeom2DMultiLineSegmentsBegin(
+                            handle,
+                            x,
+                            y,
+                            lines.len() as i32,
+                            counts.as_ptr(),
+                            xs.as_ptr(),
+                            ys.as_ptr(),
+                            mode,
+                        )
+                    }
+                }
+                GeometryShape::Rectangle { width, height } => {
+                    bindings::tecGeomRectangleBegin(handle, x, y, x + width, y + height, mode)
+                }
+                GeometryShape::Square { size } => bindings::tecGeomSquareBegin(handle, x, y, *size, mode),
+                GeometryShape::Circle { radius } => {
+                    bindings::tecGeomCircleBegin(handle, x, y, *radius, mode)
+                }
+                GeometryShape::Ellipse {
+                    horizontal_axis,
+                    vertical_axis,
+                } => bindings::tecGeomEllipseBegin(
+                    handle,
+                    x,
+                    y,
+                    *horizontal_axis,
+                    *vertical_axis,
+                    mode,
+                ),
+            }
+        };
+        try_err(er, format!("Error adding geometry {:?}", geometry.shape))?;
+
+        let set = |er, what| try_err(er, format!("Error setting geometry {}", what));
+        // The geometry has to be ended even if setting one of its attributes failed.
+        let attributes = (|| unsafe {
+            set(
+                bindings::tecGeomSetLineInfo(
+                    handle,
+                    geometry.line_pattern as i32,
+                    geometry.pattern_length,
+                    geometry.line_thickness,
+                    geometry.color,
+                ),
+                "line info",
+            )?;
+            set(bindings::tecGeomSetScope(handle, geometry.scope as i32), "scope")?;
+            if let Some(zone) = geometry.zone {
+                set(bindings::tecGeomAttachToZone(handle, zone), "zone")?;
+            }
+            if let Some(fill_color) = geometry.fill_color {
+                set(bindings::tecGeomFill(handle, fill_color), "fill color")?;
+            }
+            set(bindings::tecGeomSetClipping(handle, geometry.clipping as i32), "clipping")?;
+            set(
+                bindings::tecGeomArrowheadSetInfo(
+                    handle,
+                    geometry.arrowhead_angle,
+                    geometry.arrowhead_attachment as i32,
+                    geometry.arrowhead_size,
+                    geometry.arrowhead_style as i32,
+                ),
+                "arrowhead",
+            )?;
+            if let GeometryShape::Circle { .. } | GeometryShape::Ellipse { .. } = geometry.shape {
+                set(
+                    bindings::tecGeomEllipseSetNumPoints(handle, geometry.num_ellipse_points),
+                    "ellipse points",
+                )?;
+            }
+            if !geometry.macro_function.is_empty() {
+                set(
+                    bindings::tecGeomSetMacroFunctionCmd(handle, macro_function.as_ptr()),
+                    "macro function",
+                )?;
+            }
+            Ok(())
+        })();
+        let end = set(unsafe { bindings::tecGeomEnd(handle) }, "end");
+        attributes.and(end)
+    }
+
+    pub fn add_text(&mut self, text: &TextAnnotation) -> Result<()> {
+        if let InnerWriter::Plt(writer) = &mut self.inner {
+            writer.add_text(text.clone());
+            return Ok(());
+        }
+
+        let handle = self.tecio_handle()?;
+        let string = CString::new(text.text.as_str())?;
+        let typeface = CString::new(text.typeface.as_str())?;
+        let macro_function = CString::new(text.macro_function.as_str())?;
+        let [x, y, z] = text.anchor;
+        let units = text.size_units as i32;
+        let set = |er, what| try_err(er, format!("Error setting text {}", what));
+        unsafe {
+            let er = if text.coord_sys == CoordSys::Grid3D {
+                bindings::tecText3DBegin(handle, string.as_ptr(), x, y, z, text.height, units)
+            } else {
+                let mode = text.coord_sys as i32;
+                bindings::tecText2DBegin(handle, string.as_ptr(), x, y, mode, text.height, units)
+            };
+            try_err(er, format!("Error adding text {:?}", text.text))?;
+        }
+        // The text has to be ended even if setting one of its attributes failed.
+        let attributes = (|| unsafe {
+            set(
+                bindings::tecTextSetTypeface(
+                    handle,
+                    typeface.as_ptr(),
+                    text.bold as i32,
+                    text.italic as i32,
+                ),
+                "typeface",
+            )?;
+            set(
+                bindings::tecTextBoxSetInfo(
+                    handle,
+                    text.box_type as i32,
+                    text.box_color,
+                    text.box_fill_color,
+                    text.box_line_thickness,
+                    text.box_margin,
+                ),
+                "box",
+            )?;
+            set(bindings::tecTextSetAnchor(handle, text.alignment as i32), "anchor")?;
+            set(bindings::tecTextSetAngle(handle, text.angle), "angle")?;
+            set(bindings::tecTextSetLineSpacing(handle, text.line_spacing), "line spacing")?;
+            set(bindings::tecTextSetColor(handle, text.color), "color")?;
+            set(bindings::tecTextSetScope(handle, text.scope as i32), "scope")?;
+            set(bindings::tecTextSetClipping(handle, text.clipping as i32), "clipping")?;
+            if let Some(zone) = text.zone {
+                set(bindings::tecTextAttachToZone(handle, zone), "zone")?;
+            }
+            if !text.macro_function.is_empty() {
+                set(
+                    bindings::tecTextSetMacroFunctionCmd(handle, macro_function.as_ptr()),
+                    "macro function",
+                )?;
+            }
+            Ok(())
+        })();
+        let end = set(unsafe { bindings::tecTextEnd(handle) }, "end");
+        attributes.and(end)
+    }
+
+    /// Adds a set of custom labels, e.g. the names shown in a contour legend.
+    pub fn add_custom_labels<S: AsRef<str>>(&mut self, labels: &[S]) -> Result<()> {
+        if let InnerWriter::Plt(writer) = &mut self.inner {
+            writer.add_custom_labels(labels.iter().map(|l| l.as_ref().to_owned()).collect());
+            return Ok(());
+        }
+        let labels = CString::new(quote_labels(labels))?;
+        try_err(
+            unsafe { bindings::tecCustomLabelsAddSet(self.tecio_handle()?, labels.as_ptr()) },
+            format!("Error adding custom labels {:?}", labels),
+        )
+    }
+
+    pub fn add_user_record<R: AsRef<[u8]>>(&mut self, record: R) -> Result<()> {
+        if let InnerWriter::Plt(writer) = &mut self.inner {
+            writer.add_user_record(utf8(record.as_ref())?);
+            return Ok(());
+        }
+        let record = CString::new(record.as_ref())?;
+        try_err(
+            unsafe { bindings::tecUserRecAdd(self.tecio_handle()?, record.as_ptr()) },
+            format!("Error adding user record {:?}", record),
+        )
+    }
+
+    fn set_parent_zone(&mut self, zone: i32, parent: i32) -> Result<()> {
+        if parent <= 0 {
+            return Ok(());
         }
+        try_err(
+            unsafe { bindings::tecZoneSetParentZone(self.tecio_handle()?, zone, parent) },
+            format!("Error setting parent zone #{} of zone #{}", parent, zone),
+        )
+    }
+
+    fn add_zone_aux_data(&mut self, zone: i32, aux_data: &AuxData) -> Result<()> {
+        let handle = self.tecio_handle()?;
+        for (name, value) in aux_data.iter() {
+            let (name, value) = (CString::new(name.as_str())?, CString::new(value.as_str())?);
+            try_err(
+                unsafe { bindings::tecZoneAddAuxData(handle, zone, name.as_ptr(), value.as_ptr()) },
+                format!("Error adding aux data {:?} to zone #{}", name, zone),
+            )?;
+        }
+        Ok(())
     }
 
+    /// TecIO file handle, null for the native binary writer.
     pub fn handler(&self) -> *mut c_void {
-        self.file_handle
+        self.file_handle().unwrap_or(null_mut())
+    }
+
+    /// TecIO file handle, `None` for the native binary writer.
+    pub fn file_handle(&self) -> Option<*mut c_void> {
+        match self.inner {
+            InnerWriter::Tecio(file_handle) => Some(file_handle),
+            InnerWriter::Plt(_) => None,
+        }
+    }
+
+    fn tecio_handle(&self) -> Result<*mut c_void> {
+        self.file_handle().ok_or_else(|| TecioError::Other {
+            message: "The binary writer has no TecIO file handle".to_owned(),
+            code: -1,
+        })
     }
 
+    /// Adds a classic FE zone with nodal `f32` variables and returns its id.
     pub fn add_fe_zone<T>(
         &mut self,
         title: T,
@@ -211,56 +581,137 @@ impl TecWriter {
     where
         T: AsRef<[u8]>,
     {
-        let title = CString::new(title.as_ref())?;
-        let mut zone = 0;
-
-        let var_types = (0..self.num_vars).map(|_| 1).collect::<Vec<_>>();
-        let var_share = (0..self.num_vars).map(|_| 0).collect::<Vec<_>>();
-        let passive_var_list = (0..self.num_vars).map(|_| 0).collect::<Vec<_>>();
-        let value_locs = (0..self.num_vars).map(|_| 1).collect::<Vec<_>>();
+        self.add_fe_zone_writer(title, zone_type, nodes, cells, time, strand_id)
+            .map(|zone| zone.id)
+    }
 
-        let mut er = unsafe {
-            bindings::tecZoneCreateFE(
-                self.file_handle,
-                title.as_ptr(),
-                zone_type as i32,
-                nodes,
-                cells,
-                var_types.as_ptr(),
-                var_share.as_ptr(),
-                value_locs.as_ptr(),
-                passive_var_list.as_ptr(),
-                0,
-                0,
-                0,
-                &mut zone as *mut i32,
-            )
-        };
-        if er != 0 {
-            return Err(TecioError::Other {
-                message: "Error creating zone.".to_owned(),
-                code: er,
-            });
-        }
-        er =
-            unsafe { bindings::tecZoneSetUnsteadyOptions(self.file_handle, zone, time, strand_id) };
-        if er != 0 {
-            return Err(TecioError::Other {
-                message: "Error setting zone's unsteady options.".to_owned(),
-                code: er,
-            });
+    /// Adds a classic FE zone with nodal `f32` variables and returns its writer.
+    pub fn add_fe_zone_writer<T>(
+        &mut self,
+        title: T,
+        zone_type: ZoneType,
+        nodes: i64,
+        cells: i64,
+        time: f64,
+        strand_id: i32,
+    ) -> Result<TecZoneWriter<'_>>
+    where
+        T: AsRef<[u8]>,
+    {
+        let zone = TecZone::ClassicFE(ClassicFEZone {
+            name: utf8(title.as_ref())?,
+            zone_type,
+            id: 0,
+            solution_time: time,
+            strand: strand_id,
+            parent_zone: 0,
+            nodes,
+            cells,
+            var_location: vec![ValueLocation::Nodal; self.num_vars],
+            var_types: Some(vec![TecDataType::F32; self.num_vars]),
+            var_share_list: vec![],
+            passive_var_list: vec![],
+            connectivity_share_zone: 0,
+            aux_data: AuxData::new(),
+        });
+        let zone_writer = self.add_zone(zone)?;
+        if let InnerWriter::Tecio(file_handle) = zone_writer.writer.inner {
+            let id = zone_writer.id;
+            try_err(
+                unsafe { bindings::tecZoneSetUnsteadyOptions(file_handle, id, time, strand_id) },
+                "Error setting zone's unsteady options.",
+            )?;
         }
+        Ok(zone_writer)
+    }
 
-        Ok(zone)
+    /// Finishes and closes the file, reporting any error the drop would have to ignore.
+    pub fn close(mut self) -> Result<()> {
+        self.close_file()
+    }
+
+    fn close_file(&mut self) -> Result<()> {
+        match &mut self.inner {
+            InnerWriter::Tecio(file_handle) => {
+                if file_handle.is_null() {
+                    return Ok(());
+                }
+                let er = unsafe { bindings::tecFileWriterClose(file_handle) };
+                *file_handle = null_mut();
+                try_err(er, "Error closing tecplot file")
+            }
+            InnerWriter::Plt(writer) => writer.close(),
+        }
     }
 }
 
 impl Drop for TecWriter {
+    /// Best-effort close, use [`TecWriter::close`] to see errors.
     fn drop(&mut self) {
-        let er = unsafe { bindings::tecFileWriterClose(&mut self.file_handle) };
-        if er != 0 {
-            panic!("Error closing tecplot File!");
-        }
+        let _ = self.close_file();
+    }
+}
+
+/// Polylines need matching coordinate counts, with z coordinates only in 3D.
+fn check_geometry(geometry: &Geometry) -> Result<()> {
+    let is_3d = geometry.coord_sys == CoordSys::Grid3D;
+    let valid = match &geometry.shape {
+        GeometryShape::Lines(lines) => lines.iter().all(|l| {
+            l.y.len() == l.x.len() && l.z.len() == if is_3d { l.x.len() } else { 0 }
+        }),
+        _ => !is_3d,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(TecioError::Other {
+            message: format!("Invalid {:?} geometry {:?}", geometry.coord_sys, geometry.shape),
+            code: -1,
+        })
+    }
+}
+
+fn utf8(bytes: &[u8]) -> Result<String> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| TecioError::ParseError(ParseError::Utf8Error))
+}
+
+/// Sum of face node or boundary connection counts, `None` if a count is negative.
+fn total_count(counts: &[i32]) -> Option<i64> {
+    counts.iter().try_fold(0i64, |sum, &c| if c < 0 { None } else { Some(sum + c as i64) })
+}
+
+/// Variable locations and types need an entry per variable, so do share and passive lists
+/// unless they are empty.
+fn check_var_lists(zone: &TecZone, num_vars: usize) -> Result<()> {
+    let share_list = match zone {
+        TecZone::Ordered(z) => &z.var_share_list,
+        TecZone::ClassicFE(z) => &z.var_share_list,
+        TecZone::PolyFE(z) => &z.var_share_list,
+    };
+    let lists = [
+        ("var locations", zone.var_locs().len(), false),
+        ("var types", zone.data_types().map_or(num_vars, |t| t.len()), false),
+        ("shared vars", share_list.len(), true),
+        ("passive vars", zone.passive_var_list().len(), true),
+    ];
+    match lists
+        .iter()
+        .find(|&&(_, len, optional)| len != num_vars && !(optional && len == 0))
+    {
+        Some((what, len, _)) => Err(TecioError::Other {
+            message: format!("Zone {:?} has {} {}, expected {}", zone.name(), len, what, num_vars),
+            code: -1,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// An empty share or passive list means that no variables are shared or passive.
+fn share_list(list: &[i32], num_vars: usize) -> Vec<i32> {
+    if list.is_empty() {
+        vec![0; num_vars]
+    } else {
+        list.to_vec()
     }
 }
 
@@ -272,59 +723,61 @@ pub struct TecZoneWriter<'a> {
 
 impl<'a> TecZoneWriter<'a> {
     pub fn write_data<'d, T: Into<TecData<'d>>>(&mut self, var: i32, data: T) -> Result<()> {
-        match data.into() {
-            TecData::F32(data) => {
-                try_err(
-                    unsafe {
-                        bindings::tecZoneVarWriteFloatValues(
-                            self.writer.handler(),
-                            self.id,
-                            var,
-                            0,
-                            data.len() as i64,
-                            data.as_ptr(),
-                        )
-                    },
-                    format!(
-                        "Error writing to zone {}, var {}, data {:?}",
-                        self.id, var, data
-                    ),
-                )?;
-
-                Ok(())
-            }
-            TecData::F64(data) => {
+        if let InnerWriter::Plt(writer) = &mut self.writer.inner {
+            return writer.write_data(self.id, var, data.into());
+        }
+        macro_rules! write {
+            ($write: ident, $data: expr) => {
                 try_err(
                     unsafe {
-                        bindings::tecZoneVarWriteDoubleValues(
-                            self.writer.handler(),
+                        bindings::$write(
+                            self.writer.tecio_handle()?,
                             self.id,
                             var,
                             0,
-                            data.len() as i64,
-                            data.as_ptr(),
+                            $data.len() as i64,
+                            $data.as_ptr() as _,
                         )
                     },
                     format!(
                         "Error writing to zone {}, var {}, data {:?}",
-                        self.id, var, data
+                        self.id, var, $data
                     ),
-                )?;
-
-                Ok(())
-            }
-            _ => unimplemented!(),
+                )
+            };
+        }
+        match data.into() {
+            TecData::F32(data) => write!(tecZoneVarWriteFloatValues, data),
+            TecData::F64(data) => write!(tecZoneVarWriteDoubleValues, data),
+            TecData::I32(data) => write!(tecZoneVarWriteInt32Values, data),
+            TecData::I16(data) => write!(tecZoneVarWriteInt16Values, data),
+            // Bytes are written unsigned, bit variables take the same call.
+            TecData::I8(data) => write!(tecZoneVarWriteUInt8Values, data),
+            data => Err(TecioError::Other {
+                message: format!(
+                    "Error writing to zone {}, var {}: {} values have no Tecplot data type",
+                    self.id,
+                    var,
+                    data.data_type_name()
+                ),
+                code: -1,
+            }),
         }
     }
 
     pub fn write_nodemap<'b, T: Into<TecData<'b>>>(&mut self, nodemap: T, one_based: bool) -> Result<()> {
         match self.zone {
+            TecZone::ClassicFE(_) if matches!(self.writer.inner, InnerWriter::Plt(_)) => {
+                if let InnerWriter::Plt(writer) = &mut self.writer.inner {
+                    writer.write_nodemap(self.id, nodemap.into(), one_based)?;
+                }
+            }
             TecZone::ClassicFE(_) => match nodemap.into() {
                 TecData::I32(data) => {
                     try_err(
                         unsafe {
                             bindings::tecZoneNodeMapWrite32(
-                                self.writer.file_handle,
+                                self.writer.tecio_handle()?,
                                 self.id,
                                 0,
                                 one_based as _,
@@ -342,7 +795,7 @@ impl<'a> TecZoneWriter<'a> {
                     try_err(
                         unsafe {
                             bindings::tecZoneNodeMapWrite64(
-                                self.writer.file_handle,
+                                self.writer.tecio_handle()?,
                                 self.id,
                                 0,
                                 one_based as _,
@@ -376,14 +829,248 @@ impl<'a> TecZoneWriter<'a> {
         }
         Ok(())
     }
+
+    /// Writes faces of a polygonal or polyhedral zone, may be called repeatedly to stream them in
+    /// chunks. Face node counts are only used for polyhedra, polygon faces always have two nodes.
+    pub fn write_poly_faces<'b, T: Into<TecData<'b>>>(
+        &mut self,
+        face_node_counts: &[i32],
+        face_nodes: T,
+        face_left_elements: T,
+        face_right_elements: T,
+        one_based: bool,
+    ) -> Result<()> {
+        let zone_type = self.poly_zone_type()?;
+        let (face_nodes, left, right) = (
+            face_nodes.into(),
+            face_left_elements.into(),
+            face_right_elements.into(),
+        );
+        let num_faces = left.len();
+        if right.len() != num_faces
+            || (zone_type == ZoneType::FEPolyhedron && face_node_counts.len() != num_faces)
+        {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Face node counts and elements of zone #{} must have one value per face",
+                    self.id
+                ),
+                code: -1,
+            });
+        }
+        // TecIO reads as many face nodes as the counts add up to.
+        let expected = match zone_type {
+            ZoneType::FEPolyhedron => total_count(face_node_counts),
+            _ => Some(2 * num_faces as i64),
+        };
+        if expected != Some(face_nodes.len() as i64) {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Face node counts of zone #{} don't match {} face nodes",
+                    self.id,
+                    face_nodes.len()
+                ),
+                code: -1,
+            });
+        }
+        if let InnerWriter::Plt(writer) = &mut self.writer.inner {
+            return writer.write_poly_faces(
+                self.id,
+                face_node_counts,
+                face_nodes,
+                left,
+                right,
+                one_based,
+            );
+        }
+        let counts = match zone_type {
+            ZoneType::FEPolyhedron => face_node_counts.as_ptr(),
+            _ => null(),
+        };
+        match (face_nodes, left, right) {
+            (TecData::I32(nodes), TecData::I32(left), TecData::I32(right)) => try_err(
+                unsafe {
+                    bindings::tecZoneWritePolyFaces32(
+                        self.writer.tecio_handle()?,
+                        self.id,
+                        0,
+                        num_faces as _,
+                        counts,
+                        nodes.as_ptr(),
+                        left.as_ptr(),
+                        right.as_ptr(),
+                        one_based as _,
+                    )
+                },
+                format!("Error writing faces to zone #{}, {:?}", self.id, self.zone),
+            ),
+            (TecData::I64(nodes), TecData::I64(left), TecData::I64(right)) => try_err(
+                unsafe {
+                    bindings::tecZoneWritePolyFaces64(
+                        self.writer.tecio_handle()?,
+                        self.id,
+                        0,
+                        num_faces as _,
+                        counts,
+                        nodes.as_ptr(),
+                        left.as_ptr(),
+                        right.as_ptr(),
+                        one_based as _,
+                    )
+                },
+                format!("Error writing faces to zone #{}, {:?}", self.id, self.zone),
+            ),
+            _ => Err(TecioError::Other {
+                message: "Unsupported datatype for poly faces!".to_owned(),
+                code: -1,
+            }),
+        }
+    }
+
+    /// Writes connections of boundary faces referenced by negative right elements. Connected
+    /// zones are always one-based.
+    pub fn write_poly_boundary_connections<'b, T: Into<TecData<'b>>>(
+        &mut self,
+        connection_counts: &[i32],
+        connected_elements: T,
+        connected_zones: &[i32],
+        one_based: bool,
+    ) -> Result<()> {
+        self.poly_zone_type()?;
+        let elements = connected_elements.into();
+        if elements.len() != connected_zones.len() {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Boundary connections of zone #{} need a zone for every element",
+                    self.id
+                ),
+                code: -1,
+            });
+        }
+        if total_count(connection_counts) != Some(elements.len() as i64) {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Boundary connection counts of zone #{} don't match {} connections",
+                    self.id,
+                    elements.len()
+                ),
+                code: -1,
+            });
+        }
+        if let InnerWriter::Plt(writer) = &mut self.writer.inner {
+            return writer.write_poly_boundary_connections(
+                self.id,
+                connection_counts,
+                elements,
+                connected_zones,
+                one_based,
+            );
+        }
+        match elements {
+            TecData::I32(elements) => try_err(
+                unsafe {
+                    bindings::tecZoneWritePolyBoundaryConnections32(
+                        self.writer.tecio_handle()?,
+                        self.id,
+                        0,
+                        connection_counts.len() as _,
+                        connection_counts.as_ptr(),
+                        elements.as_ptr(),
+                        connected_zones.as_ptr(),
+                        one_based as _,
+                    )
+                },
+                format!(
+                    "Error writing boundary connections to zone #{}, {:?}",
+                    self.id, self.zone
+                ),
+            ),
+            TecData::I64(elements) => try_err(
+                unsafe {
+                    bindings::tecZoneWritePolyBoundaryConnections64(
+                        self.writer.tecio_handle()?,
+                        self.id,
+                        0,
+                        connection_counts.len() as _,
+                        connection_counts.as_ptr(),
+                        elements.as_ptr(),
+                        connected_zones.as_ptr(),
+                        one_based as _,
+                    )
+                },
+                format!(
+                    "Error writing boundary connections to zone #{}, {:?}",
+                    self.id, self.zone
+                ),
+            ),
+            _ => Err(TecioError::Other {
+                message: "Unsupported datatype for boundary connections!".to_owned(),
+                code: -1,
+            }),
+        }
+    }
+
+    fn poly_zone_type(&self) -> Result<ZoneType> {
+        match &self.zone {
+            TecZone::PolyFE(zone) => Ok(zone.zone_type),
+            zone => Err(TecioError::Other {
+                message: format!(
+                    "Error, zone #{} of type {:?}, cannot contain poly faces!",
+                    self.id,
+                    zone.zone_type()
+                ),
+                code: -1,
+            }),
+        }
+    }
 }
 
 
 #[cfg(test)]
 mod tests{
-    use super::{TecWriter, TecZoneWriter};
+    use super::{check_var_lists, TecWriter, TecZoneWriter};
     use crate::*;
 
+    #[test]
+    fn var_list_lengths() {
+        let zone = |var_share_list: Vec<i32>, passive_var_list: Vec<i32>| {
+            TecZone::Ordered(OrderedZone {
+                name: "Lists".to_string(),
+                id: 1,
+                solution_time: 0.0,
+                strand: 0,
+                parent_zone: 0,
+                i_max: 2,
+                j_max: 1,
+                k_max: 1,
+                var_location: vec![ValueLocation::Nodal; 2],
+                var_types: Some(vec![TecDataType::F32; 2]),
+                var_share_list,
+                passive_var_list,
+                aux_data: AuxData::new(),
+            })
+        };
+        assert!(check_var_lists(&zone(vec![], vec![]), 2).is_ok());
+        assert!(check_var_lists(&zone(vec![0, 1], vec![1, 0]), 2).is_ok());
+        for (zone, num_vars, message) in [
+            (zone(vec![1], vec![]), 2, "Zone \"Lists\" has 1 shared vars, expected 2"),
+            (zone(vec![], vec![0, 0, 1]), 2, "Zone \"Lists\" has 3 passive vars, expected 2"),
+            (zone(vec![], vec![]), 3, "Zone \"Lists\" has 2 var locations, expected 3"),
+        ]
+        .iter()
+        {
+            match check_var_lists(zone, *num_vars) {
+                Err(TecioError::Other { message: m, .. }) => assert_eq!(&m, message),
+                r => panic!("Expected {}, got {:?}", message, r),
+            }
+        }
+        let mut types = zone(vec![], vec![]);
+        if let TecZone::Ordered(z) = &mut types {
+            z.var_types = Some(vec![TecDataType::F32]);
+        }
+        assert!(check_var_lists(&types, 2).is_err());
+    }
+
     #[test]
     fn simple_write(){
         let xi = vec![0.0, 1.0, 0.0];
@@ -402,10 +1089,15 @@ mod tests{
                 id: 1,
                 solution_time: 2.0,
                 strand: 1,
+                parent_zone: 0,
                 nodes: 3,
                 cells: 1,
                 var_location: vec![ValueLocation::Nodal, ValueLocation::Nodal, ValueLocation::Nodal, ValueLocation::CellCentered, ],
-                var_types: Some(vec![TecDataType::F64, TecDataType::F64,TecDataType::F64, TecDataType::F64,])
+                var_types: Some(vec![TecDataType::F64, TecDataType::F64,TecDataType::F64, TecDataType::F64,]),
+                var_share_list: vec![],
+                passive_var_list: vec![],
+                connectivity_share_zone: 0,
+                aux_data: AuxData::new(),
             })
         ).unwrap();
         zone.write_data(1, xi);
@@ -415,4 +1107,525 @@ mod tests{
         zone.write_nodemap(map, false);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn plt_round_trip() {
+        let path = crate::tests::temp_path("plt_round_trip.plt");
+        let config = WriterConfig::default().file_format(FileFormat::Binary);
+        {
+            let mut writer =
+                TecWriter::create(path.to_str().unwrap(), "Round trip", "X, Y P", 3, &config)
+                    .unwrap();
+            let mut zone = writer
+                .add_zone(TecZone::Ordered(OrderedZone {
+                    name: "Ordered".to_string(),
+                    id: 1,
+                    solution_time: 1.5,
+                    strand: 2,
+                    parent_zone: 0,
+                    i_max: 3,
+                    j_max: 2,
+                    k_max: 1,
+                    var_location: vec![
+                        ValueLocation::Nodal,
+                        ValueLocation::Nodal,
+                        ValueLocation::CellCentered,
+                    ],
+                    var_types: None,
+                    var_share_list: vec![],
+                    passive_var_list: vec![0, 0, 0],
+                    aux_data: vec![("Solver".to_string(), "implicit Euler".to_string())]
+                        .into_iter()
+                        .collect(),
+                }))
+                .unwrap();
+            zone.write_data(1, vec![0.0f64, 1.0, 2.0, 0.0, 1.0, 2.0]).unwrap();
+            zone.write_data(2, vec![0.0f32, 0.0, 0.0, 1.0, 1.0, 1.0]).unwrap();
+            zone.write_data(3, vec![10.0f64, 20.0]).unwrap();
+
+            let mut zone = writer
+                .add_zone(TecZone::ClassicFE(ClassicFEZone {
+                    name: "Triangle".to_string(),
+                    zone_type: ZoneType::FETriangle,
+                    id: 2,
+                    solution_time: 0.0,
+                    strand: 0,
+                    parent_zone: 0,
+                    nodes: 3,
+                    cells: 1,
+                    var_location: vec![
+                        ValueLocation::Nodal,
+                        ValueLocation::Nodal,
+                        ValueLocation::CellCentered,
+                    ],
+                    var_types: None,
+                    var_share_list: vec![],
+                    passive_var_list: vec![],
+                    connectivity_share_zone: 0,
+                    aux_data: AuxData::new(),
+                }))
+                .unwrap();
+            zone.write_data(1, vec![0.0f32, 1.0, 0.0]).unwrap();
+            zone.write_data(2, vec![0.0f32, 0.0, 1.0]).unwrap();
+            zone.write_data(3, vec![1e3f64]).unwrap();
+            zone.write_nodemap(vec![1i32, 2, 3], true).unwrap();
+            writer.add_dataset_aux_data("Mach", "0.8").unwrap();
+            writer.add_var_aux_data(3, "Reference", "101325").unwrap();
+            assert!(writer.add_var_aux_data(4, "Reference", "0").is_err());
+        }
+
+        let plt = PltFormat::open(&path).unwrap();
+        assert_eq!(plt.dataset.title, "Round trip");
+        assert_eq!(plt.dataset.aux_data["Mach"], "0.8");
+        assert_eq!(plt.dataset.var_aux_data(3).unwrap()["Reference"], "101325");
+        assert!(plt.dataset.var_aux_data(1).unwrap().is_empty());
+        assert_eq!(plt.zones[0].aux_data()["Solver"], "implicit Euler");
+        assert!(plt.zones[1].aux_data().is_empty());
+        assert_eq!(plt.dataset.var_names, vec!["X", "Y", "P"]);
+        assert_eq!(plt.zones.len(), 2);
+        assert_eq!(plt.zones[0].name(), "Ordered");
+        assert_eq!(plt.zones[0].solution_time(), 1.5);
+        assert_eq!(plt.zones[1].zone_type(), ZoneType::FETriangle);
+
+        assert_eq!(
+            plt.get_data(1, 1).unwrap().as_f64(),
+            &[0.0, 1.0, 2.0, 0.0, 1.0, 2.0]
+        );
+        assert_eq!(
+            plt.get_data(1, 2).unwrap().as_f32(),
+            &[0.0, 0.0, 0.0, 1.0, 1.0, 1.0]
+        );
+        assert_eq!(plt.get_data(1, 3).unwrap().as_f64(), &[10.0, 20.0]);
+        assert_eq!(plt.get_data(2, 2).unwrap().as_f32(), &[0.0, 0.0, 1.0]);
+        assert_eq!(plt.get_data(2, 3).unwrap().as_f64(), &[1e3]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn plt_fe_zone_errors() {
+        let path = crate::tests::temp_path("plt_fe_zone_errors.plt");
+        let config = WriterConfig::default().file_format(FileFormat::Binary);
+        let mut writer =
+            TecWriter::create(path.to_str().unwrap(), "Errors", "X Y", 2, &config)
+                .unwrap();
+        assert!(writer.file_handle().is_none());
+        assert!(writer.handler().is_null());
+        let mut zone = writer
+            .add_fe_zone_writer("Triangle", ZoneType::FETriangle, 3, 1, 0.0, 0)
+            .unwrap();
+        match zone.write_data(0, vec![0.0f32, 1.0, 0.0]) {
+            Err(TecioError::ParseError(ParseError::IndexOutOfRange { index: 0, max: 2 })) => {}
+            other => panic!("Expected an out of range var, got {:?}", other),
+        }
+        zone.write_data(1, vec![0.0f32, 1.0, 0.0]).unwrap();
+        zone.write_data(2, vec![0.0f32, 0.0, 1.0]).unwrap();
+        assert!(zone.write_nodemap(vec![1i32, 2], true).is_err());
+        let second = writer.add_fe_zone("Second", ZoneType::FETriangle, 3, 1, 0.0, 0).unwrap();
+        assert_eq!(second, 2);
+        assert!(writer.close().is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn plt_unwritten_vars() {
+        let config = WriterConfig::default().file_format(FileFormat::Binary);
+        let path = crate::tests::temp_path("plt_unwritten_vars.plt");
+        let mut writer =
+            TecWriter::create(path.to_str().unwrap(), "Unwritten", "X Y", 2, &config).unwrap();
+        let mut zone = writer
+            .add_fe_zone_writer("Triangle", ZoneType::FETriangle, 3, 1, 0.0, 0)
+            .unwrap();
+        zone.write_data(1, vec![0.0f32, 1.0, 0.0]).unwrap();
+        zone.write_nodemap(vec![1i32, 2, 3], true).unwrap();
+        match writer.close() {
+            Err(TecioError::Other { message, .. }) => {
+                assert_eq!(message, "Var 2 of zone #1 \"Triangle\" was never written")
+            }
+            r => panic!("Expected an unwritten var, got {:?}", r),
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn plt_shared_vars() {
+        let path = crate::tests::temp_path("plt_shared_vars.plt");
+        let config = WriterConfig::default().file_format(FileFormat::Binary);
+        let zone = |name: &str, time, var_share_list| {
+            TecZone::Ordered(OrderedZone {
+                name: name.to_string(),
+                id: 0,
+                solution_time: time,
+                strand: 1,
+                parent_zone: 0,
+                i_max: 2,
+                j_max: 2,
+                k_max: 1,
+                var_location: vec![ValueLocation::Nodal; 3],
+                var_types: None,
+                var_share_list,
+                passive_var_list: vec![0, 0, 0],
+                aux_data: AuxData::new(),
+            })
+        };
+        {
+            let mut writer =
+                TecWriter::create(path.to_str().unwrap(), "Shared", "X Y P", 3, &config)
+                    .unwrap();
+            let mut first = writer.add_zone(zone("t0", 0.0, vec![])).unwrap();
+            first.write_data(1, vec![0.0f32, 1.0, 0.0, 1.0]).unwrap();
+            first.write_data(2, vec![0.0f32, 0.0, 1.0, 1.0]).unwrap();
+            first.write_data(3, vec![1.0f32, 2.0, 3.0, 4.0]).unwrap();
+            let mut second = writer.add_zone(zone("t1", 1.0, vec![1, 1, 0])).unwrap();
+            assert!(second.write_data(1, vec![0.0f32; 4]).is_err());
+            second.write_data(3, vec![5.0f32, 6.0, 7.0, 8.0]).unwrap();
+            assert!(writer.add_zone(zone("t2", 2.0, vec![3, 0, 0])).is_err());
+
+            let mut larger = zone("t2", 2.0, vec![1, 0, 0]);
+            if let TecZone::Ordered(z) = &mut larger {
+                z.i_max = 3;
+            }
+            assert!(writer.add_zone(larger).is_err());
+            let mut cell_centered = zone("t2", 2.0, vec![1, 0, 0]);
+            if let TecZone::Ordered(z) = &mut cell_centered {
+                z.var_location[0] = ValueLocation::CellCentered;
+            }
+            assert!(writer.add_zone(cell_centered).is_err());
+            let mut passive = zone("t2", 2.0, vec![]);
+            if let TecZone::Ordered(z) = &mut passive {
+                z.passive_var_list = vec![0, 1];
+            }
+            assert!(writer.add_zone(passive).is_err());
+        }
+
+        let reader = TecReader::open(&path).unwrap();
+        assert_eq!(reader.zones()[1].shared_from(1), Some(1));
+        assert_eq!(reader.zones()[1].shared_from(3), None);
+        assert_eq!(reader.get_data(2, 1).unwrap(), reader.get_data(1, 1).unwrap());
+        assert_eq!(reader.get_data(2, 2).unwrap().as_f32(), vec![0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(reader.get_data(2, 3).unwrap().as_f32(), vec![5.0, 6.0, 7.0, 8.0]);
+        assert_eq!(reader.get_var_min_max(2, 2), Some((0.0, 1.0)));
+        assert_eq!(reader.get_var_min_max(2, 3), Some((5.0, 8.0)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn plt_integer_vars() {
+        let path = crate::tests::temp_path("plt_integer_vars.plt");
+        let config = WriterConfig::default().file_format(FileFormat::Binary);
+        let mut location = vec![ValueLocation::Nodal; 5];
+        location[1] = ValueLocation::CellCentered;
+        let zone = TecZone::Ordered(OrderedZone {
+            name: "Tags".to_string(),
+            id: 0,
+            solution_time: 0.0,
+            strand: 0,
+            parent_zone: 0,
+            i_max: 3,
+            j_max: 2,
+            k_max: 1,
+            var_location: location,
+            var_types: Some(vec![
+                TecDataType::F32,
+                TecDataType::I32,
+                TecDataType::I16,
+                TecDataType::I8,
+                TecDataType::I1,
+            ]),
+            var_share_list: vec![],
+            passive_var_list: vec![],
+            aux_data: AuxData::new(),
+        });
+        let bits = vec![1i8, 0, 1, 1, 0, 1];
+        {
+            let mut writer = TecWriter::create(
+                path.to_str().unwrap(),
+                "Integers",
+                "X CELL BOUNDARY MATERIAL FLAG",
+                5,
+                &config,
+            )
+            .unwrap();
+            let mut zone = writer.add_zone(zone).unwrap();
+            zone.write_data(1, vec![0.0f32, 1.0, 2.0, 0.0, 1.0, 2.0]).unwrap();
+            zone.write_data(2, vec![-7i32, 100_000]).unwrap();
+            zone.write_data(3, vec![1i16, 2, 3, 4, 5, -6]).unwrap();
+            zone.write_data(4, vec![0i8, 1, 127, -128, -56, -1]).unwrap();
+            zone.write_data(5, bits.clone()).unwrap();
+            assert!(zone.write_data(1, vec![0u32; 6]).is_err());
+        }
+
+        let reader = TecReader::open(&path).unwrap();
+        assert_eq!(reader.get_data(1, 2).unwrap(), TecData::from(vec![-7i32, 100_000]));
+        let shorts = reader.get_data(1, 3).unwrap();
+        assert_eq!(shorts.to_vec::<i32>().unwrap(), vec![1, 2, 3, 4, 5, -6]);
+        let bytes = reader.get_data(1, 4).unwrap().to_vec::<u8>().unwrap();
+        assert_eq!(bytes, vec![0, 1, 127, 128, 200, 255]);
+        assert_eq!(reader.get_data(1, 5).unwrap(), TecData::from(bits));
+        assert_eq!(reader.get_var_min_max(1, 2), Some((-7.0, 100_000.0)));
+        assert_eq!(reader.get_var_min_max(1, 4), Some((0.0, 255.0)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn plt_shared_connectivity() {
+        let path = crate::tests::temp_path("plt_shared_connectivity.plt");
+        let config = WriterConfig::default().file_format(FileFormat::Binary);
+        let zone = |time, var_share_list, connectivity_share_zone| {
+            TecZone::ClassicFE(ClassicFEZone {
+                name: format!("t = {}", time),
+                zone_type: ZoneType::FETriangle,
+                id: 0,
+                solution_time: time,
+                strand: 1,
+                parent_zone: 0,
+                nodes: 3,
+                cells: 1,
+                var_location: vec![ValueLocation::Nodal; 3],
+                var_types: None,
+                var_share_list,
+                passive_var_list: vec![],
+                connectivity_share_zone,
+                aux_data: AuxData::new(),
+            })
+        };
+        {
+            let mut writer = TecWriter::create(
+                path.to_str().unwrap(),
+                "Shared connectivity",
+                "X Y P",
+                3,
+                &config,
+            )
+            .unwrap();
+            let mut first = writer.add_zone(zone(0.0, vec![], 0)).unwrap();
+            first.write_data(1, vec![0.0f32, 1.0, 0.0]).unwrap();
+            first.write_data(2, vec![0.0f32, 0.0, 1.0]).unwrap();
+            first.write_data(3, vec![1.0f32, 2.0, 3.0]).unwrap();
+            first.write_nodemap(vec![2i32, 0, 1], false).unwrap();
+            let mut second = writer.add_zone(zone(1.0, vec![1, 1, 0], 1)).unwrap();
+            second.write_data(3, vec![4.0f32, 5.0, 6.0]).unwrap();
+            assert!(second.write_nodemap(vec![0i32, 1, 2], false).is_err());
+            assert!(writer.add_zone(zone(2.0, vec![], 3)).is_err());
+            let mut quad = zone(2.0, vec![], 1);
+            if let TecZone::ClassicFE(z) = &mut quad {
+                z.zone_type = ZoneType::FEQuad;
+                z.nodes = 4;
+            }
+            assert!(writer.add_zone(quad).is_err());
+        }
+
+        let reader = TecReader::open(&path).unwrap();
+        assert_eq!(reader.zones()[0].connectivity_shared_from(), None);
+        assert_eq!(reader.zones()[1].connectivity_shared_from(), Some(1));
+        assert_eq!(
+            reader.get_connectivity(2).unwrap().unwrap(),
+            reader.get_connectivity(1).unwrap().unwrap()
+        );
+        let connectivity = reader.get_connectivity(2).unwrap().unwrap();
+        assert_eq!(connectivity.to_vec::<i32>().unwrap(), vec![3, 1, 2]);
+        assert_eq!(reader.get_data(2, 3).unwrap().as_f32(), vec![4.0, 5.0, 6.0]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn plt_poly_faces() {
+        let path = crate::tests::temp_path("plt_poly_faces.plt");
+        let missing = crate::tests::temp_path("plt_poly_missing.plt");
+        let config = WriterConfig::default().file_format(FileFormat::Binary);
+        let tetra = TecZone::PolyFE(PolyFE {
+            name: "tetra".to_owned(),
+            zone_type: ZoneType::FEPolyhedron,
+            id: 0,
+            solution_time: 0.0,
+            strand: 0,
+            parent_zone: 0,
+            nodes: 4,
+            cells: 1,
+            faces: 4,
+            total_num_face_nodes: 12,
+            num_connected_boundary_faces: 1,
+            total_num_boundary_connections: 1,
+            var_location: vec![ValueLocation::Nodal; 3],
+            var_types: None,
+            var_share_list: vec![],
+            passive_var_list: vec![],
+            connectivity_share_zone: 0,
+            aux_data: AuxData::new(),
+        });
+        {
+            let mut writer =
+                TecWriter::create(path.to_str().unwrap(), "Poly", "X Y Z", 3, &config)
+                    .unwrap();
+            let mut zone = writer.add_zone(tetra.clone()).unwrap();
+            zone.write_data(1, vec![0.0f32, 1.0, 0.0, 0.0]).unwrap();
+            zone.write_data(2, vec![0.0f32, 0.0, 1.0, 0.0]).unwrap();
+            zone.write_data(3, vec![0.0f32, 0.0, 0.0, 1.0]).unwrap();
+            // Counts have to match the face nodes and connected elements.
+            let nodes = vec![1i32, 3, 2, 1, 2];
+            assert!(zone.write_poly_faces(&[3, 3], nodes, vec![1, 1], vec![0, 0], true).is_err());
+            let nodes = vec![1i32, 3, 2];
+            assert!(zone.write_poly_faces(&[4, -1], nodes, vec![1, 1], vec![0, 0], true).is_err());
+            assert!(zone.write_poly_boundary_connections(&[2], vec![1i32], &[1], true).is_err());
+            let nodes = vec![1i32, 3, 2, 1, 2, 4];
+            zone.write_poly_faces(&[3, 3], nodes, vec![1, 1], vec![0, 0], true)
+                .unwrap();
+            // Zero-based 64 bit indices, the last face is the first boundary face.
+            let nodes = vec![1i64, 2, 3, 2, 0, 3];
+            zone.write_poly_faces(&[3, 3], nodes, vec![0, 0], vec![-1, -2], false)
+                .unwrap();
+            zone.write_poly_boundary_connections(&[1], vec![1i32], &[1], true)
+                .unwrap();
+            assert!(zone.write_nodemap(vec![1i32, 2, 3, 4], true).is_err());
+            // Anything beyond the counts in the zone header is rejected right away.
+            assert!(zone.write_poly_faces(&[3], vec![1i32, 2, 3], vec![1], vec![0], true).is_err());
+            assert!(zone.write_poly_boundary_connections(&[1], vec![1i32], &[1], true).is_err());
+        }
+        {
+            let mut writer =
+                TecWriter::create(missing.to_str().unwrap(), "Poly", "X Y Z", 3, &config)
+                    .unwrap();
+            let mut zone = writer.add_zone(tetra).unwrap();
+            let nodes = vec![1i32, 3, 2, 1, 2, 4];
+            zone.write_poly_faces(&[3, 3], nodes, vec![1, 1], vec![0, 0], true)
+                .unwrap();
+            assert!(writer.close().is_err());
+        }
+
+        let reader = TecReader::open(&path).unwrap();
+        assert_eq!(
+            reader.get_poly_faces(1).unwrap().unwrap(),
+            PolyFaces {
+                face_node_counts: vec![3; 4],
+                face_nodes: vec![1, 3, 2, 1, 2, 4, 2, 3, 4, 3, 1, 4],
+                face_left_elements: vec![1; 4],
+                face_right_elements: vec![0, 0, 0, -1],
+                boundary_connection_counts: vec![1],
+                boundary_connection_elements: vec![1],
+                boundary_connection_zones: vec![1],
+            }
+        );
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&missing).unwrap();
+    }
+
+    #[test]
+    fn plt_annotations() {
+        let path = crate::tests::temp_path("plt_annotations.plt");
+        let config = WriterConfig::default().file_format(FileFormat::Binary);
+        let lines = Geometry::new(
+            GeometryShape::Lines(vec![
+                Polyline {
+                    x: vec![0.0, 1.0, 2.0],
+                    y: vec![0.0, 1.0, 0.0],
+                    z: vec![],
+                },
+                Polyline {
+                    x: vec![0.5, 1.5],
+                    y: vec![2.0, 2.0],
+                    z: vec![],
+                },
+            ]),
+            [1.0, 2.0, 0.0],
+        );
+        let mut line_3d = Geometry::new(
+            GeometryShape::Lines(vec![Polyline {
+                x: vec![0.0, 1.0],
+                y: vec![0.0, 1.0],
+                z: vec![0.0, 1.0],
+            }]),
+            [0.0, 0.0, 1.0],
+        );
+        line_3d.line_pattern = LinePattern::Dashed;
+        line_3d.arrowhead_attachment = ArrowheadAttachment::End;
+        let mut rectangle = Geometry::new(
+            GeometryShape::Rectangle {
+                width: 2.0,
+                height: 1.0,
+            },
+            [5.0, 5.0, 0.0],
+        );
+        rectangle.coord_sys = CoordSys::Frame;
+        rectangle.scope = Scope::Local;
+        rectangle.clipping = Clipping::ClipToFrame;
+        let mut circle = Geometry::new(GeometryShape::Circle { radius: 0.5 }, [0.0, 0.0, 0.0]);
+        circle.zone = Some(1);
+        circle.color = 2;
+        circle.fill_color = Some(3);
+        circle.num_ellipse_points = 36;
+        circle.macro_function = "circle".to_owned();
+        let geometries = vec![lines, line_3d, rectangle, circle];
+        let mut title = TextAnnotation::new("Pressure\nsurface", [50.0, 90.0, 0.0]);
+        title.coord_sys = CoordSys::Frame;
+        title.typeface = "Times".to_owned();
+        title.bold = true;
+        title.italic = true;
+        title.alignment = TextAnchor::HeadCenter;
+        title.box_type = TextBox::Filled;
+        title.box_fill_color = 3;
+        let mut label = TextAnnotation::new("Probe", [0.0, 0.0, 1.0]);
+        label.coord_sys = CoordSys::Grid3D;
+        label.size_units = SizeUnits::Grid;
+        label.height = 0.2;
+        label.angle = 45.0;
+        label.zone = Some(1);
+        label.color = 2;
+        label.typeface = "Greek".to_owned();
+        let texts = vec![title, label];
+
+        {
+            let mut writer =
+                TecWriter::create(path.to_str().unwrap(), "Geometries", "X Y", 2, &config)
+                    .unwrap();
+            let mut bad = geometries[1].clone();
+            bad.coord_sys = CoordSys::Grid;
+            assert!(writer.add_geometry(&bad).is_err());
+            for geometry in &geometries {
+                writer.add_geometry(geometry).unwrap();
+            }
+            for text in &texts {
+                writer.add_text(text).unwrap();
+            }
+            writer.add_custom_labels(&["Low", "\"High\""]).unwrap();
+            writer.add_custom_labels::<String>(&[]).unwrap();
+            writer.add_user_record("Generated by report").unwrap();
+            let mut zone = writer
+                .add_zone(TecZone::Ordered(OrderedZone {
+                    name: "Points".to_string(),
+                    id: 1,
+                    solution_time: 0.0,
+                    strand: 0,
+                    parent_zone: 0,
+                    i_max: 2,
+                    j_max: 1,
+                    k_max: 1,
+                    var_location: vec![ValueLocation::Nodal; 2],
+                    var_types: None,
+                    var_share_list: vec![],
+                    passive_var_list: vec![0, 0],
+                    aux_data: AuxData::new(),
+                }))
+                .unwrap();
+            zone.write_data(1, vec![0.0f32, 1.0]).unwrap();
+            zone.write_data(2, vec![0.0f32, 1.0]).unwrap();
+        }
+
+        let reader = TecReader::open(&path).unwrap();
+        assert_eq!(reader.dataset().geometries, geometries);
+        assert_eq!(reader.dataset().texts, texts);
+        assert_eq!(
+            reader.dataset().custom_labels,
+            vec![vec!["Low".to_owned(), "\"High\"".to_owned()], vec![]]
+        );
+        assert_eq!(reader.dataset().user_records, vec!["Generated by report".to_owned()]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn custom_label_quoting() {
+        use crate::common::{quote_labels, unquote_labels};
+
+        let labels = ["Mon", "say \"hi\"", "C:\\data", ""];
+        let quoted = quote_labels(&labels);
+        assert_eq!(quoted, r#""Mon" "say \"hi\"" "C:\\data" """#);
+        assert_eq!(unquote_labels(&quoted), labels);
+    }
+}
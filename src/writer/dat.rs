@@ -0,0 +1,605 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    common::{DataPacking, Dataset, FileType, Result, TecDataType, TecioError, ZoneType},
+    TecData, TecZone, ValueLocation,
+};
+
+const VALUES_PER_LINE: usize = 5;
+
+pub struct DatWriterConfig {
+    packing: DataPacking,
+    precision: Option<usize>,
+    file_type: FileType,
+}
+
+impl Default for DatWriterConfig {
+    fn default() -> Self {
+        Self {
+            packing: DataPacking::Block,
+            precision: None,
+            file_type: FileType::Full,
+        }
+    }
+}
+
+impl DatWriterConfig {
+    pub fn packing(mut self, packing: DataPacking) -> Self {
+        self.packing = packing;
+        self
+    }
+    /// Number of digits after the decimal point of floating point values.
+    /// Without it values are written with the shortest representation that reads back exactly.
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+    pub fn file_type(mut self, file_type: FileType) -> Self {
+        self.file_type = file_type;
+        self
+    }
+}
+
+/// Writer of Tecplot ASCII (`.dat`) files.
+///
+/// Zones are written one after another with [`write_zone`](DatWriter::write_zone); FE zones have
+/// to be followed by their [`write_nodemap`](DatWriter::write_nodemap).
+pub struct DatWriter<W: Write> {
+    out: W,
+    num_vars: usize,
+    packing: DataPacking,
+    precision: Option<usize>,
+    connections: Option<(usize, usize)>,
+}
+
+impl DatWriter<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        dataset: &Dataset,
+        config: &DatWriterConfig,
+    ) -> Result<Self> {
+        let file = File::create(path)?;
+        Self::new(BufWriter::new(file), dataset, config)
+    }
+}
+
+impl<W: Write> DatWriter<W> {
+    pub fn new(mut out: W, dataset: &Dataset, config: &DatWriterConfig) -> Result<Self> {
+        writeln!(out, "TITLE = {}", quoted(&dataset.title))?;
+        match config.file_type {
+            FileType::Full => {}
+            FileType::GridOnly => writeln!(out, "FILETYPE = GRID")?,
+            FileType::SolutionOnly(_) => writeln!(out, "FILETYPE = SOLUTION")?,
+        }
+        write!(out, "VARIABLES =")?;
+        for name in dataset.var_names.iter() {
+            // Names read from `.dat` files keep their quotes.
+            let name = name.strip_prefix('"').and_then(|n| n.strip_suffix('"')).unwrap_or(name);
+            write!(out, " {}", quoted(name))?;
+        }
+        writeln!(out)?;
+        for (name, value) in dataset.aux_data.iter() {
+            writeln!(out, "DATASETAUXDATA {}={}", name, quoted(value))?;
+        }
+
+        Ok(Self {
+            out,
+            num_vars: dataset.var_names.len(),
+            packing: config.packing,
+            precision: config.precision,
+            connections: None,
+        })
+    }
+
+    /// Writes zone header and values of every variable, `data` is indexed by variable.
+    pub fn write_zone(&mut self, zone: &TecZone, data: &[TecData]) -> Result<()> {
+        self.check_nodemap_written()?;
+        if let TecZone::PolyFE(z) = zone {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Polygonal and polyhedral zones like {:?} can't be written as ASCII",
+                    z.name
+                ),
+                code: -1,
+            });
+        }
+        if data.len() != self.num_vars || zone.var_locs().len() != self.num_vars {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Zone {:?} should have {} variables, got {} locations and {} data arrays",
+                    zone.name(),
+                    self.num_vars,
+                    zone.var_locs().len(),
+                    data.len()
+                ),
+                code: -1,
+            });
+        }
+        for (n, (d, loc)) in data.iter().zip(zone.var_locs()).enumerate() {
+            let len = match loc {
+                ValueLocation::Nodal => zone.node_count(),
+                ValueLocation::CellCentered => zone.cell_count(),
+            };
+            if d.len() != len {
+                return Err(TecioError::Other {
+                    message: format!(
+                        "Var {} of zone {:?} should have {} values, got {}",
+                        n + 1,
+                        zone.name(),
+                        len,
+                        d.len()
+                    ),
+                    code: -1,
+                });
+            }
+        }
+        let cell_centered = zone
+            .var_locs()
+            .iter()
+            .enumerate()
+            .filter(|(_, loc)| matches!(loc, ValueLocation::CellCentered))
+            .map(|(n, _)| (n + 1).to_string())
+            .collect::<Vec<_>>();
+        if self.packing == DataPacking::Point && !cell_centered.is_empty() {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Zone {:?} has cell-centered variables, which can't be written with POINT packing",
+                    zone.name()
+                ),
+                code: -1,
+            });
+        }
+        let declared = zone.data_types().unwrap_or(&[]);
+        let types = data
+            .iter()
+            .enumerate()
+            .map(|(n, d)| match d {
+                TecData::F32(_) => Ok("SINGLE"),
+                TecData::F64(_) => Ok("DOUBLE"),
+                TecData::I32(_) => Ok("LONGINT"),
+                TecData::I16(_) => Ok("SHORTINT"),
+                TecData::I8(_) if matches!(declared.get(n), Some(TecDataType::I1)) => Ok("BIT"),
+                TecData::I8(_) => Ok("BYTE"),
+                _ => Err(TecioError::Other {
+                    message: format!(
+                        "Unsupported datatype for ASCII format in zone {:?}",
+                        zone.name()
+                    ),
+                    code: -1,
+                }),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        writeln!(self.out, "ZONE T={}", quoted(zone.name()))?;
+        let strand = match zone {
+            TecZone::Ordered(z) => z.strand,
+            TecZone::ClassicFE(z) => z.strand,
+            _ => unreachable!(),
+        };
+        writeln!(
+            self.out,
+            " STRANDID={}, SOLUTIONTIME={}",
+            strand,
+            zone.solution_time()
+        )?;
+        match zone {
+            TecZone::Ordered(z) => writeln!(
+                self.out,
+                " I={}, J={}, K={}, ZONETYPE=Ordered",
+                z.i_max, z.j_max, z.k_max
+            )?,
+            TecZone::ClassicFE(z) => writeln!(
+                self.out,
+                " NODES={}, ELEMENTS={}, ZONETYPE={}",
+                z.nodes,
+                z.cells,
+                zone_type_name(z.zone_type)
+            )?,
+            _ => unreachable!(),
+        }
+        match self.packing {
+            DataPacking::Block => writeln!(self.out, " DATAPACKING=BLOCK")?,
+            DataPacking::Point => writeln!(self.out, " DATAPACKING=POINT")?,
+        }
+        if !cell_centered.is_empty() {
+            writeln!(
+                self.out,
+                " VARLOCATION=([{}]=CELLCENTERED)",
+                cell_centered.join(",")
+            )?;
+        }
+        writeln!(self.out, " DT=({} )", types.join(" "))?;
+        for (name, value) in zone.aux_data().iter() {
+            writeln!(self.out, " AUXDATA {}={}", name, quoted(value))?;
+        }
+
+        let values = data
+            .iter()
+            .map(|d| self.format_values(d))
+            .collect::<Vec<_>>();
+        match self.packing {
+            DataPacking::Block => {
+                for values in values.iter() {
+                    for line in values.chunks(VALUES_PER_LINE) {
+                        writeln!(self.out, " {}", line.join(" "))?;
+                    }
+                }
+            }
+            DataPacking::Point => {
+                for node in 0..zone.node_count() {
+                    let line = values.iter().map(|v| v[node].as_str()).collect::<Vec<_>>();
+                    writeln!(self.out, " {}", line.join(" "))?;
+                }
+            }
+        }
+
+        if let TecZone::ClassicFE(z) = zone {
+            self.connections = Some((z.num_connections(), z.zone_type.num_nodes()));
+        }
+        Ok(())
+    }
+
+    /// Writes connectivity of the last written FE zone.
+    pub fn write_nodemap<'b, T: Into<TecData<'b>>>(
+        &mut self,
+        nodemap: T,
+        one_based: bool,
+    ) -> Result<()> {
+        let (len, nodes_per_cell) = self.connections.take().ok_or_else(|| TecioError::Other {
+            message: "Nodemap can only follow FE zone!".to_owned(),
+            code: -1,
+        })?;
+        let offset = !one_based as i64;
+        let nodemap = match nodemap.into() {
+            TecData::I32(data) => data.iter().map(|&n| n as i64 + offset).collect::<Vec<_>>(),
+            TecData::I64(data) => data.iter().map(|&n| n + offset).collect(),
+            _ => {
+                return Err(TecioError::Other {
+                    message: "Unsupported datatype for nodemap!".to_owned(),
+                    code: -1,
+                })
+            }
+        };
+        if nodemap.len() != len {
+            return Err(TecioError::Other {
+                message: format!("Expected nodemap of {} values, got {}", len, nodemap.len()),
+                code: -1,
+            });
+        }
+        for cell in nodemap.chunks(nodes_per_cell) {
+            let cell = cell.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+            writeln!(self.out, " {}", cell.join(" "))?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the output and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.check_nodemap_written()?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn check_nodemap_written(&self) -> Result<()> {
+        match self.connections {
+            Some(_) => Err(TecioError::Other {
+                message: "Previous FE zone is missing its nodemap".to_owned(),
+                code: -1,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn format_values(&self, data: &TecData) -> Vec<String> {
+        macro_rules! float {
+            ($v: expr) => {
+                match self.precision {
+                    Some(p) => $v.iter().map(|v| format!("{:.*E}", p, v)).collect(),
+                    None => $v.iter().map(|v| format!("{:E}", v)).collect(),
+                }
+            };
+        }
+        match data {
+            TecData::F64(v) => float!(v),
+            TecData::F32(v) => float!(v),
+            TecData::I32(v) => v.iter().map(|v| v.to_string()).collect(),
+            TecData::I16(v) => v.iter().map(|v| v.to_string()).collect(),
+            // Bytes are unsigned in DAT files.
+            TecData::I8(v) => v.iter().map(|&v| (v as u8).to_string()).collect(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Quotes and backslashes are escaped with a backslash.
+fn quoted(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn zone_type_name(zone_type: ZoneType) -> &'static str {
+    match zone_type {
+        ZoneType::Ordered => "Ordered",
+        ZoneType::FELine => "FELineSeg",
+        ZoneType::FETriangle => "FETriangle",
+        ZoneType::FEQuad => "FEQuadrilateral",
+        ZoneType::FETetra => "FETetrahedron",
+        ZoneType::FEBrick => "FEBrick",
+        ZoneType::FEPolygon => "FEPolygon",
+        ZoneType::FEPolyhedron => "FEPolyhedron",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DatWriter, DatWriterConfig};
+    use crate::*;
+
+    fn dataset() -> Dataset {
+        Dataset {
+            num_variables: 3,
+            num_zones: 2,
+            title: "Round trip".to_string(),
+            var_names: vec!["X".to_string(), "Y".to_string(), "P".to_string()],
+            aux_data: AuxData::new(),
+            var_aux_data: vec![],
+            geometries: vec![],
+            texts: vec![],
+            custom_labels: vec![],
+            user_records: vec![],
+        }
+    }
+
+    #[test]
+    fn block_round_trip() {
+        let ordered = TecZone::Ordered(OrderedZone {
+            name: "Ordered".to_string(),
+            id: 1,
+            solution_time: 1.5,
+            strand: 2,
+            parent_zone: 0,
+            i_max: 3,
+            j_max: 2,
+            k_max: 1,
+            var_location: vec![
+                ValueLocation::Nodal,
+                ValueLocation::Nodal,
+                ValueLocation::CellCentered,
+            ],
+            var_types: None,
+            var_share_list: vec![],
+            passive_var_list: vec![0, 0, 0],
+            aux_data: vec![("Solver".to_string(), "implicit Euler".to_string())]
+                .into_iter()
+                .collect(),
+        });
+        let triangle = TecZone::ClassicFE(ClassicFEZone {
+            name: "Triangle".to_string(),
+            zone_type: ZoneType::FETriangle,
+            id: 2,
+            solution_time: 0.0,
+            strand: 0,
+            parent_zone: 0,
+            nodes: 3,
+            cells: 1,
+            var_location: vec![
+                ValueLocation::Nodal,
+                ValueLocation::Nodal,
+                ValueLocation::CellCentered,
+            ],
+            var_types: None,
+            var_share_list: vec![],
+            passive_var_list: vec![],
+            connectivity_share_zone: 0,
+            aux_data: AuxData::new(),
+        });
+
+        let mut dataset = dataset();
+        dataset.aux_data.insert("Mach".to_string(), "0.8".to_string());
+        let mut writer = DatWriter::new(vec![], &dataset, &DatWriterConfig::default()).unwrap();
+        writer
+            .write_zone(
+                &ordered,
+                &[
+                    TecData::from(vec![0.0f64, 0.1, 0.2, 0.0, 0.1, 0.2]),
+                    TecData::from(vec![0.0f64, 0.0, 0.0, 1.0, 1.0, 1.0]),
+                    TecData::from(vec![-1e-7f64, 2e10]),
+                ],
+            )
+            .unwrap();
+        writer
+            .write_zone(
+                &triangle,
+                &[
+                    TecData::from(vec![0.0f32, 1.0, 0.0]),
+                    TecData::from(vec![0.0f32, 0.0, 1.0]),
+                    TecData::from(vec![1e3f32]),
+                ],
+            )
+            .unwrap();
+        assert!(writer.write_zone(&ordered, &[]).is_err());
+        writer.write_nodemap(vec![0i32, 1, 2], false).unwrap();
+        let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        let dat = DatFormat::read(&text).unwrap();
+        assert_eq!(dat.dataset.title, "Round trip");
+        assert_eq!(dat.dataset.aux_data["Mach"], "0.8");
+        assert_eq!(dat.zones[0].aux_data()["Solver"], "implicit Euler");
+        assert!(dat.zones[1].aux_data().is_empty());
+        assert_eq!(dat.zones.len(), 2);
+        assert_eq!(dat.zones[0].solution_time(), 1.5);
+        assert_eq!(dat.zones[1].zone_type(), ZoneType::FETriangle);
+        let block = &dat.data_blocks[0];
+        assert_eq!(
+            block.get_data(0).as_f64(),
+            vec![0.0, 0.1, 0.2, 0.0, 0.1, 0.2]
+        );
+        assert_eq!(block.get_data(2).as_f64(), vec![-1e-7, 2e10]);
+        let block = &dat.data_blocks[1];
+        assert_eq!(block.get_data(1).as_f32(), vec![0.0, 0.0, 1.0]);
+        assert_eq!(block.get_data(2).as_f32(), vec![1e3]);
+        assert_eq!(block.connectivity.as_ref().unwrap().to_vec::<i32>().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn integer_types() {
+        let zone = TecZone::Ordered(OrderedZone {
+            name: "Tags".to_string(),
+            id: 1,
+            solution_time: 0.0,
+            strand: 0,
+            parent_zone: 0,
+            i_max: 4,
+            j_max: 1,
+            k_max: 1,
+            var_location: vec![ValueLocation::Nodal; 3],
+            var_types: Some(vec![TecDataType::I16, TecDataType::I8, TecDataType::I1]),
+            var_share_list: vec![],
+            passive_var_list: vec![],
+            aux_data: AuxData::new(),
+        });
+        let data = [
+            TecData::from(vec![-300i16, 0, 1, 300]),
+            TecData::from(vec![0i8, 127, -128, -1]),
+            TecData::from(vec![1i8, 0, 0, 1]),
+        ];
+        for &packing in &[DataPacking::Block, DataPacking::Point] {
+            let config = DatWriterConfig::default().packing(packing);
+            let mut writer = DatWriter::new(vec![], &dataset(), &config).unwrap();
+            writer.write_zone(&zone, &data).unwrap();
+            let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+            assert!(text.contains(" DT=(SHORTINT BYTE BIT )\n"));
+            if packing == DataPacking::Block {
+                assert!(text.contains(" 0 127 128 255\n"));
+            }
+
+            let dat = DatFormat::read(&text).unwrap();
+            let block = &dat.data_blocks[0];
+            for (var, values) in data.iter().enumerate() {
+                assert_eq!(&block.get_data(var), values);
+            }
+            assert_eq!(block.get_data(1).to_vec::<u8>().unwrap(), vec![0, 127, 128, 255]);
+        }
+    }
+
+    #[test]
+    fn point_precision() {
+        let zone = OrderedZone {
+            name: "Line".to_string(),
+            id: 1,
+            solution_time: 0.0,
+            strand: 0,
+            parent_zone: 0,
+            i_max: 2,
+            j_max: 1,
+            k_max: 1,
+            var_location: vec![ValueLocation::Nodal; 3],
+            var_types: None,
+            var_share_list: vec![],
+            passive_var_list: vec![0, 0, 0],
+            aux_data: AuxData::new(),
+        };
+        let config = DatWriterConfig::default()
+            .packing(DataPacking::Point)
+            .precision(3);
+        let mut writer = DatWriter::new(vec![], &dataset(), &config).unwrap();
+        writer
+            .write_zone(
+                &TecZone::Ordered(zone.clone()),
+                &[
+                    TecData::from(vec![0.0f64, 1.0 / 3.0]),
+                    TecData::from(vec![-2.5f32, 1234.5]),
+                    TecData::from(vec![7i32, -8]),
+                ],
+            )
+            .unwrap();
+
+        let mut cell_centered = zone;
+        cell_centered.var_location[2] = ValueLocation::CellCentered;
+        assert!(writer
+            .write_zone(
+                &TecZone::Ordered(cell_centered),
+                &[
+                    TecData::from(vec![0.0f64, 1.0]),
+                    TecData::from(vec![0.0f64, 1.0]),
+                    TecData::from(vec![0.0f64]),
+                ],
+            )
+            .is_err());
+        // Nothing of the polyhedral zone ends up in the output.
+        let poly = TecZone::PolyFE(PolyFE {
+            name: "Poly".to_string(),
+            zone_type: ZoneType::FEPolyhedron,
+            id: 2,
+            solution_time: 0.0,
+            strand: 0,
+            parent_zone: 0,
+            nodes: 2,
+            cells: 1,
+            faces: 1,
+            total_num_face_nodes: 2,
+            num_connected_boundary_faces: 0,
+            total_num_boundary_connections: 0,
+            var_location: vec![ValueLocation::Nodal; 3],
+            var_types: None,
+            var_share_list: vec![],
+            passive_var_list: vec![],
+            connectivity_share_zone: 0,
+            aux_data: AuxData::new(),
+        });
+        let values = vec![TecData::from(vec![0.0f64, 1.0]); 3];
+        assert!(writer.write_zone(&poly, &values).is_err());
+
+        let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(
+            out,
+            "TITLE = \"Round trip\"\n\
+             VARIABLES = \"X\" \"Y\" \"P\"\n\
+             ZONE T=\"Line\"\n \
+             STRANDID=0, SOLUTIONTIME=0\n \
+             I=2, J=1, K=1, ZONETYPE=Ordered\n \
+             DATAPACKING=POINT\n \
+             DT=(DOUBLE SINGLE LONGINT )\n \
+             0.000E0 -2.500E0 7\n \
+             3.333E-1 1.234E3 -8\n"
+        );
+    }
+
+    #[test]
+    fn escaped_strings() {
+        let mut dataset = dataset();
+        dataset.title = r#"A "quoted" \ title"#.to_string();
+        dataset.var_names[2] = r#""P "total"""#.to_string();
+        dataset.aux_data.insert("Note".to_string(), r#"say "hi" \"#.to_string());
+        let zone = TecZone::Ordered(OrderedZone {
+            name: r#"Zone "1""#.to_string(),
+            id: 1,
+            solution_time: 0.0,
+            strand: 0,
+            parent_zone: 0,
+            i_max: 1,
+            j_max: 1,
+            k_max: 1,
+            var_location: vec![ValueLocation::Nodal; 3],
+            var_types: None,
+            var_share_list: vec![],
+            passive_var_list: vec![],
+            aux_data: vec![("Path".to_string(), r#"C:\"dir"\"#.to_string())]
+                .into_iter()
+                .collect(),
+        });
+        let mut writer = DatWriter::new(vec![], &dataset, &DatWriterConfig::default()).unwrap();
+        writer.write_zone(&zone, &vec![TecData::from(vec![1.0f64]); 3]).unwrap();
+        let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        let dat = DatFormat::read(&text).unwrap();
+        assert_eq!(dat.dataset.title, dataset.title);
+        assert_eq!(dat.dataset.var_names, vec!["\"X\"", "\"Y\"", r#""P "total"""#]);
+        assert_eq!(dat.dataset.aux_data["Note"], dataset.aux_data["Note"]);
+        assert_eq!(dat.zones[0].name(), zone.name());
+        assert_eq!(dat.zones[0].aux_data()["Path"], zone.aux_data()["Path"]);
+    }
+}
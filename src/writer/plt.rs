@@ -0,0 +1,853 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use super::check_var_lists;
+use crate::{
+    common::{AuxData, FileType, ParseError, Result, TecioError},
+    formats::plt::{ghost_cell_dims, ghost_cell_indices},
+    Geometry, GeometryShape, PolyFE, PolyFaces, TecData, TecDataType, TecZone, TextAnnotation,
+    ValueLocation, ZoneType,
+};
+
+const VERSION: &[u8] = b"#!TDV112";
+const ZONE_MARKER: f32 = 299.0;
+const GEOMETRY_MARKER: f32 = 399.0;
+const TEXT_MARKER: f32 = 499.0;
+const CUSTOM_LABELS_MARKER: f32 = 599.0;
+const USER_REC_MARKER: f32 = 699.0;
+const DATASET_AUX_MARKER: f32 = 799.0;
+const VAR_AUX_MARKER: f32 = 899.0;
+const END_OF_HEADER: f32 = 357.0;
+
+/// Native writer of binary (`.plt`) files.
+///
+/// Every zone header has to be written before the first data block, so zones and their data are
+/// kept in memory until the writer is closed.
+pub(crate) struct PltWriter {
+    file: Option<File>,
+    file_type: FileType,
+    title: String,
+    var_names: Vec<String>,
+    zones: Vec<PltZone>,
+    aux_data: AuxData,
+    var_aux_data: Vec<AuxData>,
+    geometries: Vec<Geometry>,
+    texts: Vec<TextAnnotation>,
+    custom_labels: Vec<Vec<String>>,
+    user_records: Vec<String>,
+}
+
+struct PltZone {
+    zone: TecZone,
+    data: Vec<Option<TecData<'static>>>,
+    nodemap: Option<Vec<i32>>,
+    poly_faces: PolyFaces,
+}
+
+impl PltWriter {
+    pub(crate) fn create(
+        file: File,
+        file_type: FileType,
+        title: String,
+        var_names: Vec<String>,
+    ) -> Self {
+        Self {
+            file: Some(file),
+            file_type,
+            title,
+            var_aux_data: vec![AuxData::new(); var_names.len()],
+            var_names,
+            zones: vec![],
+            aux_data: AuxData::new(),
+            geometries: vec![],
+            texts: vec![],
+            custom_labels: vec![],
+            user_records: vec![],
+        }
+    }
+
+    pub(crate) fn add_zone(&mut self, zone: TecZone) -> Result<i32> {
+        check_var_lists(&zone, self.var_names.len())?;
+        let num_zones = self.zones.len();
+        if (1..=self.var_names.len())
+            .filter_map(|var| zone.shared_from(var))
+            .chain(zone.connectivity_shared_from())
+            .any(|source| source > num_zones)
+        {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Zone {:?} can only share data from previously added zones",
+                    zone.name()
+                ),
+                code: -1,
+            });
+        }
+        // Shared data has to fit the zone it is shared into.
+        let len = |zone: &TecZone, loc| match loc {
+            ValueLocation::Nodal => zone.node_count(),
+            ValueLocation::CellCentered => zone.cell_count(),
+        };
+        for (var, &loc) in zone.var_locs().iter().enumerate() {
+            if let Some(source) = zone.shared_from(var + 1) {
+                let from = &self.zones[source - 1].zone;
+                if from.var_locs()[var] != loc || len(from, loc) != len(&zone, loc) {
+                    return Err(TecioError::Other {
+                        message: format!(
+                            "Var {} of zone {:?} doesn't fit the one shared from zone #{}",
+                            var + 1,
+                            zone.name(),
+                            source
+                        ),
+                        code: -1,
+                    });
+                }
+            }
+        }
+        let faces = |zone: &TecZone| match zone {
+            TecZone::PolyFE(z) => (z.faces, z.total_num_face_nodes, z.num_connected_boundary_faces),
+            _ => (0, 0, 0),
+        };
+        if let Some(source) = zone.connectivity_shared_from() {
+            let from = &self.zones[source - 1].zone;
+            if from.zone_type() != zone.zone_type()
+                || from.node_count() != zone.node_count()
+                || from.cell_count() != zone.cell_count()
+                || faces(from) != faces(&zone)
+            {
+                return Err(TecioError::Other {
+                    message: format!(
+                        "Connectivity of zone {:?} doesn't fit the one shared from zone #{}",
+                        zone.name(),
+                        source
+                    ),
+                    code: -1,
+                });
+            }
+        }
+        self.zones.push(PltZone {
+            zone,
+            data: vec![None; self.var_names.len()],
+            nodemap: None,
+            poly_faces: PolyFaces::default(),
+        });
+        Ok(self.zones.len() as i32)
+    }
+
+    pub(crate) fn add_geometry(&mut self, geometry: Geometry) {
+        self.geometries.push(geometry);
+    }
+
+    pub(crate) fn add_text(&mut self, text: TextAnnotation) {
+        self.texts.push(text);
+    }
+
+    pub(crate) fn add_custom_labels(&mut self, labels: Vec<String>) {
+        self.custom_labels.push(labels);
+    }
+
+    pub(crate) fn add_user_record(&mut self, record: String) {
+        self.user_records.push(record);
+    }
+
+    pub(crate) fn add_dataset_aux_data(&mut self, name: String, value: String) {
+        self.aux_data.insert(name, value);
+    }
+
+    pub(crate) fn add_var_aux_data(&mut self, var: i32, name: String, value: String) -> Result<()> {
+        let aux_data = self
+            .var_aux_data
+            .get_mut((var as usize).wrapping_sub(1))
+            .ok_or_else(|| TecioError::Other {
+                message: format!("Dataset has no var {}", var),
+                code: -1,
+            })?;
+        aux_data.insert(name, value);
+        Ok(())
+    }
+
+    pub(crate) fn write_data(&mut self, zone: i32, var: i32, data: TecData) -> Result<()> {
+        let z = self.zone_mut(zone)?;
+        let index = (var as usize).checked_sub(1);
+        let loc = *index
+            .and_then(|i| z.zone.var_locs().get(i))
+            .ok_or_else(|| out_of_range(var, z.data.len()))?;
+        if let Some(source) = z.zone.shared_from(var as usize) {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Var {} of zone #{} is shared from zone #{}",
+                    var, zone, source
+                ),
+                code: -1,
+            });
+        }
+        let len = match loc {
+            ValueLocation::Nodal => z.zone.node_count(),
+            ValueLocation::CellCentered => z.zone.cell_count(),
+        };
+        match data {
+            TecData::F64(_)
+            | TecData::F32(_)
+            | TecData::I32(_)
+            | TecData::I16(_)
+            | TecData::I8(_)
+                if data.len() == len => {}
+            _ => {
+                return Err(TecioError::Other {
+                    message: format!(
+                    "Error writing to zone {}, var {}, expected {} values of a binary format type",
+                    zone, var, len
+                ),
+                    code: -1,
+                })
+            }
+        }
+        if let Some(slot) = index.and_then(|i| z.data.get_mut(i)) {
+            *slot = Some(data.into_owned());
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write_nodemap(
+        &mut self,
+        zone: i32,
+        nodemap: TecData,
+        one_based: bool,
+    ) -> Result<()> {
+        let z = self.zone_mut(zone)?;
+        if let Some(source) = z.zone.connectivity_shared_from() {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Connectivity of zone #{} is shared from zone #{}",
+                    zone, source
+                ),
+                code: -1,
+            });
+        }
+        let nodemap = indices(nodemap, -(one_based as i64), "nodemap")?;
+        if let TecZone::ClassicFE(fe) = &z.zone {
+            if nodemap.len() != fe.num_connections() {
+                return Err(TecioError::Other {
+                    message: format!(
+                        "Expected nodemap of {} values for zone #{}, got {}",
+                        fe.num_connections(),
+                        zone,
+                        nodemap.len()
+                    ),
+                    code: -1,
+                });
+            }
+        }
+        let nodes = z.zone.node_count() as i64;
+        if nodemap.iter().any(|&n| n < 0 || n >= nodes) {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Nodemap of zone #{} references nodes outside of the zone",
+                    zone
+                ),
+                code: -1,
+            });
+        }
+        z.nodemap = Some(nodemap.into_iter().map(|n| n as i32).collect());
+        Ok(())
+    }
+
+    /// Appends faces to a polygonal or polyhedral zone, stored one-based until the file is closed.
+    /// Counts are checked against the face nodes by the zone writer.
+    pub(crate) fn write_poly_faces(
+        &mut self,
+        zone: i32,
+        face_node_counts: &[i32],
+        face_nodes: TecData,
+        face_left_elements: TecData,
+        face_right_elements: TecData,
+        one_based: bool,
+    ) -> Result<()> {
+        let z = self.poly_zone_mut(zone)?;
+        let offset = !one_based as i64;
+        let face_nodes = indices(face_nodes, offset, "poly faces")?;
+        let left = indices(face_left_elements, offset, "poly faces")?;
+        let right = indices(face_right_elements, offset, "poly faces")?;
+        let counts = match z.zone.zone_type() {
+            ZoneType::FEPolyhedron => face_node_counts.to_vec(),
+            _ => vec![2; left.len()],
+        };
+        if let TecZone::PolyFE(poly) = &z.zone {
+            let [faces, nodes, boundary_faces, connections] = face_counts(&z.poly_faces);
+            let (faces, nodes) = (faces + left.len(), nodes + face_nodes.len());
+            let written = [faces, nodes, boundary_faces, connections];
+            check_face_counts(poly, written, false)?;
+        }
+        let faces = &mut z.poly_faces;
+        faces.face_node_counts.extend(counts);
+        faces.face_nodes.extend(face_nodes);
+        faces.face_left_elements.extend(left);
+        faces.face_right_elements.extend(right);
+        Ok(())
+    }
+
+    /// Appends connections of boundary faces, connected zones are always one-based.
+    pub(crate) fn write_poly_boundary_connections(
+        &mut self,
+        zone: i32,
+        connection_counts: &[i32],
+        connected_elements: TecData,
+        connected_zones: &[i32],
+        one_based: bool,
+    ) -> Result<()> {
+        let z = self.poly_zone_mut(zone)?;
+        let elements = indices(connected_elements, !one_based as i64, "boundary connections")?;
+        if let TecZone::PolyFE(poly) = &z.zone {
+            let [faces, nodes, boundary_faces, connections] = face_counts(&z.poly_faces);
+            let boundary_faces = boundary_faces + connection_counts.len();
+            let written = [faces, nodes, boundary_faces, connections + elements.len()];
+            check_face_counts(poly, written, false)?;
+        }
+        let faces = &mut z.poly_faces;
+        faces.boundary_connection_counts.extend(connection_counts);
+        faces.boundary_connection_elements.extend(elements);
+        faces.boundary_connection_zones.extend(connected_zones);
+        Ok(())
+    }
+
+    pub(crate) fn close(&mut self) -> Result<()> {
+        if let Some(file) = self.file.take() {
+            for (id, zone) in self.zones.iter().enumerate() {
+                let shared = zone.zone.connectivity_shared_from();
+                if let (TecZone::PolyFE(z), None) = (&zone.zone, shared) {
+                    check_face_counts(z, face_counts(&zone.poly_faces), true)?;
+                }
+                let stored = |var: usize| {
+                    !zone.zone.is_passive(var) && zone.zone.shared_from(var).is_none()
+                };
+                let unwritten = |var: usize| stored(var) && zone.data[var - 1].is_none();
+                if let Some(var) = (1..=zone.data.len()).find(|&var| unwritten(var)) {
+                    return Err(TecioError::Other {
+                        message: format!(
+                            "Var {} of zone #{} {:?} was never written",
+                            var,
+                            id + 1,
+                            zone.zone.name()
+                        ),
+                        code: -1,
+                    });
+                }
+            }
+            let mut out = BufWriter::new(file);
+            self.write_header(&mut out)?;
+            for zone in self.zones.iter() {
+                write_data_block(&mut out, zone)?;
+            }
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    fn zone_mut(&mut self, zone: i32) -> Result<&mut PltZone> {
+        let len = self.zones.len();
+        (zone as usize)
+            .checked_sub(1)
+            .and_then(move |i| self.zones.get_mut(i))
+            .ok_or_else(|| out_of_range(zone, len))
+    }
+
+    fn poly_zone_mut(&mut self, zone: i32) -> Result<&mut PltZone> {
+        let z = self.zone_mut(zone)?;
+        if !matches!(z.zone, TecZone::PolyFE(_)) {
+            return Err(TecioError::Other {
+                message: format!("Zone #{} is not a polygonal or polyhedral zone", zone),
+                code: -1,
+            });
+        }
+        if let Some(source) = z.zone.connectivity_shared_from() {
+            return Err(TecioError::Other {
+                message: format!("Faces of zone #{} are shared from zone #{}", zone, source),
+                code: -1,
+            });
+        }
+        Ok(z)
+    }
+
+    fn write_header<W: Write>(&self, out: &mut W) -> Result<()> {
+        out.write_all(VERSION)?;
+        write_i32(out, 1)?;
+        write_i32(out, self.file_type.as_i32())?;
+        write_string(out, &self.title)?;
+        write_i32(out, self.var_names.len() as i32)?;
+        for name in self.var_names.iter() {
+            write_string(out, name)?;
+        }
+        for zone in self.zones.iter() {
+            write_zone_header(out, &zone.zone)?;
+        }
+        for geometry in self.geometries.iter() {
+            write_geometry(out, geometry)?;
+        }
+        for text in self.texts.iter() {
+            write_text(out, text)?;
+        }
+        for labels in self.custom_labels.iter() {
+            write_f32(out, CUSTOM_LABELS_MARKER)?;
+            write_i32(out, labels.len() as i32)?;
+            for label in labels.iter() {
+                write_string(out, label)?;
+            }
+        }
+        for record in self.user_records.iter() {
+            write_f32(out, USER_REC_MARKER)?;
+            write_string(out, record)?;
+        }
+        for (name, value) in self.aux_data.iter() {
+            write_f32(out, DATASET_AUX_MARKER)?;
+            write_aux_data(out, name, value)?;
+        }
+        for (var, aux_data) in self.var_aux_data.iter().enumerate() {
+            for (name, value) in aux_data.iter() {
+                write_f32(out, VAR_AUX_MARKER)?;
+                write_i32(out, var as i32)?;
+                write_aux_data(out, name, value)?;
+            }
+        }
+        write_f32(out, END_OF_HEADER)?;
+        Ok(())
+    }
+}
+
+impl Drop for PltWriter {
+    /// Best-effort close, errors are only reported by an explicit close.
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+/// One-based zone or variable id that isn't in `1..=len`.
+fn out_of_range(id: i32, len: usize) -> TecioError {
+    TecioError::from(ParseError::IndexOutOfRange {
+        index: id as i64,
+        max: len as i64,
+    })
+}
+
+fn write_zone_header<W: Write>(out: &mut W, zone: &TecZone) -> Result<()> {
+    let (strand, solution_time) = match zone {
+        TecZone::Ordered(z) => (z.strand, z.solution_time),
+        TecZone::ClassicFE(z) => (z.strand, z.solution_time),
+        TecZone::PolyFE(z) => (z.strand, z.solution_time),
+    };
+
+    write_f32(out, ZONE_MARKER)?;
+    write_string(out, zone.name())?;
+    write_i32(out, zone.parent_zone().map_or(-1, |z| z as i32 - 1))?;
+    write_i32(out, strand)?;
+    write_f64(out, solution_time)?;
+    write_i32(out, -1)?; // zone color
+    write_i32(out, zone.zone_type() as i32)?;
+    let locs = zone.var_locs();
+    if locs.iter().all(|loc| matches!(loc, ValueLocation::Nodal)) {
+        write_i32(out, 0)?;
+    } else {
+        write_i32(out, 1)?;
+        for &loc in locs {
+            write_i32(out, 1 - loc as i32)?;
+        }
+    }
+    write_i32(out, 0)?; // raw local face neighbors
+    write_i32(out, 0)?; // miscellaneous face neighbor connections
+
+    match zone {
+        TecZone::Ordered(z) => {
+            write_i32(out, z.i_max as i32)?;
+            write_i32(out, z.j_max as i32)?;
+            write_i32(out, z.k_max as i32)?;
+        }
+        TecZone::ClassicFE(z) => {
+            write_i32(out, z.nodes as i32)?;
+            write_i32(out, z.cells as i32)?;
+            for _ in 0..3 {
+                write_i32(out, 0)?;
+            }
+        }
+        TecZone::PolyFE(z) => {
+            write_i32(out, z.nodes as i32)?;
+            write_i32(out, z.faces as i32)?;
+            write_i32(out, z.total_num_face_nodes as i32)?;
+            write_i32(out, z.num_connected_boundary_faces as i32)?;
+            write_i32(out, z.total_num_boundary_connections as i32)?;
+            write_i32(out, z.cells as i32)?;
+            for _ in 0..3 {
+                write_i32(out, 0)?;
+            }
+        }
+    }
+    for (name, value) in zone.aux_data().iter() {
+        write_i32(out, 1)?;
+        write_aux_data(out, name, value)?;
+    }
+    write_i32(out, 0)?;
+    Ok(())
+}
+
+/// Geometry record with coordinates stored in double precision.
+fn write_geometry<W: Write>(out: &mut W, geometry: &Geometry) -> Result<()> {
+    write_f32(out, GEOMETRY_MARKER)?;
+    write_i32(out, geometry.coord_sys as i32)?;
+    write_i32(out, geometry.scope as i32)?;
+    write_i32(out, geometry.draw_order as i32)?;
+    for &v in geometry.anchor.iter() {
+        write_f64(out, v)?;
+    }
+    write_i32(out, geometry.zone.unwrap_or(0))?;
+    write_i32(out, geometry.color)?;
+    write_i32(out, geometry.fill_color.unwrap_or(0))?;
+    write_i32(out, geometry.fill_color.is_some() as i32)?;
+    write_i32(out, geometry.shape.type_id())?;
+    write_i32(out, geometry.line_pattern as i32)?;
+    write_f64(out, geometry.pattern_length)?;
+    write_f64(out, geometry.line_thickness)?;
+    write_i32(out, geometry.num_ellipse_points)?;
+    write_i32(out, geometry.arrowhead_style as i32)?;
+    write_i32(out, geometry.arrowhead_attachment as i32)?;
+    write_f64(out, geometry.arrowhead_size)?;
+    write_f64(out, geometry.arrowhead_angle)?;
+    write_string(out, &geometry.macro_function)?;
+    write_i32(out, TecDataType::F64 as i32)?;
+    write_i32(out, geometry.clipping as i32)?;
+
+    let values = match &geometry.shape {
+        GeometryShape::Lines(lines) => {
+            write_i32(out, lines.len() as i32)?;
+            for line in lines.iter() {
+                write_i32(out, line.x.len() as i32)?;
+                for v in line.x.iter().chain(line.y.iter()).chain(line.z.iter()) {
+                    write_f64(out, *v)?;
+                }
+            }
+            vec![]
+        }
+        GeometryShape::Rectangle { width, height } => vec![*width, *height],
+        GeometryShape::Square { size } => vec![*size],
+        GeometryShape::Circle { radius } => vec![*radius],
+        GeometryShape::Ellipse {
+            horizontal_axis,
+            vertical_axis,
+        } => vec![*horizontal_axis, *vertical_axis],
+    };
+    for v in values {
+        write_f64(out, v)?;
+    }
+    Ok(())
+}
+
+fn write_text<W: Write>(out: &mut W, text: &TextAnnotation) -> Result<()> {
+    write_f32(out, TEXT_MARKER)?;
+    write_i32(out, text.coord_sys as i32)?;
+    write_i32(out, text.scope as i32)?;
+    for &v in text.anchor.iter() {
+        write_f64(out, v)?;
+    }
+    write_i32(out, text.font_id())?;
+    write_i32(out, text.size_units as i32)?;
+    write_f64(out, text.height)?;
+    write_i32(out, text.box_type as i32)?;
+    write_f64(out, text.box_margin)?;
+    write_f64(out, text.box_line_thickness)?;
+    write_i32(out, text.box_color)?;
+    write_i32(out, text.box_fill_color)?;
+    write_f64(out, text.angle)?;
+    write_f64(out, text.line_spacing)?;
+    write_i32(out, text.alignment as i32)?;
+    write_i32(out, text.zone.unwrap_or(0))?;
+    write_i32(out, text.color)?;
+    write_string(out, &text.macro_function)?;
+    write_i32(out, text.clipping as i32)?;
+    write_string(out, &text.text)
+}
+
+/// Name/value pair, values are always stored as strings.
+fn write_aux_data<W: Write>(out: &mut W, name: &str, value: &str) -> Result<()> {
+    write_string(out, name)?;
+    write_i32(out, 0)?;
+    write_string(out, value)
+}
+
+fn write_data_block<W: Write>(out: &mut W, zone: &PltZone) -> Result<()> {
+    let passive = zone.zone.passive_var_list();
+    let is_passive = |var: usize| passive.get(var) == Some(&1);
+    let declared = zone.zone.data_types();
+    let types = zone
+        .data
+        .iter()
+        .enumerate()
+        .map(|(var, data)| {
+            let declared = declared.and_then(|types| types.get(var).copied());
+            match (data.as_ref().and_then(TecData::data_type), declared) {
+                // Bytes are packed into bits for variables declared as such.
+                (Some(TecDataType::I8), Some(TecDataType::I1)) => TecDataType::I1,
+                (Some(ty), _) => ty,
+                (None, declared) => declared.unwrap_or(TecDataType::F32),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    write_f32(out, ZONE_MARKER)?;
+    for &ty in types.iter() {
+        write_i32(out, ty as i32)?;
+    }
+    if passive.contains(&1) {
+        write_i32(out, 1)?;
+        for &p in passive.iter() {
+            write_i32(out, p)?;
+        }
+    } else {
+        write_i32(out, 0)?;
+    }
+    let shared = (1..=types.len())
+        .map(|var| zone.zone.shared_from(var))
+        .collect::<Vec<_>>();
+    if shared.iter().any(|s| s.is_some()) {
+        write_i32(out, 1)?;
+        for s in shared.iter() {
+            write_i32(out, s.map(|z| z as i32 - 1).unwrap_or(-1))?;
+        }
+    } else {
+        write_i32(out, 0)?;
+    }
+    write_i32(
+        out,
+        zone.zone
+            .connectivity_shared_from()
+            .map(|z| z as i32 - 1)
+            .unwrap_or(-1),
+    )?;
+
+    let stored_types = (0..types.len())
+        .filter(|&var| !is_passive(var) && shared[var].is_none())
+        .map(|var| types[var])
+        .collect::<Vec<_>>();
+    let values = zone
+        .data
+        .iter()
+        .zip(zone.zone.var_locs())
+        .enumerate()
+        .filter(|(var, _)| !is_passive(*var) && shared[*var].is_none())
+        .map(|(_, (data, &loc))| {
+            let data = data.clone().expect("unwritten variables are rejected on close");
+            match (&zone.zone, loc) {
+                (TecZone::Ordered(z), ValueLocation::CellCentered) => {
+                    let len = ghost_cell_dims(z).iter().product();
+                    add_ghost_cells(&data, &ghost_cell_indices(z), len)
+                }
+                _ => data,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for data in values.iter() {
+        let (min, max) = min_max(data);
+        write_f64(out, min)?;
+        write_f64(out, max)?;
+    }
+    for (data, &ty) in values.iter().zip(stored_types.iter()) {
+        write_values(out, data, ty)?;
+    }
+
+    if let (TecZone::ClassicFE(z), None) = (&zone.zone, zone.zone.connectivity_shared_from()) {
+        let len = z.num_connections();
+        let nodemap = zone.nodemap.clone().unwrap_or_default();
+        if nodemap.len() != len {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Expected nodemap of {} values for zone {:?}, got {}",
+                    len,
+                    z.name,
+                    nodemap.len()
+                ),
+                code: -1,
+            });
+        }
+        for n in nodemap {
+            write_i32(out, n)?;
+        }
+    }
+    if let (TecZone::PolyFE(z), None) = (&zone.zone, zone.zone.connectivity_shared_from()) {
+        write_poly_faces(out, z, &zone.poly_faces)?;
+    }
+    Ok(())
+}
+
+/// Face, face node, boundary face and boundary connection counts written so far.
+fn face_counts(faces: &PolyFaces) -> [usize; 4] {
+    [
+        faces.face_left_elements.len(),
+        faces.face_nodes.len(),
+        faces.boundary_connection_counts.len(),
+        faces.boundary_connection_elements.len(),
+    ]
+}
+
+/// Checks face counts against the zone header, `complete` once all faces should be written.
+fn check_face_counts(zone: &PolyFE, counts: [usize; 4], complete: bool) -> Result<()> {
+    let expected = [
+        (zone.faces, "faces"),
+        (zone.total_num_face_nodes, "face nodes"),
+        (zone.num_connected_boundary_faces, "boundary faces"),
+        (zone.total_num_boundary_connections, "boundary connections"),
+    ];
+    for (&len, &(expected, what)) in counts.iter().zip(expected.iter()) {
+        let len = len as i64;
+        if len > expected || (complete && len != expected) {
+            return Err(TecioError::Other {
+                message: format!(
+                    "Expected {} {} for zone {:?}, got {}",
+                    expected, what, zone.name, len
+                ),
+                code: -1,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Writes face data zero-based, with node and boundary connection counts turned into offsets.
+fn write_poly_faces<W: Write>(out: &mut W, zone: &PolyFE, faces: &PolyFaces) -> Result<()> {
+    let write_offsets = |out: &mut W, counts: &[i32]| -> Result<()> {
+        let mut offset = 0;
+        write_i32(out, offset)?;
+        for &c in counts {
+            offset += c;
+            write_i32(out, offset)?;
+        }
+        Ok(())
+    };
+    let write_zero_based = |out: &mut W, values: &[i64]| -> Result<()> {
+        for &v in values {
+            write_i32(out, v as i32 - 1)?;
+        }
+        Ok(())
+    };
+
+    if zone.zone_type == ZoneType::FEPolyhedron {
+        write_offsets(out, &faces.face_node_counts)?;
+    }
+    write_zero_based(out, &faces.face_nodes)?;
+    write_zero_based(out, &faces.face_left_elements)?;
+    write_zero_based(out, &faces.face_right_elements)?;
+    if zone.num_connected_boundary_faces > 0 {
+        write_offsets(out, &faces.boundary_connection_counts)?;
+        write_zero_based(out, &faces.boundary_connection_elements)?;
+        for &z in faces.boundary_connection_zones.iter() {
+            write_i32(out, z - 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// Index values shifted by `offset`, integer data only.
+fn indices(data: TecData, offset: i64, what: &str) -> Result<Vec<i64>> {
+    match data {
+        TecData::I32(data) => Ok(data.iter().map(|&n| n as i64 + offset).collect()),
+        TecData::I64(data) => Ok(data.iter().map(|&n| n + offset).collect()),
+        _ => Err(TecioError::Other {
+            message: format!("Unsupported datatype for {}!", what),
+            code: -1,
+        }),
+    }
+}
+
+fn add_ghost_cells(data: &TecData, indices: &[usize], len: usize) -> TecData<'static> {
+    macro_rules! add {
+        ($v: expr, $zero: expr) => {{
+            let mut out = vec![$zero; len];
+            for (&i, &value) in indices.iter().zip($v.iter()) {
+                out[i] = value;
+            }
+            TecData::from(out)
+        }};
+    }
+    match data {
+        TecData::F64(v) => add!(v, 0.0),
+        TecData::F32(v) => add!(v, 0.0),
+        TecData::I32(v) => add!(v, 0),
+        TecData::I16(v) => add!(v, 0),
+        TecData::I8(v) => add!(v, 0),
+        _ => unreachable!(),
+    }
+}
+
+fn min_max(data: &TecData) -> (f64, f64) {
+    macro_rules! min_max {
+        ($v: expr) => {
+            min_max!($v, f64)
+        };
+        ($v: expr, $ty: ty) => {
+            $v.iter()
+                .map(|&v| v as $ty as f64)
+                .fold(None, |acc: Option<(f64, f64)>, v| match acc {
+                    Some((min, max)) => Some((min.min(v), max.max(v))),
+                    None => Some((v, v)),
+                })
+                .unwrap_or((0.0, 0.0))
+        };
+    }
+    match data {
+        TecData::F64(v) => min_max!(v),
+        TecData::F32(v) => min_max!(v),
+        TecData::I32(v) => min_max!(v),
+        TecData::I16(v) => min_max!(v),
+        // Bytes are unsigned in the file.
+        TecData::I8(v) => min_max!(v, u8),
+        _ => unreachable!(),
+    }
+}
+
+/// Writes values little endian, bits are packed eight to a byte starting with the lowest bit.
+fn write_values<W: Write>(out: &mut W, data: &TecData, ty: TecDataType) -> Result<()> {
+    if let (TecData::I8(v), TecDataType::I1) = (data, ty) {
+        for bits in v.chunks(8) {
+            let byte = bits.iter().enumerate().fold(0u8, |byte, (i, &bit)| {
+                byte | ((bit != 0) as u8) << i
+            });
+            out.write_all(&[byte])?;
+        }
+        return Ok(());
+    }
+    macro_rules! write {
+        ($v: expr) => {
+            for v in $v.iter() {
+                out.write_all(&v.to_le_bytes())?;
+            }
+        };
+    }
+    match data {
+        TecData::F64(v) => write!(v),
+        TecData::F32(v) => write!(v),
+        TecData::I32(v) => write!(v),
+        TecData::I16(v) => write!(v),
+        TecData::I8(v) => write!(v),
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+fn write_string<W: Write>(out: &mut W, s: &str) -> Result<()> {
+    for b in s.bytes() {
+        write_i32(out, b as i32)?;
+    }
+    write_i32(out, 0)
+}
+
+fn write_i32<W: Write>(out: &mut W, v: i32) -> Result<()> {
+    out.write_all(&v.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_f32<W: Write>(out: &mut W, v: f32) -> Result<()> {
+    out.write_all(&v.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_f64<W: Write>(out: &mut W, v: f64) -> Result<()> {
+    out.write_all(&v.to_le_bytes())?;
+    Ok(())
+}